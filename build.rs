@@ -1,7 +1,9 @@
 fn main() {
     // Note: We're using the wayland-protocols-wlr crate which already provides
     // the generated bindings for wlr-layer-shell and wlr-screencopy protocols.
-    // If custom protocol generation is needed, wayland-scanner can be used.
+    // The ext image capture bindings are generated in src/protocols by
+    // wayland-scanner's macros, which read the XML in protocols/ without
+    // telling cargo, so rebuild whenever it changes.
 
     println!("cargo:rerun-if-changed=protocols/");
 }
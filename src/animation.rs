@@ -0,0 +1,120 @@
+//! Eased transitions for zoom and lens size.
+//!
+//! A transition remembers where it started, where it is going and when it
+//! began; its current value is worked out from the clock whenever a frame is
+//! drawn. Frames are paced by the compositor's frame callbacks, so nothing
+//! here runs on a timer.
+
+use crate::config::Easing;
+use std::ops::{Add, Mul, Sub};
+use std::time::{Duration, Instant};
+
+/// A value that moves to new targets over a fixed duration.
+#[derive(Debug, Clone, Copy)]
+pub struct Animated<T> {
+    from: T,
+    to: T,
+    started: Instant,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl<T> Animated<T>
+where
+    T: Copy + PartialEq + Add<Output = T> + Sub<Output = T> + Mul<f64, Output = T>,
+{
+    /// A value resting at `value`. A zero `duration` makes every change
+    /// take effect immediately.
+    pub fn new(value: T, duration: Duration, easing: Easing) -> Self {
+        Self {
+            from: value,
+            to: value,
+            started: Instant::now(),
+            duration,
+            easing,
+        }
+    }
+
+    /// Start moving toward `target` from wherever the value is at `now`.
+    pub fn set(&mut self, target: T, now: Instant) {
+        self.from = self.value_at(now);
+        self.to = target;
+        self.started = now;
+    }
+
+    /// The value the transition is heading for.
+    pub fn target(&self) -> T {
+        self.to
+    }
+
+    pub fn value_at(&self, now: Instant) -> T {
+        let t = self.progress(now);
+        if t >= 1.0 {
+            return self.to;
+        }
+        self.from + (self.to - self.from) * self.easing.apply(t)
+    }
+
+    /// Whether the value is still changing at `now`.
+    pub fn is_running(&self, now: Instant) -> bool {
+        self.from != self.to && self.progress(now) < 1.0
+    }
+
+    /// Fraction of the duration elapsed at `now`.
+    fn progress(&self, now: Instant) -> f64 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        let elapsed = now.saturating_duration_since(self.started);
+        (elapsed.as_secs_f64() / self.duration.as_secs_f64()).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::Vector2D;
+
+    #[test]
+    fn test_easing_curves() {
+        for easing in [Easing::Linear, Easing::EaseOut, Easing::EaseInOut] {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+        }
+        assert_eq!(Easing::Linear.apply(0.25), 0.25);
+        // Ease-out covers most of the distance early
+        assert!(Easing::EaseOut.apply(0.5) > 0.8);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert!(Easing::EaseInOut.apply(0.25) < 0.25);
+    }
+
+    #[test]
+    fn test_transition() {
+        let start = Instant::now();
+        let duration = Duration::from_millis(200);
+        let mut zoom = Animated::new(0.5, duration, Easing::Linear);
+        assert!(!zoom.is_running(start));
+
+        zoom.set(0.25, start);
+        assert_eq!(zoom.target(), 0.25);
+        assert_eq!(zoom.value_at(start), 0.5);
+        assert_eq!(zoom.value_at(start + duration / 2), 0.375);
+        assert!(zoom.is_running(start + duration / 2));
+        assert_eq!(zoom.value_at(start + duration), 0.25);
+        assert!(!zoom.is_running(start + duration));
+
+        // Retargeting mid-way continues from the current value
+        let mid = start + duration / 2;
+        zoom.set(0.5, mid);
+        assert_eq!(zoom.value_at(mid), 0.375);
+    }
+
+    #[test]
+    fn test_zero_duration_jumps() {
+        let now = Instant::now();
+        let mut size = Animated::new(Vector2D::new(300.0, 150.0), Duration::ZERO, Easing::EaseOut);
+        size.set(Vector2D::new(330.0, 165.0), now);
+        assert_eq!(size.value_at(now), Vector2D::new(330.0, 165.0));
+        assert!(!size.is_running(now));
+    }
+}
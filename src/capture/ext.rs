@@ -0,0 +1,321 @@
+//! Capturing with ext-image-copy-capture.
+//!
+//! Each monitor gets a capture session that lives as long as we do. The
+//! session announces the buffer size and formats it accepts, then every
+//! capture is a frame of that session copied into a buffer of ours. Frames
+//! report which parts of the output changed since the session's previous
+//! frame, so live captures only need to refresh those.
+//!
+//! After its first frame a session may hold a capture back until something
+//! on the output changes, so a frame of a still screen can take a while.
+
+use super::{Backend, BufferSpec, FrameRequest, Started};
+use crate::pixel_format::PixelFormat;
+use crate::protocols::ext_image_capture_source::{
+    ext_image_capture_source_v1::ExtImageCaptureSourceV1,
+    ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1,
+};
+use crate::protocols::ext_image_copy_capture::{
+    ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1,
+    ext_image_copy_capture_manager_v1::{ExtImageCopyCaptureManagerV1, Options},
+    ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1,
+};
+use crate::utils::{Rect, Vector2D};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use wayland_client::protocol::wl_buffer::WlBuffer;
+use wayland_client::protocol::wl_output::Transform;
+use wayland_client::protocol::wl_shm::Format;
+use wayland_client::{Dispatch, Proxy, QueueHandle};
+
+/// Formats we take when offered, best first.
+const PREFERRED_FORMATS: [Format; 2] = [Format::Argb8888, Format::Xrgb8888];
+
+/// A monitor's capture session and the constraints it announced.
+pub struct SessionData {
+    pub monitor: usize,
+    state: Mutex<SessionState>,
+}
+
+#[derive(Debug, Default)]
+struct SessionState {
+    size: Option<Vector2D>,
+    formats: Vec<u32>,
+    /// Buffer to use, as of the last `done`
+    spec: Option<BufferSpec>,
+    /// A frame waits for the constraints
+    wanted: bool,
+    stopped: bool,
+}
+
+impl SessionData {
+    fn new(monitor: usize) -> Self {
+        Self {
+            monitor,
+            state: Mutex::new(SessionState::default()),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, SessionState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn buffer_size(&self, width: u32, height: u32) {
+        self.state().size = Some(Vector2D::new(width as f64, height as f64));
+    }
+
+    pub fn shm_format(&self, format: u32) {
+        self.state().formats.push(format);
+    }
+
+    /// The constraints are complete; returns the buffer a waiting frame
+    /// needs, if one is waiting.
+    pub fn done(&self) -> Option<BufferSpec> {
+        let mut state = self.state();
+        let formats = std::mem::take(&mut state.formats);
+        state.spec = match (state.size, pick_format(&formats)) {
+            (Some(size), Some((format, pixel))) => Some(BufferSpec {
+                format,
+                size,
+                stride: size.x as u32 * pixel.bytes_per_pixel() as u32,
+            }),
+            _ => None,
+        };
+        if state.spec.is_none() {
+            log::warn!(
+                "Capture session of monitor {} offers no usable shm format",
+                self.monitor
+            );
+        }
+        if state.wanted && state.spec.is_some() {
+            state.wanted = false;
+            return state.spec;
+        }
+        None
+    }
+
+    /// The session ended; returns whether a frame was waiting on it.
+    pub fn stop(&self) -> bool {
+        let mut state = self.state();
+        state.stopped = true;
+        std::mem::take(&mut state.wanted)
+    }
+
+    /// Ask for a frame: the buffer it needs, or None to wait for `done`.
+    fn request(&self) -> Option<BufferSpec> {
+        let mut state = self.state();
+        if state.spec.is_none() {
+            state.wanted = true;
+        }
+        state.spec
+    }
+
+    fn spec(&self) -> Option<BufferSpec> {
+        self.state().spec
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.state().stopped
+    }
+}
+
+/// A frame of a session and the damage and transform it reported.
+pub struct FrameData {
+    pub monitor: usize,
+    damage: Mutex<Vec<Rect>>,
+    transform: Mutex<Option<Transform>>,
+}
+
+impl FrameData {
+    fn new(monitor: usize) -> Self {
+        Self {
+            monitor,
+            damage: Mutex::new(Vec::new()),
+            transform: Mutex::new(None),
+        }
+    }
+
+    pub fn set_transform(&self, transform: Transform) {
+        *self.transform.lock().unwrap_or_else(|e| e.into_inner()) = Some(transform);
+    }
+
+    /// Transform of the buffer contents, as the output's would be, if the
+    /// frame reported one.
+    pub fn transform(&self) -> Option<Transform> {
+        *self.transform.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn damage(&self, x: i32, y: i32, width: i32, height: i32) {
+        let rect = Rect::new(
+            Vector2D::new(x as f64, y as f64),
+            Vector2D::new(width as f64, height as f64),
+        );
+        self.damage
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(rect);
+    }
+
+    /// Parts of the output that changed since the session's last frame, in
+    /// capture pixels.
+    pub fn take_damage(&self) -> Vec<Rect> {
+        std::mem::take(&mut self.damage.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+}
+
+/// The ext-image-copy-capture backend.
+pub struct ExtImageCopy {
+    sources: ExtOutputImageCaptureSourceManagerV1,
+    manager: ExtImageCopyCaptureManagerV1,
+    sessions: HashMap<usize, ExtImageCopyCaptureSessionV1>,
+}
+
+impl ExtImageCopy {
+    pub fn new(
+        sources: ExtOutputImageCaptureSourceManagerV1,
+        manager: ExtImageCopyCaptureManagerV1,
+    ) -> Self {
+        Self {
+            sources,
+            manager,
+            sessions: HashMap::new(),
+        }
+    }
+}
+
+impl<D> Backend<D> for ExtImageCopy
+where
+    D: Dispatch<ExtImageCaptureSourceV1, ()>
+        + Dispatch<ExtImageCopyCaptureSessionV1, SessionData>
+        + Dispatch<ExtImageCopyCaptureFrameV1, FrameData>
+        + 'static,
+{
+    fn name(&self) -> &'static str {
+        "ext-image-copy-capture"
+    }
+
+    fn supports_regions(&self) -> bool {
+        false
+    }
+
+    fn capture(&mut self, request: &FrameRequest, qh: &QueueHandle<D>) -> Result<Started> {
+        let live = self
+            .sessions
+            .get(&request.monitor)
+            .filter(|s| s.data::<SessionData>().is_some_and(|d| !d.is_stopped()))
+            .cloned();
+        let session = match live {
+            Some(session) => session,
+            None => {
+                if let Some(stopped) = self.sessions.remove(&request.monitor) {
+                    stopped.destroy();
+                }
+                let options = if request.cursor {
+                    Options::PaintCursors
+                } else {
+                    Options::empty()
+                };
+                let source = self.sources.create_source(request.output, qh, ());
+                let session = self.manager.create_session(
+                    &source,
+                    options,
+                    qh,
+                    SessionData::new(request.monitor),
+                );
+                // The session keeps capturing the output without it
+                source.destroy();
+                self.sessions.insert(request.monitor, session.clone());
+                session
+            }
+        };
+
+        let data = session
+            .data::<SessionData>()
+            .context("Capture session without data")?;
+        Ok(match data.request() {
+            Some(spec) => Started::NeedsBuffer(spec),
+            None => Started::Pending,
+        })
+    }
+
+    fn attach(&mut self, monitor: usize, buffer: &WlBuffer, qh: &QueueHandle<D>) {
+        let Some(session) = self.sessions.get(&monitor) else {
+            return;
+        };
+        let Some(spec) = session.data::<SessionData>().and_then(|d| d.spec()) else {
+            return;
+        };
+
+        let frame = session.create_frame(qh, FrameData::new(monitor));
+        frame.attach_buffer(buffer);
+        // Our buffers are reused and may hold anything, so have all of it
+        // written; the damage events still say what actually changed
+        frame.damage_buffer(0, 0, spec.size.x as i32, spec.size.y as i32);
+        frame.capture();
+    }
+}
+
+/// Format to use among those a session `offered`: one Cairo can use in
+/// place if possible, else any we can convert.
+fn pick_format(offered: &[u32]) -> Option<(u32, PixelFormat)> {
+    PREFERRED_FORMATS
+        .iter()
+        .map(|&f| u32::from(f))
+        .find(|f| offered.contains(f))
+        .into_iter()
+        .chain(offered.iter().copied())
+        .find_map(|f| Some((f, PixelFormat::from_shm(f)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_format() {
+        let xrgb = u32::from(Format::Xrgb8888);
+        let abgr = u32::from(Format::Abgr8888);
+        let yuyv = u32::from(Format::Yuyv);
+        assert_eq!(
+            pick_format(&[abgr, xrgb]),
+            Some((xrgb, PixelFormat::Xrgb8888))
+        );
+        assert_eq!(
+            pick_format(&[yuyv, abgr]),
+            Some((abgr, PixelFormat::Abgr8888))
+        );
+        assert_eq!(pick_format(&[yuyv]), None);
+        assert_eq!(pick_format(&[]), None);
+    }
+
+    #[test]
+    fn test_session_constraints() {
+        let session = SessionData::new(1);
+        // Nothing known yet: the frame waits for the constraints
+        assert!(session.request().is_none());
+
+        session.buffer_size(1920, 1080);
+        session.shm_format(u32::from(Format::Xrgb8888));
+        let spec = session.done().unwrap();
+        assert_eq!(spec.size, Vector2D::new(1920.0, 1080.0));
+        assert_eq!(spec.stride, 1920 * 4);
+
+        // Known constraints are used at once, and updated ones with
+        // nothing waiting ask for nothing
+        assert_eq!(session.request(), Some(spec));
+        session.buffer_size(1920, 1080);
+        session.shm_format(u32::from(Format::Argb8888));
+        assert!(session.done().is_none());
+        assert_eq!(session.spec().unwrap().format, u32::from(Format::Argb8888));
+
+        // Stride follows the format's pixel size
+        session.buffer_size(1920, 1080);
+        session.shm_format(u32::from(Format::Bgr888));
+        session.done();
+        assert_eq!(session.spec().unwrap().stride, 1920 * 3);
+
+        assert!(!session.stop());
+        assert!(session.is_stopped());
+    }
+}
@@ -0,0 +1,104 @@
+//! Capturing from a static image file.
+//!
+//! Every capture of every monitor is the same PNG, stretched over the
+//! output like a screenshot of it would be. Handy for demos and for trying
+//! effects on a known picture without a compositor that can capture.
+
+use super::{Backend, FrameRequest, Started};
+use crate::effects::Image;
+use anyhow::{Context, Result};
+use cairo::{Format, ImageSurface};
+use std::fs::File;
+use std::path::Path;
+use wayland_client::QueueHandle;
+
+/// The image file backend.
+pub struct ImageFile {
+    image: Image,
+}
+
+impl ImageFile {
+    /// Load the PNG at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(Self {
+            image: read_png(path)?,
+        })
+    }
+}
+
+impl<D> Backend<D> for ImageFile {
+    fn name(&self) -> &'static str {
+        "image"
+    }
+
+    fn supports_regions(&self) -> bool {
+        false
+    }
+
+    fn capture(&mut self, _request: &FrameRequest, _qh: &QueueHandle<D>) -> Result<Started> {
+        Ok(Started::Image(self.image.clone()))
+    }
+}
+
+/// Read a PNG as premultiplied ARGB32, whatever its own format.
+fn read_png(path: &Path) -> Result<Image> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let png = ImageSurface::create_from_png(&mut file)
+        .with_context(|| format!("Failed to read {} as PNG", path.display()))?;
+
+    let mut surface = ImageSurface::create(Format::ARgb32, png.width(), png.height())?;
+    {
+        let ctx = cairo::Context::new(&surface)?;
+        ctx.set_source_surface(&png, 0.0, 0.0)?;
+        ctx.paint()?;
+    }
+    surface.flush();
+
+    let (width, height) = (surface.width() as usize, surface.height() as usize);
+    let stride = surface.stride() as usize;
+    let data = surface.data()?;
+    let pixels = (0..height)
+        .flat_map(|y| (0..width).map(move |x| y * stride + x * 4))
+        .map(|i| u32::from_ne_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]))
+        .collect();
+
+    Ok(Image {
+        width,
+        height,
+        pixels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_png() {
+        let path =
+            std::env::temp_dir().join(format!("remagnify-capture-{}.png", std::process::id()));
+        let expected = Image {
+            width: 3,
+            height: 2,
+            pixels: vec![
+                0xffff_0000,
+                0xff00_ff00,
+                0xff00_00ff,
+                0xff10_2030,
+                0x8000_0000,
+                0x0000_0000,
+            ],
+        };
+        let surface = expected.clone().into_surface().unwrap();
+        surface
+            .write_to_png(&mut File::create(&path).unwrap())
+            .unwrap();
+
+        let image = read_png(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(image, expected);
+
+        assert!(read_png(Path::new("/nonexistent/remagnify.png")).is_err());
+    }
+}
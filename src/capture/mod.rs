@@ -0,0 +1,320 @@
+//! Screen captures and the part of the output they hold.
+//!
+//! A capture is either the whole output or, with `--capture-region`, only
+//! the area around what the views read from. Either way positions are
+//! given in pixels of the whole output, so the renderer need not care
+//! which it has; a region capture simply knows where its top-left lies.
+//! Reads outside a region repeat its edge until the pointer's move has
+//! fetched a new one.
+//!
+//! Capture pixels are also upright, however the buffer itself lies: the
+//! capture's [`Orientation`] takes reads and the renderer's source matrix
+//! from the picture the user sees to the buffer of a rotated, flipped or
+//! y-inverted frame.
+//!
+//! Frames come from a [`Backend`]: wlr-screencopy, the standard
+//! ext-image-copy-capture, or a static image file. The protocol backends
+//! answer through the Dispatch events of the objects they create, which
+//! hand the frame to the app state the same way whichever protocol it
+//! came through.
+
+pub mod ext;
+pub mod image;
+mod orientation;
+pub mod wlr;
+
+pub use orientation::Orientation;
+
+use crate::config::CaptureBackend;
+use crate::effects::Image;
+use crate::pool_buffer::PoolBuffer;
+use crate::utils::{Rect, Vector2D};
+use anyhow::Result;
+use cairo::{Extend, Matrix, SurfacePattern};
+use wayland_client::protocol::wl_buffer::WlBuffer;
+use wayland_client::protocol::wl_output::WlOutput;
+use wayland_client::QueueHandle;
+
+/// Capture pixels fetched around the areas the views read from, so the
+/// pointer can move a little before a new region is needed.
+pub const REGION_MARGIN: f64 = 256.0;
+
+/// Captured pixels of (part of) an output.
+pub struct Capture {
+    pub buffer: PoolBuffer,
+    /// Where the buffer's top-left lies on the output, in capture pixels
+    pub origin: Vector2D,
+    /// Size of the whole output in capture pixels
+    pub pixel_size: Vector2D,
+    /// How the buffer lies relative to the upright output
+    pub orientation: Orientation,
+}
+
+impl Capture {
+    /// A capture of the whole output, its buffer lying as `orientation`
+    /// says.
+    pub fn oriented(buffer: PoolBuffer, orientation: Orientation) -> Self {
+        Self {
+            origin: Vector2D::default(),
+            pixel_size: orientation.upright_size(buffer.pixel_size),
+            orientation,
+            buffer,
+        }
+    }
+
+    /// Take the `orientation` a frame arrived with.
+    pub fn set_orientation(&mut self, orientation: Orientation) {
+        let full = !self.is_partial();
+        self.orientation = orientation;
+        if full {
+            self.pixel_size = self.area().size;
+        }
+    }
+
+    /// Part of the output the buffer holds, in capture pixels.
+    pub fn area(&self) -> Rect {
+        Rect::new(
+            self.origin,
+            self.orientation.upright_size(self.buffer.pixel_size),
+        )
+    }
+
+    /// Whether the buffer holds less than the whole output.
+    pub fn is_partial(&self) -> bool {
+        self.area() != Rect::new(Vector2D::default(), self.pixel_size)
+    }
+
+    /// Whether the buffer holds all of `area` that lies on the output.
+    pub fn covers(&self, area: &Rect) -> bool {
+        let area = area.intersect(&Rect::new(Vector2D::default(), self.pixel_size));
+        area.is_empty() || self.area().covers(&area)
+    }
+
+    /// Copy of `rect`, given in output capture pixels.
+    ///
+    /// Parts of `rect` outside the buffer repeat the nearest edge pixel.
+    pub fn crop(&mut self, rect: Rect) -> Image {
+        let local = Rect::new(rect.pos - self.origin, rect.size);
+        if self.orientation.is_identity() {
+            return Image::crop(&mut self.buffer, local);
+        }
+
+        let (x0, y0, width, height) = local.to_pixels();
+        let (width, height) = (width.max(1) as usize, height.max(1) as usize);
+        let size = self.area().size;
+        let (max_x, max_y) = ((size.x as i32 - 1).max(0), (size.y as i32 - 1).max(0));
+        let mut image = Image::new(width, height, 0);
+        for y in 0..height {
+            for x in 0..width {
+                let px = (x0 + x as i32).clamp(0, max_x);
+                let py = (y0 + y as i32).clamp(0, max_y);
+                let (bx, by) = self
+                    .orientation
+                    .buffer_pixel(self.buffer.pixel_size, px, py);
+                image.pixels[y * width + x] = self.buffer.pixel_at(bx, by).unwrap_or(0);
+            }
+        }
+        image
+    }
+
+    /// Copy `area` of `frame`, in output capture pixels, over this
+    /// capture. Both must lie the same way.
+    pub fn copy_from(&mut self, frame: &mut Capture, area: Rect) {
+        let area = area.intersect(&frame.area()).intersect(&self.area());
+        if area.is_empty() {
+            return;
+        }
+        let to_buffer = |capture: &Capture| {
+            capture.orientation.buffer_rect(
+                capture.buffer.pixel_size,
+                Rect::new(area.pos - capture.origin, area.size),
+            )
+        };
+        let (source, (x, y, _, _)) = (to_buffer(frame), to_buffer(self).to_pixels());
+        Image::crop(&mut frame.buffer, source).paste(&mut self.buffer, x, y);
+    }
+
+    /// Pixel at `(x, y)` in output capture pixels, if the buffer holds it.
+    pub fn pixel_at(&self, x: i32, y: i32) -> Option<u32> {
+        let (x, y) = (x - self.origin.x as i32, y - self.origin.y as i32);
+        let size = self.area().size;
+        if x < 0 || y < 0 || x as f64 >= size.x || y as f64 >= size.y {
+            return None;
+        }
+        let (x, y) = self.orientation.buffer_pixel(self.buffer.pixel_size, x, y);
+        self.buffer.pixel_at(x, y)
+    }
+
+    /// Pattern of the capture; `matrix` maps user space to output capture
+    /// pixels, and the capture's orientation takes those on to the buffer.
+    pub fn pattern(&mut self, matrix: Matrix) -> Result<SurfacePattern> {
+        let partial = self.is_partial();
+        let shift = Matrix::new(1.0, 0.0, 0.0, 1.0, -self.origin.x, -self.origin.y);
+        let to_buffer = self.orientation.matrix(self.buffer.pixel_size);
+        let pattern = SurfacePattern::create(self.buffer.get_cairo_surface()?);
+        pattern.set_matrix(Matrix::multiply(
+            &Matrix::multiply(&matrix, &shift),
+            &to_buffer,
+        ));
+        if partial {
+            pattern.set_extend(Extend::Pad);
+        }
+        Ok(pattern)
+    }
+}
+
+/// A frame to capture.
+pub struct FrameRequest<'a> {
+    pub monitor: usize,
+    pub output: &'a WlOutput,
+    /// Only this part of the output, in capture pixels
+    pub region: Option<Rect>,
+    /// Capture pixels per logical pixel
+    pub to_capture: Vector2D,
+    /// Paint the compositor's cursor into the frame
+    pub cursor: bool,
+}
+
+/// Shape of the buffer a frame is copied into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BufferSpec {
+    /// wl_shm format code
+    pub format: u32,
+    pub size: Vector2D,
+    pub stride: u32,
+}
+
+/// How a capture got under way.
+pub enum Started {
+    /// The backend's Dispatch events will deliver the frame
+    Pending,
+    /// The frame needs a buffer of this shape, passed to [`Backend::attach`]
+    NeedsBuffer(BufferSpec),
+    /// The pixels are already here
+    Image(Image),
+}
+
+/// A source of screen captures.
+pub trait Backend<D> {
+    /// Name for logs and `--capture-backend`
+    fn name(&self) -> &'static str;
+
+    /// Whether a [`FrameRequest::region`] is honoured; otherwise whole
+    /// outputs are captured.
+    fn supports_regions(&self) -> bool;
+
+    /// Start capturing a frame.
+    fn capture(&mut self, request: &FrameRequest, qh: &QueueHandle<D>) -> Result<Started>;
+
+    /// Copy the frame waiting on `monitor` into `buffer`, once asked for
+    /// with [`Started::NeedsBuffer`] or by the backend's events.
+    fn attach(&mut self, _monitor: usize, _buffer: &WlBuffer, _qh: &QueueHandle<D>) {}
+}
+
+/// Backend to use for `choice`, given which protocols the compositor
+/// offers. Automatic selection prefers the standard ext-image-copy-capture,
+/// unless captures should be limited to regions and wlr-screencopy, which
+/// can do that, is there too.
+pub fn choose(
+    choice: CaptureBackend,
+    wlr: bool,
+    ext: bool,
+    regions: bool,
+) -> Result<CaptureBackend> {
+    match choice {
+        CaptureBackend::Auto if ext && !(regions && wlr) => Ok(CaptureBackend::ExtImageCopy),
+        CaptureBackend::Auto if wlr => Ok(CaptureBackend::WlrScreencopy),
+        CaptureBackend::Auto => anyhow::bail!(
+            "Screen capture not available - your compositor supports neither \
+             wlr-screencopy nor ext-image-copy-capture"
+        ),
+        CaptureBackend::WlrScreencopy if !wlr => {
+            anyhow::bail!(
+                "Screencopy not available - your compositor doesn't support wlr-screencopy"
+            )
+        }
+        CaptureBackend::ExtImageCopy if !ext => anyhow::bail!(
+            "Image copy capture not available - your compositor doesn't support \
+             ext-image-copy-capture"
+        ),
+        choice => Ok(choice),
+    }
+}
+
+/// Region of an output of `full` capture pixels to fetch so the `areas`
+/// are covered with room to move.
+pub fn region(areas: &[Rect], full: Vector2D) -> Option<Rect> {
+    let bounds = areas.iter().copied().reduce(|a, b| a.union(&b))?;
+    let region = bounds
+        .inflate(REGION_MARGIN)
+        .intersect(&Rect::new(Vector2D::default(), full));
+    (!region.is_empty()).then_some(region)
+}
+
+/// Whole logical pixels `(x, y, width, height)` to request for a `region`
+/// of capture pixels, and where the frame's top-left will lie in capture
+/// pixels.
+pub fn logical_region(region: Rect, to_capture: Vector2D) -> ((i32, i32, i32, i32), Vector2D) {
+    let logical = Rect::new(region.pos / to_capture, region.size / to_capture).to_pixels();
+    let origin = (Vector2D::new(logical.0 as f64, logical.1 as f64) * to_capture).round();
+    (logical, origin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(x: f64, y: f64, w: f64, h: f64) -> Rect {
+        Rect::new(Vector2D::new(x, y), Vector2D::new(w, h))
+    }
+
+    #[test]
+    fn test_region() {
+        let full = Vector2D::new(3840.0, 2160.0);
+        let both = region(
+            &[
+                area(1000.0, 1000.0, 100.0, 100.0),
+                area(1200.0, 900.0, 50.0, 50.0),
+            ],
+            full,
+        )
+        .unwrap();
+        assert_eq!(both, area(744.0, 644.0, 762.0, 712.0));
+
+        // Clipped to the output
+        let corner = region(&[area(-50.0, -50.0, 100.0, 100.0)], full).unwrap();
+        assert_eq!(corner, area(0.0, 0.0, 306.0, 306.0));
+
+        assert!(region(&[], full).is_none());
+        assert!(region(&[area(5000.0, 5000.0, 10.0, 10.0)], full).is_none());
+    }
+
+    #[test]
+    fn test_choose() {
+        use CaptureBackend::*;
+
+        assert_eq!(choose(Auto, true, true, false).unwrap(), ExtImageCopy);
+        assert_eq!(choose(Auto, true, false, false).unwrap(), WlrScreencopy);
+        // Regions need wlr-screencopy, but ext will do without it
+        assert_eq!(choose(Auto, true, true, true).unwrap(), WlrScreencopy);
+        assert_eq!(choose(Auto, false, true, true).unwrap(), ExtImageCopy);
+        assert!(choose(Auto, false, false, false).is_err());
+
+        assert_eq!(
+            choose(WlrScreencopy, true, true, false).unwrap(),
+            WlrScreencopy
+        );
+        assert!(choose(ExtImageCopy, true, false, false).is_err());
+        // An image file needs no protocol at all
+        assert_eq!(choose(Image, false, false, false).unwrap(), Image);
+    }
+
+    #[test]
+    fn test_logical_region() {
+        // 1.5x scale: logical pixels are widened to whole ones
+        let scale = Vector2D::new(1.5, 1.5);
+        let (logical, origin) = logical_region(area(100.0, 31.0, 200.0, 100.0), scale);
+        assert_eq!(logical, (66, 20, 134, 68));
+        assert_eq!(origin, Vector2D::new(99.0, 30.0));
+    }
+}
@@ -0,0 +1,202 @@
+//! How a captured buffer lies relative to the output as it is seen.
+//!
+//! Compositors capture the output's framebuffer, which for a rotated or
+//! flipped monitor is not the upright picture the user sees, and
+//! wlr-screencopy may store it bottom row first (`Y_INVERT`). Capture
+//! pixels everywhere else are upright; an [`Orientation`] maps them to the
+//! buffer, following the wl_output transform as wlroots applies it.
+
+use crate::utils::{Rect, Vector2D};
+use cairo::Matrix;
+use wayland_client::protocol::wl_output::Transform;
+
+/// Transform and row order of a captured buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Orientation {
+    /// wl_output transform of the captured output
+    pub transform: Transform,
+    /// Rows are stored bottom first
+    pub y_invert: bool,
+}
+
+impl Default for Orientation {
+    fn default() -> Self {
+        Self {
+            transform: Transform::Normal,
+            y_invert: false,
+        }
+    }
+}
+
+impl Orientation {
+    pub fn new(transform: Transform, y_invert: bool) -> Self {
+        Self {
+            transform,
+            y_invert,
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Whether the buffer's width is the picture's height.
+    fn swaps_axes(&self) -> bool {
+        matches!(
+            self.transform,
+            Transform::_90 | Transform::_270 | Transform::Flipped90 | Transform::Flipped270
+        )
+    }
+
+    /// Upright size of a picture stored in a `buffer` of this size.
+    pub fn upright_size(&self, buffer: Vector2D) -> Vector2D {
+        if self.swaps_axes() {
+            Vector2D::new(buffer.y, buffer.x)
+        } else {
+            buffer
+        }
+    }
+
+    /// Matrix from upright pixels to pixels of a `buffer` of this size.
+    pub fn matrix(&self, buffer: Vector2D) -> Matrix {
+        let Vector2D { x: w, y: h } = self.upright_size(buffer);
+        // The inverse of the output transform, as wlr_box_transform has it
+        let (xx, yx, xy, yy, x0, y0) = match self.transform {
+            Transform::_90 => (0.0, -1.0, 1.0, 0.0, 0.0, w),
+            Transform::_180 => (-1.0, 0.0, 0.0, -1.0, w, h),
+            Transform::_270 => (0.0, 1.0, -1.0, 0.0, h, 0.0),
+            Transform::Flipped => (-1.0, 0.0, 0.0, 1.0, w, 0.0),
+            Transform::Flipped90 => (0.0, -1.0, -1.0, 0.0, h, w),
+            Transform::Flipped180 => (1.0, 0.0, 0.0, -1.0, 0.0, h),
+            Transform::Flipped270 => (0.0, 1.0, 1.0, 0.0, 0.0, 0.0),
+            _ => (1.0, 0.0, 0.0, 1.0, 0.0, 0.0),
+        };
+        let rotate = Matrix::new(xx, yx, xy, yy, x0, y0);
+        if self.y_invert {
+            let invert = Matrix::new(1.0, 0.0, 0.0, -1.0, 0.0, buffer.y);
+            Matrix::multiply(&rotate, &invert)
+        } else {
+            rotate
+        }
+    }
+
+    /// Buffer pixel holding the upright pixel `(x, y)`.
+    pub fn buffer_pixel(&self, buffer: Vector2D, x: i32, y: i32) -> (i32, i32) {
+        let (bx, by) = self
+            .matrix(buffer)
+            .transform_point(x as f64 + 0.5, y as f64 + 0.5);
+        (bx.floor() as i32, by.floor() as i32)
+    }
+
+    /// Part of the buffer holding the upright `rect`.
+    pub fn buffer_rect(&self, buffer: Vector2D, rect: Rect) -> Rect {
+        map_rect(&self.matrix(buffer), rect)
+    }
+
+    /// Upright area shown by `rect` of a `buffer` of this size, such as
+    /// damage reported in buffer coordinates.
+    pub fn upright_rect(&self, buffer: Vector2D, rect: Rect) -> Rect {
+        let mut matrix = self.matrix(buffer);
+        matrix.invert();
+        map_rect(&matrix, rect)
+    }
+}
+
+/// Bounding box of `rect` under `matrix`, which only rotates and flips.
+fn map_rect(matrix: &Matrix, rect: Rect) -> Rect {
+    let (x0, y0) = matrix.transform_point(rect.pos.x, rect.pos.y);
+    let (x1, y1) = matrix.transform_point(rect.pos.x + rect.size.x, rect.pos.y + rect.size.y);
+    Rect::new(
+        Vector2D::new(x0.min(x1), y0.min(y1)),
+        Vector2D::new((x1 - x0).abs(), (y1 - y0).abs()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_pixel() {
+        // A 4x2 framebuffer of a monitor turned to portrait
+        let buffer = Vector2D::new(4.0, 2.0);
+        let portrait = Orientation::new(Transform::_90, false);
+        assert_eq!(portrait.upright_size(buffer), Vector2D::new(2.0, 4.0));
+        assert_eq!(portrait.buffer_pixel(buffer, 0, 0), (0, 1));
+        assert_eq!(portrait.buffer_pixel(buffer, 1, 0), (0, 0));
+        assert_eq!(portrait.buffer_pixel(buffer, 0, 3), (3, 1));
+
+        let upside_down = Orientation::new(Transform::_180, false);
+        assert_eq!(upside_down.buffer_pixel(buffer, 0, 0), (3, 1));
+
+        let inverted = Orientation::new(Transform::Normal, true);
+        assert_eq!(inverted.buffer_pixel(buffer, 1, 0), (1, 1));
+        assert_eq!(inverted.buffer_pixel(buffer, 1, 1), (1, 0));
+
+        assert!(Orientation::default().is_identity());
+        assert_eq!(Orientation::default().buffer_pixel(buffer, 3, 1), (3, 1));
+    }
+
+    #[test]
+    fn test_every_transform_is_a_bijection() {
+        let buffer = Vector2D::new(3.0, 2.0);
+        for transform in [
+            Transform::Normal,
+            Transform::_90,
+            Transform::_180,
+            Transform::_270,
+            Transform::Flipped,
+            Transform::Flipped90,
+            Transform::Flipped180,
+            Transform::Flipped270,
+        ] {
+            for y_invert in [false, true] {
+                let orientation = Orientation::new(transform, y_invert);
+                let upright = orientation.upright_size(buffer);
+                let mut seen = Vec::new();
+                for y in 0..upright.y as i32 {
+                    for x in 0..upright.x as i32 {
+                        let (bx, by) = orientation.buffer_pixel(buffer, x, y);
+                        assert!((0..3).contains(&bx) && (0..2).contains(&by));
+                        seen.push((bx, by));
+                    }
+                }
+                seen.sort();
+                seen.dedup();
+                assert_eq!(seen.len(), 6, "{:?}", orientation);
+            }
+        }
+    }
+
+    #[test]
+    fn test_buffer_rect() {
+        let buffer = Vector2D::new(40.0, 20.0);
+        let portrait = Orientation::new(Transform::_270, false);
+        let rect = Rect::new(Vector2D::new(0.0, 0.0), Vector2D::new(5.0, 10.0));
+        let mapped = portrait.buffer_rect(buffer, rect);
+        assert_eq!(mapped.size, Vector2D::new(10.0, 5.0));
+        // Upright (0, 0) sits at the buffer's right edge
+        assert_eq!(mapped.pos, Vector2D::new(30.0, 0.0));
+        assert_eq!(portrait.upright_rect(buffer, mapped), rect);
+    }
+
+    #[test]
+    fn test_upright_rect() {
+        // Damage along the top of a portrait monitor's 40x20 framebuffer
+        let buffer = Vector2D::new(40.0, 20.0);
+        let portrait = Orientation::new(Transform::_90, false);
+        let damage = Rect::new(Vector2D::new(0.0, 0.0), Vector2D::new(10.0, 4.0));
+        let upright = portrait.upright_rect(buffer, damage);
+        // Buffer row 0 is the upright picture's right edge
+        assert_eq!(upright.pos, Vector2D::new(16.0, 0.0));
+        assert_eq!(upright.size, Vector2D::new(4.0, 10.0));
+
+        // Every pixel of the upright area lies in the damaged buffer area
+        for y in 0..10 {
+            for x in 16..20 {
+                let (bx, by) = portrait.buffer_pixel(buffer, x, y);
+                assert!((0..10).contains(&bx) && (0..4).contains(&by));
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! Capturing with wlr-screencopy.
+//!
+//! Each capture is a new frame object. The compositor names the buffer it
+//! wants in the frame's `buffer` event and reports `ready` once the copy
+//! is done. Regions are captured with `capture_output_region`.
+
+use super::{logical_region, Backend, FrameRequest, Started};
+use crate::utils::Vector2D;
+use anyhow::Result;
+use wayland_client::{Dispatch, QueueHandle};
+use wayland_protocols_wlr::screencopy::v1::client::{
+    zwlr_screencopy_frame_v1::ZwlrScreencopyFrameV1,
+    zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1,
+};
+
+/// What a screencopy frame was requested for.
+#[derive(Debug, Clone, Copy)]
+pub struct FrameData {
+    pub monitor: usize,
+    /// Where a region frame's top-left lies, in capture pixels
+    pub origin: Option<Vector2D>,
+}
+
+/// The wlr-screencopy backend.
+pub struct WlrScreencopy {
+    manager: ZwlrScreencopyManagerV1,
+}
+
+impl WlrScreencopy {
+    pub fn new(manager: ZwlrScreencopyManagerV1) -> Self {
+        Self { manager }
+    }
+}
+
+impl<D> Backend<D> for WlrScreencopy
+where
+    D: Dispatch<ZwlrScreencopyFrameV1, FrameData> + 'static,
+{
+    fn name(&self) -> &'static str {
+        "wlr-screencopy"
+    }
+
+    fn supports_regions(&self) -> bool {
+        true
+    }
+
+    fn capture(&mut self, request: &FrameRequest, qh: &QueueHandle<D>) -> Result<Started> {
+        let overlay_cursor = i32::from(request.cursor);
+        match request.region {
+            Some(region) => {
+                let ((x, y, width, height), origin) = logical_region(region, request.to_capture);
+                let data = FrameData {
+                    monitor: request.monitor,
+                    origin: Some(origin),
+                };
+                self.manager.capture_output_region(
+                    overlay_cursor,
+                    request.output,
+                    x,
+                    y,
+                    width,
+                    height,
+                    qh,
+                    data,
+                );
+            }
+            None => {
+                let data = FrameData {
+                    monitor: request.monitor,
+                    origin: None,
+                };
+                self.manager
+                    .capture_output(overlay_cursor, request.output, qh, data);
+            }
+        }
+        Ok(Started::Pending)
+    }
+}
@@ -0,0 +1,206 @@
+//! Clipboard ownership.
+//!
+//! Offers data on the regular clipboard selection. wlr-data-control is used
+//! when the compositor has it, since it needs no input serial or keyboard
+//! focus; otherwise the core wl_data_device protocol is used with the
+//! serial of the input event that triggered the copy.
+//!
+//! The data is served from this process, so it stays on the clipboard for
+//! as long as remagnify runs or until a clipboard manager takes a copy.
+//! Each paste is written from its own thread, so a client that reads
+//! slowly or not at all cannot stall the event loop.
+
+use std::io::Write;
+use std::os::fd::OwnedFd;
+use std::sync::Arc;
+use wayland_client::backend::ObjectId;
+use wayland_client::protocol::wl_data_device::WlDataDevice;
+use wayland_client::protocol::wl_data_device_manager::WlDataDeviceManager;
+use wayland_client::protocol::wl_data_source::WlDataSource;
+use wayland_client::protocol::wl_seat::WlSeat;
+use wayland_client::{Dispatch, Proxy, QueueHandle};
+use wayland_protocols_wlr::data_control::v1::client::{
+    zwlr_data_control_device_v1::ZwlrDataControlDeviceV1,
+    zwlr_data_control_manager_v1::ZwlrDataControlManagerV1,
+    zwlr_data_control_source_v1::ZwlrDataControlSourceV1,
+};
+
+/// MIME types offered for plain text.
+pub const TEXT_MIME_TYPES: [&str; 5] = [
+    "text/plain;charset=utf-8",
+    "text/plain",
+    "UTF8_STRING",
+    "STRING",
+    "TEXT",
+];
+
+enum Manager {
+    DataControl(ZwlrDataControlManagerV1),
+    DataDevice(WlDataDeviceManager),
+}
+
+enum Device {
+    DataControl(ZwlrDataControlDeviceV1),
+    DataDevice(WlDataDevice),
+}
+
+enum Source {
+    DataControl(ZwlrDataControlSourceV1),
+    DataDevice(WlDataSource),
+}
+
+/// Our side of the clipboard selection.
+pub struct Clipboard {
+    manager: Manager,
+    device: Option<Device>,
+    source: Option<Source>,
+    mime_types: Vec<String>,
+    data: Arc<[u8]>,
+}
+
+impl Clipboard {
+    /// Use the wlr-data-control manager.
+    pub fn data_control(manager: ZwlrDataControlManagerV1) -> Self {
+        Self::new(Manager::DataControl(manager))
+    }
+
+    /// Use the core data device manager.
+    pub fn data_device(manager: WlDataDeviceManager) -> Self {
+        Self::new(Manager::DataDevice(manager))
+    }
+
+    fn new(manager: Manager) -> Self {
+        Self {
+            manager,
+            device: None,
+            source: None,
+            mime_types: Vec::new(),
+            data: Arc::from([]),
+        }
+    }
+
+    /// Whether this clipboard uses wlr-data-control.
+    pub fn is_data_control(&self) -> bool {
+        matches!(self.manager, Manager::DataControl(_))
+    }
+
+    /// Put `text` on the clipboard.
+    pub fn set_text<D>(&mut self, text: &str, seat: &WlSeat, serial: u32, qh: &QueueHandle<D>)
+    where
+        D: Dispatch<ZwlrDataControlDeviceV1, ()> + Dispatch<ZwlrDataControlSourceV1, ()>,
+        D: Dispatch<WlDataDevice, ()> + Dispatch<WlDataSource, ()> + 'static,
+    {
+        self.set_contents(&TEXT_MIME_TYPES, text.as_bytes().to_vec(), seat, serial, qh);
+    }
+
+    /// Offer `data` under each of `mime_types`.
+    ///
+    /// `serial` must come from a recent input event on our surface; it is
+    /// ignored by wlr-data-control.
+    pub fn set_contents<D>(
+        &mut self,
+        mime_types: &[&str],
+        data: Vec<u8>,
+        seat: &WlSeat,
+        serial: u32,
+        qh: &QueueHandle<D>,
+    ) where
+        D: Dispatch<ZwlrDataControlDeviceV1, ()> + Dispatch<ZwlrDataControlSourceV1, ()>,
+        D: Dispatch<WlDataDevice, ()> + Dispatch<WlDataSource, ()> + 'static,
+    {
+        self.mime_types = mime_types.iter().map(|m| m.to_string()).collect();
+        self.data = data.into();
+
+        // Destroyed only after the new selection is set, so the clipboard
+        // never goes empty in between
+        let previous = self.source.take();
+
+        match &self.manager {
+            Manager::DataControl(manager) => {
+                let device = match &self.device {
+                    Some(Device::DataControl(device)) => device.clone(),
+                    _ => {
+                        let device = manager.get_data_device(seat, qh, ());
+                        self.device = Some(Device::DataControl(device.clone()));
+                        device
+                    }
+                };
+                let source = manager.create_data_source(qh, ());
+                for mime_type in &self.mime_types {
+                    source.offer(mime_type.clone());
+                }
+                device.set_selection(Some(&source));
+                self.source = Some(Source::DataControl(source));
+            }
+            Manager::DataDevice(manager) => {
+                let device = match &self.device {
+                    Some(Device::DataDevice(device)) => device.clone(),
+                    _ => {
+                        let device = manager.get_data_device(seat, qh, ());
+                        self.device = Some(Device::DataDevice(device.clone()));
+                        device
+                    }
+                };
+                let source = manager.create_data_source(qh, ());
+                for mime_type in &self.mime_types {
+                    source.offer(mime_type.clone());
+                }
+                device.set_selection(Some(&source), serial);
+                self.source = Some(Source::DataDevice(source));
+            }
+        }
+
+        if let Some(previous) = previous {
+            destroy(previous);
+        }
+    }
+
+    /// Write the clipboard contents to a pasting client.
+    ///
+    /// The write happens on a short-lived thread that ends once the reader
+    /// has everything or closes its end of the pipe.
+    pub fn send(&self, mime_type: &str, fd: OwnedFd) {
+        if !self.mime_types.iter().any(|m| m == mime_type) {
+            log::debug!("Clipboard request for unoffered type {}", mime_type);
+            return;
+        }
+
+        let data = Arc::clone(&self.data);
+        let spawned = std::thread::Builder::new()
+            .name("clipboard-send".into())
+            .spawn(move || {
+                let mut file = std::fs::File::from(fd);
+                if let Err(e) = file.write_all(&data) {
+                    log::warn!("Failed to send clipboard data: {}", e);
+                }
+            });
+        if let Err(e) = spawned {
+            log::warn!("Failed to start clipboard writer: {}", e);
+        }
+    }
+
+    /// The source `id` lost the selection; drop it if it is still ours.
+    pub fn cancelled(&mut self, id: ObjectId) {
+        let current = match &self.source {
+            Some(Source::DataControl(source)) => source.id(),
+            Some(Source::DataDevice(source)) => source.id(),
+            None => return,
+        };
+        if current == id {
+            self.destroy_source();
+        }
+    }
+
+    fn destroy_source(&mut self) {
+        if let Some(source) = self.source.take() {
+            destroy(source);
+        }
+    }
+}
+
+fn destroy(source: Source) {
+    match source {
+        Source::DataControl(source) => source.destroy(),
+        Source::DataDevice(source) => source.destroy(),
+    }
+}
@@ -3,8 +3,14 @@
 //! This module handles all configuration options for remagnify, including
 //! CLI argument parsing, validation, and default values.
 
-use crate::utils::Vector2D;
-use clap::{Parser, ValueEnum};
+use crate::effects::{ColorFilter, CvdFilter, CvdKind, CvdMode, TextFilter};
+use crate::smoothing::Smoothing;
+use crate::snapshot::Snapshots;
+use crate::utils::{Rect, Vector2D};
+use crate::zoom::{ZoomSteps, MAX_MAGNIFICATION};
+use clap::{ArgAction, Parser, ValueEnum};
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Magnifier movement mode.
 ///
@@ -18,6 +24,313 @@ pub enum MoveType {
     Cursor,
 }
 
+/// How the magnified view is presented.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Default)]
+pub enum ViewMode {
+    /// Floating lens around the pointer over an unmagnified screen (default).
+    #[default]
+    Lens,
+    /// The whole monitor is magnified and the viewport pans with the pointer.
+    Fullscreen,
+    /// The magnified view lives in a strip docked to a screen edge.
+    Docked,
+}
+
+/// Where screen captures come from.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Default)]
+pub enum CaptureBackend {
+    /// Pick from the protocols the compositor offers (default).
+    #[default]
+    Auto,
+    /// The wlr-screencopy protocol.
+    WlrScreencopy,
+    /// The standard ext-image-copy-capture protocol.
+    ExtImageCopy,
+    /// A static PNG given with --capture-image.
+    Image,
+}
+
+/// Screen edge a docked magnifier pane is anchored to.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Default)]
+pub enum DockEdge {
+    Top,
+    /// Bottom edge of the screen (default).
+    #[default]
+    Bottom,
+    Left,
+    Right,
+}
+
+impl DockEdge {
+    /// Whether the pane spans the full width of the screen.
+    pub fn is_horizontal(self) -> bool {
+        matches!(self, DockEdge::Top | DockEdge::Bottom)
+    }
+
+    /// Area a pane of size `pane` covers on a monitor of size `monitor`.
+    pub fn area(self, monitor: Vector2D, pane: Vector2D) -> Rect {
+        let pos = match self {
+            DockEdge::Top | DockEdge::Left => Vector2D::default(),
+            DockEdge::Bottom => Vector2D::new(0.0, monitor.y - pane.y),
+            DockEdge::Right => Vector2D::new(monitor.x - pane.x, 0.0),
+        };
+        Rect::new(pos, pane)
+    }
+}
+
+/// Colour transform applied inside the lens.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Default)]
+pub enum ColorMode {
+    /// Screen colours unchanged (default).
+    #[default]
+    Normal,
+    /// Invert every channel.
+    Invert,
+    /// Invert lightness but keep hue, so colours stay recognisable.
+    SmartInvert,
+    /// Luminance only.
+    Grayscale,
+    /// Steep contrast curve for faint content.
+    HighContrast,
+}
+
+impl ColorMode {
+    const ALL: [ColorMode; 5] = [
+        ColorMode::Normal,
+        ColorMode::Invert,
+        ColorMode::SmartInvert,
+        ColorMode::Grayscale,
+        ColorMode::HighContrast,
+    ];
+
+    /// The mode after this one, wrapping around.
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|m| *m == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    /// The mode before this one, wrapping around.
+    pub fn prev(self) -> Self {
+        let idx = Self::ALL.iter().position(|m| *m == self).unwrap_or(0);
+        Self::ALL[(idx + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// Adaptive binarization of the lens contents for reading text.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Default)]
+pub enum ReadingMode {
+    /// No binarization (default).
+    #[default]
+    Off,
+    /// Black text on a white background.
+    DarkOnLight,
+    /// White text on a black background.
+    LightOnDark,
+}
+
+impl ReadingMode {
+    /// The mode after this one, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            ReadingMode::Off => ReadingMode::DarkOnLight,
+            ReadingMode::DarkOnLight => ReadingMode::LightOnDark,
+            ReadingMode::LightOnDark => ReadingMode::Off,
+        }
+    }
+}
+
+/// Notation a picked colour is copied to the clipboard in.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Default)]
+pub enum PickFormat {
+    /// `#rrggbb` (default).
+    #[default]
+    Hex,
+    /// `rgb(r, g, b)`
+    Rgb,
+    /// `hsl(h, s%, l%)`
+    Hsl,
+    /// `oklch(L% C h)`
+    Oklch,
+}
+
+/// Interpolation used to enlarge the magnified region.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Default)]
+pub enum ScaleFilter {
+    /// Smooth below the auto threshold, nearest at or above it (default).
+    #[default]
+    Auto,
+    /// Hard pixel edges, for inspecting individual pixels.
+    Nearest,
+    Bilinear,
+    /// Cairo's highest quality filter; smooth text at low magnification.
+    Bicubic,
+    /// Lanczos-3 resampling on the CPU; sharper than bicubic.
+    Lanczos,
+    /// Scale2x (EPX) pixel-art scaler.
+    Scale2x,
+    /// 2xBR pixel-art scaler.
+    Xbr,
+}
+
+/// How the lens catches up with the pointer.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Default)]
+pub enum SmoothingMode {
+    /// The lens sits exactly on the pointer (default).
+    #[default]
+    Off,
+    /// The lens closes a fixed fraction of the gap each moment.
+    Exponential,
+    /// The lens is pulled by a critically damped spring and keeps some
+    /// momentum.
+    Inertia,
+}
+
+/// Easing curve for zoom and lens-size transitions.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Default)]
+pub enum Easing {
+    /// Constant speed.
+    Linear,
+    /// Fast start, gentle stop (default).
+    #[default]
+    EaseOut,
+    /// Gentle start and stop.
+    EaseInOut,
+}
+
+impl Easing {
+    /// Eased progress for linear progress `t` in 0.0..=1.0 (cubic curves).
+    pub fn apply(self, t: f64) -> f64 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut if t < 0.5 => 4.0 * t.powi(3),
+            Easing::EaseInOut => 1.0 - (2.0 - 2.0 * t).powi(3) / 2.0,
+        }
+    }
+}
+
+/// Pointer drawn inside the magnified view.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Default)]
+pub enum CursorStyle {
+    /// No pointer.
+    None,
+    /// Built-in arrow.
+    Arrow,
+    /// Built-in crosshair centred on the hotspot.
+    Crosshair,
+    /// The Xcursor theme's pointer, or the arrow if it can't be found
+    /// (default).
+    #[default]
+    Theme,
+}
+
+/// Shape of the magnifying lens.
+///
+/// The shape is always fitted to the configured magnifier size, so resizing
+/// the lens resizes the mask and outline with it.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LensShape {
+    /// Hard-edged rectangle (default).
+    #[default]
+    Rectangle,
+    /// Circle whose diameter is the shorter side of the magnifier size.
+    Circle,
+    /// Ellipse inscribed in the magnifier size.
+    Ellipse,
+    /// Rectangle with rounded corners of the given radius (logical pixels).
+    Rounded(f64),
+}
+
+impl LensShape {
+    /// Corner radius used by `rounded` when none is given.
+    pub const DEFAULT_CORNER_RADIUS: f64 = 16.0;
+}
+
+/// Where the floating lens sits relative to the pointer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LensOffset {
+    /// Gap in logical pixels between the pointer and the lens's near edge
+    /// on each axis; the sign picks the side and 0 centres the lens on the
+    /// pointer (default 0,0).
+    Fixed(Vector2D),
+    /// Beside the pointer on the side facing the middle of the screen.
+    Auto,
+}
+
+impl LensOffset {
+    /// Gap used by `auto`, in logical pixels.
+    pub const AUTO_GAP: f64 = 24.0;
+}
+
+impl Default for LensOffset {
+    fn default() -> Self {
+        LensOffset::Fixed(Vector2D::default())
+    }
+}
+
+/// How the lens maps the capture onto its area.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LensProjection {
+    /// Uniform magnification across the lens (default).
+    #[default]
+    Flat,
+    /// Full magnification at the centre, easing to 1x at the edge, with the
+    /// given falloff strength.
+    Fisheye(f64),
+}
+
+impl LensProjection {
+    /// Falloff strength used by `fisheye` when none is given.
+    pub const DEFAULT_FISHEYE_STRENGTH: f64 = 2.0;
+}
+
+/// Dimming of everything outside the lens.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spotlight {
+    pub enabled: bool,
+    /// Opacity of the dim, 0.05 to 0.95
+    pub dim: f64,
+    /// Width of the soft edge around the lens in logical pixels; 0 gives a
+    /// hard edge
+    pub feather: f64,
+}
+
+impl Spotlight {
+    /// Bands in a soft edge, enough that the steps don't show.
+    const FEATHER_STEPS: usize = 24;
+
+    /// Bands of the dim from the outside in: how far each one grows the
+    /// lens shape, and the dim left inside that grown shape.
+    ///
+    /// The last band is the lens itself with no dim. A hard edge has only
+    /// that band.
+    pub fn bands(&self) -> Vec<(f64, f64)> {
+        let steps = if self.feather > 0.0 {
+            Self::FEATHER_STEPS
+        } else {
+            1
+        };
+        (0..steps)
+            .rev()
+            .map(|i| {
+                let t = i as f64 / steps as f64;
+                // Smoothstep, so the edge has no visible start or end
+                (self.feather * t, self.dim * t * t * (3.0 - 2.0 * t))
+            })
+            .collect()
+    }
+}
+
+impl Default for Spotlight {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            dim: 0.6,
+            feather: 0.0,
+        }
+    }
+}
+
 /// Application configuration.
 ///
 /// Contains all validated configuration options for the magnifier.
@@ -29,14 +342,64 @@ pub struct Config {
     pub size: Vector2D,
     #[allow(dead_code)]
     pub render_inactive: bool,
-    #[allow(dead_code)]
+    /// Keep re-capturing the active monitor while magnifying.
     pub continuous_capture: bool,
-    pub zoom_speed: f64,
+    /// Live captures per second.
+    pub refresh_rate: u32,
+    /// Capture only the area around the views, not whole monitors.
+    pub region_capture: bool,
+    /// Where captures come from.
+    pub capture_backend: CaptureBackend,
+    /// Image shown instead of the screen by the image backend.
+    pub capture_image: Option<PathBuf>,
+    /// Zoom limits, presets and scroll behaviour.
+    pub zoom: ZoomSteps,
     pub exit_delay_ms: u64,
     pub hide_cursor: bool,
+    /// Pointer drawn in the magnified view.
+    pub cursor: CursorStyle,
+    /// Capture the compositor's own cursor instead of drawing one.
+    pub capture_cursor: bool,
     /// Fractional scale override (e.g., 1.5 for 150% scaling).
     /// If None, uses the integer scale from wl_output.
     pub scale: Option<f64>,
+    /// Outline of the lens, fitted to `size`.
+    pub shape: LensShape,
+    /// Flat or fisheye magnification inside the lens.
+    pub lens: LensProjection,
+    pub lens_offset: LensOffset,
+    /// Lens, full-screen or docked magnification.
+    pub mode: ViewMode,
+    pub dock_edge: DockEdge,
+    /// Thickness of the docked pane in logical pixels.
+    pub dock_size: u32,
+    /// Colour mode and levels applied inside the lens.
+    pub color: ColorFilter,
+    /// Colour-vision deficiency simulation or correction inside the lens.
+    pub cvd: CvdFilter,
+    /// Resampling filter for the magnified capture.
+    pub filter: ScaleFilter,
+    /// Magnification at which `ScaleFilter::Auto` switches to nearest.
+    pub filter_threshold: f64,
+    /// Sharpening and reading mode inside the lens.
+    pub text: TextFilter,
+    pub pixel_grid: bool,
+    pub inspector: bool,
+    /// Side of the square averaged by the colour picker, in source pixels.
+    pub pick_size: u32,
+    pub pick_format: PickFormat,
+    /// Where `w` and `W` save PNG snapshots.
+    pub snapshots: Snapshots,
+    /// Start in measure mode, where clicks anchor the ruler.
+    pub measure: bool,
+    /// Show the fading zoom readout.
+    pub hud: bool,
+    pub spotlight: Spotlight,
+    /// Length of zoom and lens-size transitions; 0 makes them instant.
+    pub animation_ms: u64,
+    pub easing: Easing,
+    /// How the lens follows the pointer.
+    pub smoothing: Smoothing,
 }
 
 impl Default for Config {
@@ -45,11 +408,39 @@ impl Default for Config {
             move_type: MoveType::Cursor,
             size: Vector2D::new(300.0, 150.0),
             render_inactive: false,
-            continuous_capture: true,
-            zoom_speed: 0.05,   // Default zoom speed (5% per scroll notch)
-            exit_delay_ms: 200, // Default 200ms delay before exit
-            hide_cursor: true,  // Hide cursor by default
-            scale: None,        // Auto-detect from wl_output
+            continuous_capture: false,
+            refresh_rate: 30,
+            region_capture: false,
+            capture_backend: CaptureBackend::Auto,
+            capture_image: None,
+            zoom: ZoomSteps::default(), // 5% per scroll notch
+            exit_delay_ms: 200,         // Default 200ms delay before exit
+            hide_cursor: true,          // Hide cursor by default
+            cursor: CursorStyle::Theme,
+            capture_cursor: false,
+            scale: None, // Auto-detect from wl_output
+            shape: LensShape::Rectangle,
+            lens: LensProjection::Flat,
+            lens_offset: LensOffset::default(),
+            mode: ViewMode::Lens,
+            dock_edge: DockEdge::Bottom,
+            dock_size: 300,
+            color: ColorFilter::default(),
+            cvd: CvdFilter::default(),
+            filter: ScaleFilter::Auto,
+            filter_threshold: 4.0,
+            text: TextFilter::default(),
+            pixel_grid: false,
+            inspector: false,
+            pick_size: 1,
+            pick_format: PickFormat::Hex,
+            snapshots: Snapshots::default(),
+            measure: false,
+            hud: false,
+            spotlight: Spotlight::default(),
+            animation_ms: 150,
+            easing: Easing::EaseOut,
+            smoothing: Smoothing::default(),
         }
     }
 }
@@ -74,14 +465,55 @@ pub struct Cli {
     #[arg(short, long)]
     pub render_inactive: bool,
 
-    /// Enable continuous capture (live updates)
-    #[arg(short, long, default_value = "true")]
+    /// Keep capturing the screen so the lens shows live content
+    /// (`-c`, or `--continuous true|false`; default: false)
+    #[arg(
+        short,
+        long,
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        default_value = "false",
+        default_missing_value = "true"
+    )]
     pub continuous: bool,
 
-    /// Zoom speed multiplier (default: 0.05, higher = faster)
+    /// Live captures per second with --continuous, 1 to 60 (default: 30)
+    #[arg(long, default_value = "30")]
+    pub refresh_rate: u32,
+
+    /// Capture only the area around the lens, fetching more as the pointer
+    /// moves on; less work per capture on large or high-DPI monitors
+    #[arg(long)]
+    pub capture_region: bool,
+
+    /// Where captures come from: auto (default), wlr-screencopy,
+    /// ext-image-copy or image
+    #[arg(long, value_enum, default_value = "auto")]
+    pub capture_backend: CaptureBackend,
+
+    /// Magnify this PNG instead of the screen; selects the image backend
+    #[arg(long, value_name = "PATH")]
+    pub capture_image: Option<PathBuf>,
+
+    /// Zoom change per scroll notch as a fraction of the magnification
+    /// (default: 0.05, higher = faster)
     #[arg(short = 'z', long, default_value = "0.05")]
     pub zoom_speed: f64,
 
+    /// Lowest magnification, 1 to 1000; at 1x scrolling out exits
+    /// (default: 1)
+    #[arg(long, default_value = "1")]
+    pub min_zoom: f64,
+
+    /// Highest magnification, up to 1000 (default: 100)
+    #[arg(long, default_value = "100")]
+    pub max_zoom: f64,
+
+    /// Comma-separated magnifications to scroll between instead of zooming
+    /// smoothly, e.g. 1.5,2,3,4,6,8,16; the number keys 1-9 jump to them
+    #[arg(long, value_delimiter = ',')]
+    pub zoom_presets: Vec<f64>,
+
     /// Exit delay in milliseconds after zooming out (default: 200)
     #[arg(short = 'e', long, default_value = "200")]
     pub exit_delay: u64,
@@ -98,11 +530,174 @@ pub struct Cli {
     #[arg(long)]
     pub show_cursor: bool,
 
+    /// Pointer drawn inside the magnified view: none, arrow, crosshair or
+    /// theme (uses XCURSOR_THEME and XCURSOR_SIZE)
+    #[arg(long, value_enum, default_value = "theme")]
+    pub cursor: CursorStyle,
+
+    /// Capture the compositor's cursor into the magnified image instead of
+    /// drawing one; implies --show-cursor
+    #[arg(long)]
+    pub capture_cursor: bool,
+
     /// Override monitor scale (e.g., 1.5 for 150% scaling).
     /// Use this for fractional scaling if auto-detection doesn't work.
     /// If not specified, uses the integer scale from wl_output.
     #[arg(long)]
     pub scale: Option<f64>,
+
+    /// Lens shape: rectangle, circle, ellipse or rounded[:RADIUS]
+    #[arg(long, value_parser = parse_shape, default_value = "rectangle")]
+    pub shape: LensShape,
+
+    /// Lens projection: flat or fisheye[:STRENGTH] (strength 0.5 to 10,
+    /// default: 2)
+    #[arg(long, value_parser = parse_lens, default_value = "flat")]
+    pub lens: LensProjection,
+
+    /// Put the lens beside the pointer: X,Y gap to the lens edge in logical
+    /// pixels (negative for left / above), or auto (default: 0,0)
+    #[arg(long, value_parser = parse_lens_offset, default_value = "0,0", allow_hyphen_values = true)]
+    pub lens_offset: LensOffset,
+
+    /// View mode: a floating lens, or full-screen zoom that pans with the pointer
+    #[arg(long, value_enum, default_value = "lens")]
+    pub mode: ViewMode,
+
+    /// Screen edge for the docked pane (with --mode docked)
+    #[arg(long, value_enum, default_value = "bottom")]
+    pub dock_edge: DockEdge,
+
+    /// Thickness of the docked pane in logical pixels (default: 300)
+    #[arg(long, default_value = "300")]
+    pub dock_size: u32,
+
+    /// Colour mode inside the lens (cycle at runtime with i / I)
+    #[arg(long, value_enum, default_value = "normal")]
+    pub color: ColorMode,
+
+    /// Brightness offset inside the lens, -1.0 to 1.0 (runtime: b / B)
+    #[arg(long, default_value = "0.0", allow_negative_numbers = true)]
+    pub brightness: f64,
+
+    /// Contrast multiplier inside the lens, 0.0 to 4.0 (runtime: c / C)
+    #[arg(long, default_value = "1.0")]
+    pub contrast: f64,
+
+    /// Gamma inside the lens, 0.1 to 5.0 (runtime: y / Y)
+    #[arg(long, default_value = "1.0")]
+    pub gamma: f64,
+
+    /// Colour-vision deficiency filter: TYPE[:simulate|correct], where TYPE is
+    /// protanopia, deuteranopia or tritanopia (runtime: d cycles, D switches mode)
+    #[arg(long, value_parser = parse_cvd)]
+    pub cvd: Option<CvdFilter>,
+
+    /// Interpolation for the magnified region: auto, nearest, bilinear,
+    /// bicubic, lanczos, scale2x or xbr
+    #[arg(long, value_enum, default_value = "auto")]
+    pub filter: ScaleFilter,
+
+    /// Magnification at which --filter auto switches from smooth to nearest (default: 4.0)
+    #[arg(long, default_value = "4.0")]
+    pub filter_threshold: f64,
+
+    /// Sharpen the lens with an unsharp mask (toggle at runtime with s)
+    #[arg(long)]
+    pub sharpen: bool,
+
+    /// Unsharp mask radius in pixels, 0.5 to 10.0 (default: 1.5)
+    #[arg(long, default_value = "1.5")]
+    pub sharpen_radius: f64,
+
+    /// Sharpen / reading strength, 0.0 to 5.0 (default: 1.0, runtime: , and .)
+    #[arg(long, default_value = "1.0")]
+    pub sharpen_amount: f64,
+
+    /// Reading mode: binarize text inside the lens (cycle at runtime with r)
+    #[arg(long, value_enum, default_value = "off")]
+    pub reading: ReadingMode,
+
+    /// Draw a grid between source pixels at high zoom (toggle at runtime with g)
+    #[arg(long)]
+    pub pixel_grid: bool,
+
+    /// Show the coordinates and RGBA value of the pixel under the lens centre
+    /// (toggle at runtime with x)
+    #[arg(long)]
+    pub inspector: bool,
+
+    /// Average an NxN block when picking a colour (default: 1, max: 15)
+    #[arg(long, default_value = "1")]
+    pub pick_size: u32,
+
+    /// Format copied to the clipboard when picking: hex, rgb, hsl or oklch
+    #[arg(long, value_enum, default_value = "hex")]
+    pub pick_format: PickFormat,
+
+    /// Directory for PNG snapshots saved with w (the view) and W (the
+    /// source at native resolution) (default: XDG pictures directory)
+    #[arg(long)]
+    pub save_dir: Option<PathBuf>,
+
+    /// Also put each snapshot on the clipboard as image/png
+    #[arg(long)]
+    pub save_clipboard: bool,
+
+    /// Start in measure mode: clicks anchor a ruler instead of picking
+    /// colours (toggle at runtime with m)
+    #[arg(long)]
+    pub measure: bool,
+
+    /// Show the magnification, monitor name and pointer position near the
+    /// lens for a moment after they change
+    #[arg(long)]
+    pub hud: bool,
+
+    /// Dim everything outside the lens; zooming out to 1x then keeps a
+    /// plain spotlight instead of exiting (toggle at runtime with o)
+    #[arg(long)]
+    pub spotlight: bool,
+
+    /// Opacity of the spotlight dim, 0.05 to 0.95 (default: 0.6)
+    #[arg(long, default_value = "0.6")]
+    pub spotlight_dim: f64,
+
+    /// Width of the spotlight's soft edge in logical pixels, 0 to 500
+    /// (default: 0, a hard edge)
+    #[arg(long, default_value = "0")]
+    pub spotlight_feather: f64,
+
+    /// Duration of zoom and lens-size transitions in milliseconds
+    /// (default: 150, max: 2000)
+    #[arg(long, default_value = "150")]
+    pub animation_duration: u64,
+
+    /// Easing curve for transitions: linear, ease-out or ease-in-out
+    #[arg(long, value_enum, default_value = "ease-out")]
+    pub easing: Easing,
+
+    /// Apply zoom and lens-size changes instantly
+    #[arg(long)]
+    pub no_animation: bool,
+
+    /// Pointer smoothing: off, exponential or inertia
+    #[arg(long, value_enum, default_value = "off")]
+    pub smoothing: SmoothingMode,
+
+    /// Smoothing time constant in milliseconds, 10 to 1000 (default: 80)
+    #[arg(long, default_value = "80")]
+    pub smoothing_time: u64,
+
+    /// Pointer movement in logical pixels ignored before the lens follows,
+    /// 0 to 100 (default: 0)
+    #[arg(long, default_value = "0")]
+    pub dead_zone: f64,
+
+    /// Aim the lens ahead of the pointer by this many milliseconds of its
+    /// motion, 0 to 200 (default: 0)
+    #[arg(long, default_value = "0")]
+    pub lead: u64,
 }
 
 /// Parse a size string in the format "WIDTHxHEIGHT".
@@ -142,12 +737,167 @@ fn parse_size(s: &str) -> Result<Vector2D, String> {
     Ok(Vector2D::new(width, height))
 }
 
+/// Parse a lens shape string.
+///
+/// Accepts `rectangle`, `circle`, `ellipse`, `rounded` and `rounded:RADIUS`,
+/// where RADIUS is the corner radius in logical pixels.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(parse_shape("circle")?, LensShape::Circle);
+/// assert_eq!(parse_shape("rounded:24")?, LensShape::Rounded(24.0));
+/// ```
+fn parse_shape(s: &str) -> Result<LensShape, String> {
+    let (name, arg) = match s.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (s, None),
+    };
+
+    match (name.to_ascii_lowercase().as_str(), arg) {
+        ("rectangle" | "rect", None) => Ok(LensShape::Rectangle),
+        ("circle", None) => Ok(LensShape::Circle),
+        ("ellipse", None) => Ok(LensShape::Ellipse),
+        ("rounded", None) => Ok(LensShape::Rounded(LensShape::DEFAULT_CORNER_RADIUS)),
+        ("rounded", Some(radius)) => {
+            let radius: f64 = radius
+                .parse()
+                .map_err(|_| format!("Invalid corner radius: {}", radius))?;
+            if radius < 0.0 {
+                return Err("Corner radius must not be negative".to_string());
+            }
+            Ok(LensShape::Rounded(radius))
+        }
+        (_, Some(_)) => Err(format!("Shape '{}' does not take an argument", name)),
+        _ => Err(format!(
+            "Unknown shape '{}', expected rectangle, circle, ellipse or rounded[:RADIUS]",
+            s
+        )),
+    }
+}
+
+/// Parse a lens projection string.
+///
+/// Accepts `flat`, `fisheye` and `fisheye:STRENGTH`, where STRENGTH sets how
+/// long the centre holds full magnification before falling off.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(parse_lens("fisheye:3")?, LensProjection::Fisheye(3.0));
+/// ```
+fn parse_lens(s: &str) -> Result<LensProjection, String> {
+    let (name, arg) = match s.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (s, None),
+    };
+
+    match (name.to_ascii_lowercase().as_str(), arg) {
+        ("flat", None) => Ok(LensProjection::Flat),
+        ("fisheye", None) => Ok(LensProjection::Fisheye(
+            LensProjection::DEFAULT_FISHEYE_STRENGTH,
+        )),
+        ("fisheye", Some(strength)) => {
+            let strength: f64 = strength
+                .parse()
+                .map_err(|_| format!("Invalid fisheye strength: {}", strength))?;
+            if !(0.5..=10.0).contains(&strength) {
+                return Err("Fisheye strength must be between 0.5 and 10".to_string());
+            }
+            Ok(LensProjection::Fisheye(strength))
+        }
+        (_, Some(_)) => Err(format!("Lens '{}' does not take an argument", name)),
+        _ => Err(format!(
+            "Unknown lens '{}', expected flat or fisheye[:STRENGTH]",
+            s
+        )),
+    }
+}
+
+/// Parse a lens offset string: `X,Y` or `auto`.
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(parse_lens_offset("40,-40")?, LensOffset::Fixed(Vector2D::new(40.0, -40.0)));
+/// ```
+fn parse_lens_offset(s: &str) -> Result<LensOffset, String> {
+    if s.eq_ignore_ascii_case("auto") {
+        return Ok(LensOffset::Auto);
+    }
+
+    let (x, y) = s
+        .split_once(',')
+        .ok_or_else(|| format!("Lens offset must be X,Y or auto, got: {}", s))?;
+    let x: f64 = x
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid offset: {}", x))?;
+    let y: f64 = y
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid offset: {}", y))?;
+
+    Ok(LensOffset::Fixed(Vector2D::new(x, y)))
+}
+
+/// Parse a colour-vision deficiency filter string.
+///
+/// Accepts `TYPE` or `TYPE:MODE`. TYPE is `protanopia`, `deuteranopia` or
+/// `tritanopia` (or the short forms `protan`, `deutan`, `tritan`); MODE is
+/// `simulate` (default) or `correct`.
+///
+/// # Examples
+///
+/// ```ignore
+/// let cvd = parse_cvd("deuteranopia:correct")?;
+/// assert_eq!(cvd.kind, Some(CvdKind::Deuteranopia));
+/// ```
+fn parse_cvd(s: &str) -> Result<CvdFilter, String> {
+    let (kind, mode) = s.split_once(':').unwrap_or((s, "simulate"));
+
+    let kind = match kind.to_ascii_lowercase().as_str() {
+        "protanopia" | "protan" => CvdKind::Protanopia,
+        "deuteranopia" | "deutan" => CvdKind::Deuteranopia,
+        "tritanopia" | "tritan" => CvdKind::Tritanopia,
+        _ => {
+            return Err(format!(
+                "Unknown deficiency '{}', expected protanopia, deuteranopia or tritanopia",
+                kind
+            ))
+        }
+    };
+
+    let mode = match mode.to_ascii_lowercase().as_str() {
+        "simulate" | "sim" => CvdMode::Simulate,
+        "correct" | "daltonize" => CvdMode::Correct,
+        _ => {
+            return Err(format!(
+                "Unknown mode '{}', expected simulate or correct",
+                mode
+            ))
+        }
+    };
+
+    Ok(CvdFilter {
+        kind: Some(kind),
+        mode,
+    })
+}
+
 impl Config {
     /// Create a Config from CLI arguments.
     ///
     /// Validates and clamps all values to safe ranges:
     /// - zoom_speed: clamped to 0.001..=1.0
+    /// - min_zoom, max_zoom: clamped to 1..=1000, max at least min
+    /// - zoom_presets: values below 1x dropped, sorted
     /// - exit_delay_ms: clamped to 0..=5000
+    /// - refresh_rate: clamped to 1..=60
+    /// - dock_size: clamped to 50..=2000
+    /// - animation_ms: clamped to 0..=2000, 0 with `--no-animation`
+    /// - smoothing: time 10..=1000ms, dead zone 0..=100px, lead 0..=200ms
+    /// - brightness, contrast, gamma: clamped by [`ColorFilter`]
     ///
     /// # Arguments
     ///
@@ -170,15 +920,96 @@ impl Config {
             }
         });
 
+        let mut color = ColorFilter {
+            mode: cli.color,
+            ..ColorFilter::default()
+        };
+        color.set_brightness(cli.brightness);
+        color.set_contrast(cli.contrast);
+        color.set_gamma(cli.gamma);
+
+        let mut text = TextFilter {
+            sharpen: cli.sharpen,
+            reading: cli.reading,
+            ..TextFilter::default()
+        };
+        text.set_radius(cli.sharpen_radius);
+        text.set_amount(cli.sharpen_amount);
+
+        let zoom_speed = cli.zoom_speed.clamp(0.001, 1.0);
+        let min_zoom = cli.min_zoom.clamp(1.0, MAX_MAGNIFICATION);
+        let max_zoom = cli.max_zoom.clamp(min_zoom, MAX_MAGNIFICATION);
+        let mut presets: Vec<f64> = cli
+            .zoom_presets
+            .iter()
+            .copied()
+            .filter(|m| m.is_finite() && *m >= 1.0)
+            .collect();
+        presets.sort_by(f64::total_cmp);
+        presets.dedup();
+
         Config {
             move_type: cli.move_type,
             size: cli.size.unwrap_or_else(|| Config::default().size),
             render_inactive: cli.render_inactive,
             continuous_capture: cli.continuous,
-            zoom_speed: cli.zoom_speed.clamp(0.001, 1.0),
+            refresh_rate: cli.refresh_rate.clamp(1, 60),
+            region_capture: cli.capture_region,
+            capture_backend: match cli.capture_backend {
+                CaptureBackend::Auto if cli.capture_image.is_some() => CaptureBackend::Image,
+                backend => backend,
+            },
+            capture_image: cli.capture_image,
+            zoom: ZoomSteps::new(min_zoom, max_zoom, 1.0 + zoom_speed, presets),
             exit_delay_ms: cli.exit_delay.min(5000),
-            hide_cursor: !cli.show_cursor, // Invert: show_cursor flag disables hiding
+            // Invert: show_cursor flag disables hiding. A hidden cursor
+            // would be missing from the capture too.
+            hide_cursor: !cli.show_cursor && !cli.capture_cursor,
+            cursor: if cli.capture_cursor {
+                CursorStyle::None
+            } else {
+                cli.cursor
+            },
+            capture_cursor: cli.capture_cursor,
             scale,
+            shape: cli.shape,
+            lens: cli.lens,
+            lens_offset: cli.lens_offset,
+            mode: cli.mode,
+            dock_edge: cli.dock_edge,
+            dock_size: cli.dock_size.clamp(50, 2000),
+            color,
+            cvd: cli.cvd.unwrap_or_default(),
+            filter: cli.filter,
+            filter_threshold: cli.filter_threshold.clamp(1.0, 100.0),
+            text,
+            pixel_grid: cli.pixel_grid,
+            inspector: cli.inspector,
+            pick_size: cli.pick_size.clamp(1, 15),
+            pick_format: cli.pick_format,
+            snapshots: Snapshots {
+                dir: cli.save_dir,
+                clipboard: cli.save_clipboard,
+            },
+            measure: cli.measure,
+            hud: cli.hud,
+            spotlight: Spotlight {
+                enabled: cli.spotlight,
+                dim: cli.spotlight_dim.clamp(0.05, 0.95),
+                feather: cli.spotlight_feather.clamp(0.0, 500.0),
+            },
+            animation_ms: if cli.no_animation {
+                0
+            } else {
+                cli.animation_duration.min(2000)
+            },
+            easing: cli.easing,
+            smoothing: Smoothing {
+                mode: cli.smoothing,
+                time: Duration::from_millis(cli.smoothing_time.clamp(10, 1000)),
+                dead_zone: cli.dead_zone.clamp(0.0, 100.0),
+                lead: Duration::from_millis(cli.lead.min(200)),
+            },
         }
     }
 
@@ -198,6 +1029,11 @@ impl Config {
 mod tests {
     use super::*;
 
+    /// CLI with every option at its default, for struct-update syntax.
+    fn default_cli() -> Cli {
+        Cli::parse_from(["remagnify"])
+    }
+
     #[test]
     fn test_parse_size() {
         assert_eq!(parse_size("300x150").unwrap(), Vector2D::new(300.0, 150.0));
@@ -210,6 +1046,79 @@ mod tests {
         assert!(parse_size("-300x150").is_err());
     }
 
+    #[test]
+    fn test_parse_shape() {
+        assert_eq!(parse_shape("rectangle").unwrap(), LensShape::Rectangle);
+        assert_eq!(parse_shape("circle").unwrap(), LensShape::Circle);
+        assert_eq!(parse_shape("Ellipse").unwrap(), LensShape::Ellipse);
+        assert_eq!(
+            parse_shape("rounded").unwrap(),
+            LensShape::Rounded(LensShape::DEFAULT_CORNER_RADIUS)
+        );
+        assert_eq!(parse_shape("rounded:24").unwrap(), LensShape::Rounded(24.0));
+        assert!(parse_shape("rounded:-4").is_err());
+        assert!(parse_shape("rounded:abc").is_err());
+        assert!(parse_shape("circle:10").is_err());
+        assert!(parse_shape("hexagon").is_err());
+    }
+
+    #[test]
+    fn test_parse_lens_offset() {
+        assert_eq!(
+            parse_lens_offset("40,-40").unwrap(),
+            LensOffset::Fixed(Vector2D::new(40.0, -40.0))
+        );
+        assert_eq!(parse_lens_offset("Auto").unwrap(), LensOffset::Auto);
+        assert!(parse_lens_offset("40").is_err());
+        assert!(parse_lens_offset("a,b").is_err());
+
+        let config = Config::from_cli(Cli::parse_from(["remagnify", "--lens-offset", "-20,30"]));
+        assert_eq!(
+            config.lens_offset,
+            LensOffset::Fixed(Vector2D::new(-20.0, 30.0))
+        );
+        assert_eq!(
+            Config::from_cli(default_cli()).lens_offset,
+            LensOffset::default()
+        );
+    }
+
+    #[test]
+    fn test_parse_lens() {
+        assert_eq!(parse_lens("flat").unwrap(), LensProjection::Flat);
+        assert_eq!(
+            parse_lens("Fisheye").unwrap(),
+            LensProjection::Fisheye(LensProjection::DEFAULT_FISHEYE_STRENGTH)
+        );
+        assert_eq!(
+            parse_lens("fisheye:3.5").unwrap(),
+            LensProjection::Fisheye(3.5)
+        );
+        assert!(parse_lens("fisheye:0").is_err());
+        assert!(parse_lens("fisheye:abc").is_err());
+        assert!(parse_lens("flat:2").is_err());
+        assert!(parse_lens("barrel").is_err());
+
+        assert_eq!(Config::from_cli(default_cli()).lens, LensProjection::Flat);
+    }
+
+    #[test]
+    fn test_parse_cvd() {
+        let cvd = parse_cvd("protanopia:simulate").unwrap();
+        assert_eq!(cvd.kind, Some(CvdKind::Protanopia));
+        assert_eq!(cvd.mode, CvdMode::Simulate);
+
+        let cvd = parse_cvd("Deutan:correct").unwrap();
+        assert_eq!(cvd.kind, Some(CvdKind::Deuteranopia));
+        assert_eq!(cvd.mode, CvdMode::Correct);
+
+        // Mode defaults to simulate
+        assert_eq!(parse_cvd("tritanopia").unwrap().mode, CvdMode::Simulate);
+
+        assert!(parse_cvd("achromatopsia").is_err());
+        assert!(parse_cvd("protanopia:fix").is_err());
+    }
+
     #[test]
     fn test_config_from_cli() {
         let cli = Cli {
@@ -223,15 +1132,17 @@ mod tests {
             verbose: false,
             show_cursor: false,
             scale: None,
+            ..default_cli()
         };
 
         let config = Config::from_cli(cli);
         assert_eq!(config.size.x, 400.0);
         assert_eq!(config.size.y, 200.0);
-        assert_eq!(config.zoom_speed, 0.1);
+        assert_eq!(config.zoom.ratio, 1.1);
         assert_eq!(config.exit_delay_ms, 500);
         assert!(config.hide_cursor); // Default: cursor hidden
         assert_eq!(config.scale, None);
+        assert_eq!(config.mode, ViewMode::Lens);
     }
 
     #[test]
@@ -248,10 +1159,11 @@ mod tests {
             verbose: false,
             show_cursor: false,
             scale: None,
+            ..default_cli()
         };
 
         let config = Config::from_cli(cli_too_low);
-        assert!(config.zoom_speed >= 0.001); // Should be clamped to minimum
+        assert!(config.zoom.ratio >= 1.001); // Should be clamped to minimum
 
         let cli_too_high = Cli {
             move_type: MoveType::Cursor,
@@ -264,10 +1176,60 @@ mod tests {
             verbose: false,
             show_cursor: false,
             scale: None,
+            ..default_cli()
         };
 
         let config = Config::from_cli(cli_too_high);
-        assert!(config.zoom_speed <= 1.0); // Should be clamped to maximum
+        assert!(config.zoom.ratio <= 2.0); // Should be clamped to maximum
+    }
+
+    #[test]
+    fn test_zoom_options() {
+        let config = Config::from_cli(default_cli());
+        assert_eq!(config.zoom, ZoomSteps::default());
+
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--zoom-speed",
+            "0.2",
+            "--min-zoom",
+            "2",
+            "--max-zoom",
+            "5000",
+            "--zoom-presets",
+            "8,0.5,3,2",
+        ]));
+        assert_eq!(config.zoom.min, 2.0);
+        assert_eq!(config.zoom.max, 1000.0);
+        assert_eq!(config.zoom.ratio, 1.2);
+        assert_eq!(config.zoom.presets, [2.0, 3.0, 8.0]);
+        assert!(config.zoom.ladder);
+
+        // The ceiling can't go below the floor
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--min-zoom",
+            "8",
+            "--max-zoom",
+            "4",
+        ]));
+        assert_eq!(config.zoom.max, 8.0);
+    }
+
+    #[test]
+    fn test_snapshot_options() {
+        let config = Config::from_cli(default_cli());
+        assert_eq!(config.snapshots, Snapshots::default());
+
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--save-dir",
+            "/tmp/shots",
+            "--save-clipboard",
+        ]));
+        assert_eq!(config.snapshots.dir, Some(PathBuf::from("/tmp/shots")));
+        assert_eq!(config.snapshots.directory(), PathBuf::from("/tmp/shots"));
+        assert!(config.snapshots.clipboard);
     }
 
     #[test]
@@ -284,6 +1246,7 @@ mod tests {
             verbose: false,
             show_cursor: false,
             scale: None,
+            ..default_cli()
         };
 
         let config = Config::from_cli(cli);
@@ -304,6 +1267,7 @@ mod tests {
             verbose: false,
             show_cursor: false, // Default: don't show cursor
             scale: None,
+            ..default_cli()
         };
 
         let config = Config::from_cli(cli_default);
@@ -321,12 +1285,34 @@ mod tests {
             verbose: false,
             show_cursor: true, // Explicitly show cursor
             scale: None,
+            ..default_cli()
         };
 
         let config = Config::from_cli(cli_show);
         assert!(!config.hide_cursor); // Cursor should be visible
     }
 
+    #[test]
+    fn test_cursor_options() {
+        let config = Config::from_cli(default_cli());
+        assert_eq!(config.cursor, CursorStyle::Theme);
+        assert!(!config.capture_cursor);
+
+        let config = Config::from_cli(Cli::parse_from(["remagnify", "--cursor", "crosshair"]));
+        assert_eq!(config.cursor, CursorStyle::Crosshair);
+
+        // The captured cursor replaces the drawn one and must stay visible
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--cursor",
+            "arrow",
+            "--capture-cursor",
+        ]));
+        assert!(config.capture_cursor);
+        assert_eq!(config.cursor, CursorStyle::None);
+        assert!(!config.hide_cursor);
+    }
+
     #[test]
     fn test_scale_validation() {
         // Test valid scale
@@ -341,6 +1327,7 @@ mod tests {
             verbose: false,
             show_cursor: false,
             scale: Some(1.5),
+            ..default_cli()
         };
 
         let config = Config::from_cli(cli_valid);
@@ -358,6 +1345,7 @@ mod tests {
             verbose: false,
             show_cursor: false,
             scale: Some(15.0), // Too high
+            ..default_cli()
         };
 
         let config = Config::from_cli(cli_too_high);
@@ -375,9 +1363,260 @@ mod tests {
             verbose: false,
             show_cursor: false,
             scale: Some(-1.5), // Invalid
+            ..default_cli()
         };
 
         let config = Config::from_cli(cli_negative);
         assert_eq!(config.scale, None); // Should be rejected
     }
+
+    #[test]
+    fn test_dock_size_clamping() {
+        let config = Config::from_cli(Cli {
+            dock_size: 10,
+            ..default_cli()
+        });
+        assert_eq!(config.dock_size, 50);
+
+        let config = Config::from_cli(Cli {
+            mode: ViewMode::Docked,
+            dock_edge: DockEdge::Left,
+            dock_size: 400,
+            ..default_cli()
+        });
+        assert_eq!(config.mode, ViewMode::Docked);
+        assert!(!config.dock_edge.is_horizontal());
+        assert_eq!(config.dock_size, 400);
+
+        let monitor = Vector2D::new(1920.0, 1080.0);
+        let pane = Vector2D::new(1920.0, 300.0);
+        assert_eq!(
+            DockEdge::Bottom.area(monitor, pane).pos,
+            Vector2D::new(0.0, 780.0)
+        );
+        let pane = Vector2D::new(400.0, 1080.0);
+        assert_eq!(
+            DockEdge::Right.area(monitor, pane).pos,
+            Vector2D::new(1520.0, 0.0)
+        );
+        assert_eq!(DockEdge::Top.area(monitor, pane).pos, Vector2D::default());
+    }
+
+    #[test]
+    fn test_continuous_options() {
+        let config = Config::from_cli(default_cli());
+        assert!(!config.continuous_capture);
+        assert_eq!(config.refresh_rate, 30);
+
+        let config = Config::from_cli(Cli::parse_from(["remagnify", "-c"]));
+        assert!(config.continuous_capture);
+
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--continuous",
+            "false",
+            "--refresh-rate",
+            "500",
+        ]));
+        assert!(!config.continuous_capture);
+        assert_eq!(config.refresh_rate, 60);
+
+        let config = Config::from_cli(Cli::parse_from(["remagnify", "--continuous=true"]));
+        assert!(config.continuous_capture);
+        assert!(!config.region_capture);
+
+        let config = Config::from_cli(Cli::parse_from(["remagnify", "--capture-region"]));
+        assert!(config.region_capture);
+        assert!(!config.continuous_capture);
+    }
+
+    #[test]
+    fn test_capture_backend_options() {
+        let config = Config::from_cli(default_cli());
+        assert_eq!(config.capture_backend, CaptureBackend::Auto);
+        assert_eq!(config.capture_image, None);
+
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--capture-backend",
+            "ext-image-copy",
+        ]));
+        assert_eq!(config.capture_backend, CaptureBackend::ExtImageCopy);
+
+        // An image alone is enough to pick the image backend
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--capture-image",
+            "/tmp/desktop.png",
+        ]));
+        assert_eq!(config.capture_backend, CaptureBackend::Image);
+        assert_eq!(
+            config.capture_image,
+            Some(PathBuf::from("/tmp/desktop.png"))
+        );
+    }
+
+    #[test]
+    fn test_color_options() {
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--color",
+            "smart-invert",
+            "--brightness",
+            "-2",
+            "--gamma",
+            "1.8",
+        ]));
+        assert_eq!(config.color.mode, ColorMode::SmartInvert);
+        assert_eq!(config.color.brightness, -1.0);
+        assert_eq!(config.color.contrast, 1.0);
+        assert_eq!(config.color.gamma, 1.8);
+
+        assert_eq!(ColorMode::Normal.next(), ColorMode::Invert);
+        assert_eq!(ColorMode::HighContrast.next(), ColorMode::Normal);
+        assert_eq!(ColorMode::Normal.prev(), ColorMode::HighContrast);
+    }
+
+    #[test]
+    fn test_filter_options() {
+        let config = Config::from_cli(default_cli());
+        assert_eq!(config.filter, ScaleFilter::Auto);
+        assert_eq!(config.filter_threshold, 4.0);
+
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--filter",
+            "xbr",
+            "--filter-threshold",
+            "0.5",
+        ]));
+        assert_eq!(config.filter, ScaleFilter::Xbr);
+        assert_eq!(config.filter_threshold, 1.0);
+    }
+
+    #[test]
+    fn test_text_options() {
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--sharpen",
+            "--sharpen-radius",
+            "20",
+            "--reading",
+            "light-on-dark",
+        ]));
+        assert!(config.text.sharpen);
+        assert_eq!(config.text.radius, 10.0);
+        assert_eq!(config.text.amount, 1.0);
+        assert_eq!(config.text.reading, ReadingMode::LightOnDark);
+
+        assert_eq!(ReadingMode::LightOnDark.next(), ReadingMode::Off);
+    }
+
+    #[test]
+    fn test_animation_options() {
+        let config = Config::from_cli(default_cli());
+        assert_eq!(config.animation_ms, 150);
+        assert_eq!(config.easing, Easing::EaseOut);
+
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--animation-duration",
+            "9000",
+            "--easing",
+            "ease-in-out",
+        ]));
+        assert_eq!(config.animation_ms, 2000);
+        assert_eq!(config.easing, Easing::EaseInOut);
+
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--animation-duration",
+            "300",
+            "--no-animation",
+        ]));
+        assert_eq!(config.animation_ms, 0);
+    }
+
+    #[test]
+    fn test_smoothing_options() {
+        let config = Config::from_cli(default_cli());
+        assert_eq!(config.smoothing, Smoothing::default());
+
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--smoothing",
+            "inertia",
+            "--smoothing-time",
+            "5",
+            "--dead-zone",
+            "3",
+            "--lead",
+            "500",
+        ]));
+        assert_eq!(config.smoothing.mode, SmoothingMode::Inertia);
+        assert_eq!(config.smoothing.time, Duration::from_millis(10));
+        assert_eq!(config.smoothing.dead_zone, 3.0);
+        assert_eq!(config.smoothing.lead, Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_pick_options() {
+        let config = Config::from_cli(default_cli());
+        assert_eq!(config.pick_size, 1);
+        assert_eq!(config.pick_format, PickFormat::Hex);
+
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--pick-size",
+            "99",
+            "--pick-format",
+            "oklch",
+        ]));
+        assert_eq!(config.pick_size, 15);
+        assert_eq!(config.pick_format, PickFormat::Oklch);
+        assert!(!config.measure);
+
+        let config = Config::from_cli(Cli::parse_from(["remagnify", "--measure"]));
+        assert!(config.measure);
+    }
+
+    #[test]
+    fn test_spotlight_options() {
+        let config = Config::from_cli(default_cli());
+        assert_eq!(config.spotlight, Spotlight::default());
+
+        let config = Config::from_cli(Cli::parse_from([
+            "remagnify",
+            "--spotlight",
+            "--spotlight-dim",
+            "1.5",
+            "--spotlight-feather",
+            "40",
+        ]));
+        assert!(config.spotlight.enabled);
+        assert_eq!(config.spotlight.dim, 0.95);
+        assert_eq!(config.spotlight.feather, 40.0);
+    }
+
+    #[test]
+    fn test_spotlight_bands() {
+        // A hard edge is a single clear band: the lens
+        assert_eq!(Spotlight::default().bands(), [(0.0, 0.0)]);
+
+        let spotlight = Spotlight {
+            enabled: true,
+            dim: 0.5,
+            feather: 48.0,
+        };
+        let bands = spotlight.bands();
+        assert_eq!(bands.len(), Spotlight::FEATHER_STEPS);
+        assert_eq!(*bands.last().unwrap(), (0.0, 0.0));
+        // Bands shrink toward the lens and get clearer, without reaching
+        // the full dim that surrounds them
+        for pair in bands.windows(2) {
+            assert!(pair[1].0 < pair[0].0);
+            assert!(pair[1].1 < pair[0].1);
+        }
+        assert!(bands[0].0 < 48.0 && bands[0].1 < 0.5);
+    }
 }
@@ -0,0 +1,395 @@
+//! Pointer drawn inside the magnified view.
+//!
+//! The real cursor is hidden over the overlay and screencopy captures
+//! without it, so the magnified view draws its own at the pointer's
+//! hotspot, scaled with the magnification. The image comes from the
+//! user's Xcursor theme (`XCURSOR_THEME`, `XCURSOR_SIZE`, `XCURSOR_PATH`)
+//! when one can be found; otherwise a built-in arrow is drawn.
+
+use crate::config::CursorStyle;
+use crate::effects::Image;
+use crate::utils::Vector2D;
+use anyhow::Result;
+use cairo::{Filter, SurfacePattern};
+use std::path::PathBuf;
+
+/// Cursor size when `XCURSOR_SIZE` is unset, in logical pixels.
+const DEFAULT_SIZE: f64 = 24.0;
+
+/// Cursor names tried in the theme, most specific first.
+const CURSOR_NAMES: [&str; 3] = ["left_ptr", "default", "arrow"];
+
+/// Directories searched for themes when `XCURSOR_PATH` is unset; `~` is
+/// the home directory.
+const DEFAULT_SEARCH_PATH: &str =
+    "~/.local/share/icons:~/.icons:/usr/share/icons:/usr/share/pixmaps";
+
+/// How many levels of `Inherits=` are followed when looking up a theme.
+const MAX_INHERIT_DEPTH: usize = 8;
+
+/// Xcursor chunk type of an image.
+const XCURSOR_IMAGE_TYPE: u32 = 0xfffd_0002;
+
+/// One size of a themed cursor.
+#[derive(Debug, Clone, PartialEq)]
+struct CursorImage {
+    /// Size the image was designed for, in pixels
+    nominal: u32,
+    hotspot: Vector2D,
+    image: Image,
+}
+
+/// The cursor drawn in the magnified view.
+#[derive(Debug, Clone)]
+pub struct Cursor {
+    style: CursorStyle,
+    /// Logical size of the cursor at 1x
+    size: f64,
+    /// Theme images, one per nominal size
+    images: Vec<CursorImage>,
+}
+
+impl Default for Cursor {
+    fn default() -> Self {
+        Self {
+            style: CursorStyle::None,
+            size: DEFAULT_SIZE,
+            images: Vec::new(),
+        }
+    }
+}
+
+impl Cursor {
+    /// Prepare a cursor of `style`, loading the theme if it asks for one.
+    ///
+    /// A theme that can't be found falls back to the built-in arrow.
+    pub fn load(style: CursorStyle) -> Self {
+        let size = std::env::var("XCURSOR_SIZE")
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .filter(|s| *s > 0.0)
+            .unwrap_or(DEFAULT_SIZE);
+
+        let mut cursor = Self {
+            style,
+            size,
+            images: Vec::new(),
+        };
+        if style != CursorStyle::Theme {
+            return cursor;
+        }
+
+        let theme = std::env::var("XCURSOR_THEME").unwrap_or_else(|_| "default".to_string());
+        let search_path = search_path();
+        let loaded = CURSOR_NAMES.iter().find_map(|name| {
+            let path = find_cursor(&search_path, &theme, name, 0)?;
+            let data = std::fs::read(&path).ok()?;
+            let images = parse_xcursor(&data);
+            (!images.is_empty()).then_some((path, images))
+        });
+
+        match loaded {
+            Some((path, images)) => {
+                log::info!("Loaded cursor from {}", path.display());
+                cursor.images = images;
+            }
+            None => {
+                log::info!("Cursor theme '{}' not found, using built-in arrow", theme);
+                cursor.style = CursorStyle::Arrow;
+            }
+        }
+        cursor
+    }
+
+    /// Draw the cursor with its hotspot at `hotspot`, `scale` times its
+    /// normal size.
+    pub fn draw(&self, ctx: &cairo::Context, hotspot: Vector2D, scale: f64) -> Result<()> {
+        match self.style {
+            CursorStyle::None => Ok(()),
+            CursorStyle::Arrow => draw_arrow(ctx, hotspot, self.size * scale),
+            CursorStyle::Crosshair => draw_crosshair(ctx, hotspot, self.size * scale),
+            CursorStyle::Theme => self.draw_themed(ctx, hotspot, self.size * scale),
+        }
+    }
+
+    fn draw_themed(&self, ctx: &cairo::Context, hotspot: Vector2D, size: f64) -> Result<()> {
+        let Some(cursor) = best_image(&self.images, size) else {
+            return Ok(());
+        };
+
+        let surface = cursor.image.clone().into_surface()?;
+        let k = size / cursor.nominal as f64;
+
+        ctx.save()?;
+        ctx.translate(hotspot.x, hotspot.y);
+        ctx.scale(k, k);
+        let pattern = SurfacePattern::create(&surface);
+        // Keep the theme's pixels visible once they are clearly enlarged,
+        // like the magnified content around it
+        pattern.set_filter(if k >= 3.0 {
+            Filter::Nearest
+        } else {
+            Filter::Good
+        });
+        pattern.set_matrix(cairo::Matrix::new(
+            1.0,
+            0.0,
+            0.0,
+            1.0,
+            cursor.hotspot.x,
+            cursor.hotspot.y,
+        ));
+        ctx.set_source(&pattern)?;
+        ctx.paint()?;
+        ctx.restore()?;
+
+        Ok(())
+    }
+}
+
+/// The image to scale to `size`: the smallest one at least that big, or
+/// the largest there is.
+fn best_image(images: &[CursorImage], size: f64) -> Option<&CursorImage> {
+    images
+        .iter()
+        .filter(|i| i.nominal as f64 >= size)
+        .min_by_key(|i| i.nominal)
+        .or_else(|| images.iter().max_by_key(|i| i.nominal))
+}
+
+/// Classic arrow pointer with its tip at `hotspot`, `size` pixels tall.
+fn draw_arrow(ctx: &cairo::Context, hotspot: Vector2D, size: f64) -> Result<()> {
+    // Outline on a 24-unit grid, tip at the origin
+    const POINTS: [(f64, f64); 7] = [
+        (0.0, 0.0),
+        (0.0, 17.0),
+        (4.0, 13.0),
+        (7.0, 19.5),
+        (9.5, 18.5),
+        (6.5, 12.0),
+        (12.0, 12.0),
+    ];
+    let k = size / 24.0;
+
+    ctx.save()?;
+    ctx.translate(hotspot.x, hotspot.y);
+    ctx.scale(k, k);
+    ctx.new_path();
+    for (x, y) in POINTS {
+        ctx.line_to(x, y);
+    }
+    ctx.close_path();
+    ctx.set_source_rgba(1.0, 1.0, 1.0, 1.0);
+    ctx.fill_preserve()?;
+    ctx.set_source_rgba(0.0, 0.0, 0.0, 1.0);
+    ctx.set_line_width(1.0);
+    ctx.set_line_join(cairo::LineJoin::Round);
+    ctx.stroke()?;
+    ctx.restore()?;
+
+    Ok(())
+}
+
+/// Crosshair centred exactly on `hotspot`, `size` pixels across.
+fn draw_crosshair(ctx: &cairo::Context, hotspot: Vector2D, size: f64) -> Result<()> {
+    let r = size / 2.0;
+    // Leave the hotspot itself uncovered so the pixel under it shows
+    let gap = (size / 8.0).max(2.0);
+
+    ctx.save()?;
+    ctx.new_path();
+    for (dx, dy) in [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)] {
+        ctx.move_to(hotspot.x + dx * gap, hotspot.y + dy * gap);
+        ctx.line_to(hotspot.x + dx * r, hotspot.y + dy * r);
+    }
+    // Two strokes so it shows on any colour, like the pixel marker
+    ctx.set_line_width(3.0);
+    ctx.set_source_rgba(0.0, 0.0, 0.0, 0.8);
+    ctx.stroke_preserve()?;
+    ctx.set_line_width(1.0);
+    ctx.set_source_rgba(1.0, 1.0, 1.0, 1.0);
+    ctx.stroke()?;
+    ctx.restore()?;
+
+    Ok(())
+}
+
+/// Theme search directories from `XCURSOR_PATH` or the defaults.
+fn search_path() -> Vec<PathBuf> {
+    let path = std::env::var("XCURSOR_PATH").unwrap_or_else(|_| DEFAULT_SEARCH_PATH.to_string());
+    let home = std::env::var("HOME").unwrap_or_default();
+    path.split(':')
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| match dir.strip_prefix('~') {
+            Some(rest) => PathBuf::from(format!("{}{}", home, rest)),
+            None => PathBuf::from(dir),
+        })
+        .collect()
+}
+
+/// Find cursor `name` in `theme` or the themes it inherits from.
+fn find_cursor(search_path: &[PathBuf], theme: &str, name: &str, depth: usize) -> Option<PathBuf> {
+    if depth > MAX_INHERIT_DEPTH {
+        return None;
+    }
+
+    for dir in search_path {
+        let path = dir.join(theme).join("cursors").join(name);
+        if path.is_file() {
+            return Some(path);
+        }
+    }
+
+    search_path
+        .iter()
+        .filter_map(|dir| std::fs::read_to_string(dir.join(theme).join("index.theme")).ok())
+        .flat_map(|index| inherited_themes(&index))
+        .filter(|parent| parent != theme)
+        .find_map(|parent| find_cursor(search_path, &parent, name, depth + 1))
+}
+
+/// Themes listed on the `Inherits=` line of an `index.theme`.
+fn inherited_themes(index: &str) -> Vec<String> {
+    index
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("Inherits"))
+        .filter_map(|rest| rest.trim_start().strip_prefix('='))
+        .flat_map(|list| list.split([',', ';']))
+        .map(|theme| theme.trim().to_string())
+        .filter(|theme| !theme.is_empty())
+        .collect()
+}
+
+/// Read the images from an Xcursor file, keeping the first frame of each
+/// size. Malformed chunks are skipped.
+fn parse_xcursor(data: &[u8]) -> Vec<CursorImage> {
+    let u32_at = |offset: usize| -> Option<u32> {
+        let bytes = data.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    };
+
+    if data.get(0..4) != Some(b"Xcur".as_slice()) {
+        return Vec::new();
+    }
+    let (Some(header_size), Some(toc_len)) = (u32_at(4), u32_at(12)) else {
+        return Vec::new();
+    };
+
+    let mut images: Vec<CursorImage> = Vec::new();
+    for i in 0..toc_len as usize {
+        let entry = header_size as usize + i * 12;
+        let (Some(kind), Some(nominal), Some(position)) =
+            (u32_at(entry), u32_at(entry + 4), u32_at(entry + 8))
+        else {
+            break;
+        };
+        if kind != XCURSOR_IMAGE_TYPE || images.iter().any(|i| i.nominal == nominal) {
+            continue;
+        }
+        if let Some(image) = parse_image(data, position as usize, nominal) {
+            images.push(image);
+        }
+    }
+    images
+}
+
+fn parse_image(data: &[u8], position: usize, nominal: u32) -> Option<CursorImage> {
+    let u32_at = |offset: usize| -> Option<u32> {
+        let bytes = data.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    };
+
+    let header_size = u32_at(position)? as usize;
+    let width = u32_at(position + 16)? as usize;
+    let height = u32_at(position + 20)? as usize;
+    let xhot = u32_at(position + 24)?;
+    let yhot = u32_at(position + 28)?;
+    // The format caps images at 0x7fff per side
+    if width == 0 || height == 0 || width > 0x7fff || height > 0x7fff {
+        return None;
+    }
+
+    let start = position + header_size;
+    let bytes = data.get(start..start + width * height * 4)?;
+    let pixels = bytes
+        .chunks_exact(4)
+        .map(|px| u32::from_le_bytes([px[0], px[1], px[2], px[3]]))
+        .collect();
+
+    Some(CursorImage {
+        nominal,
+        hotspot: Vector2D::new(xhot as f64, yhot as f64),
+        image: Image {
+            width,
+            height,
+            pixels,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build an Xcursor file with one solid image per `(nominal, side)`.
+    fn xcursor(sizes: &[(u32, u32)]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let push = |data: &mut Vec<u8>, v: u32| data.extend_from_slice(&v.to_le_bytes());
+
+        data.extend_from_slice(b"Xcur");
+        push(&mut data, 16);
+        push(&mut data, 0x1_0000);
+        push(&mut data, sizes.len() as u32);
+
+        let mut position = 16 + sizes.len() as u32 * 12;
+        for &(nominal, side) in sizes {
+            push(&mut data, XCURSOR_IMAGE_TYPE);
+            push(&mut data, nominal);
+            push(&mut data, position);
+            position += 36 + side * side * 4;
+        }
+        for &(nominal, side) in sizes {
+            for v in [36, XCURSOR_IMAGE_TYPE, nominal, 1, side, side, 1, 2, 0] {
+                push(&mut data, v);
+            }
+            for _ in 0..side * side {
+                push(&mut data, 0xff00_0000 | nominal);
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_xcursor() {
+        let images = parse_xcursor(&xcursor(&[(24, 24), (48, 48), (24, 24)]));
+        // The repeated size is a second animation frame and is dropped
+        assert_eq!(images.len(), 2);
+        assert_eq!(images[0].nominal, 24);
+        assert_eq!(images[0].hotspot, Vector2D::new(1.0, 2.0));
+        assert_eq!(images[1].image.width, 48);
+        assert_eq!(images[1].image.at(5, 5), 0xff00_0030);
+
+        // Truncated or foreign files yield nothing rather than panicking
+        let mut truncated = xcursor(&[(24, 24)]);
+        truncated.truncate(100);
+        assert!(parse_xcursor(&truncated).is_empty());
+        assert!(parse_xcursor(b"GIF89a").is_empty());
+    }
+
+    #[test]
+    fn test_best_image() {
+        let images = parse_xcursor(&xcursor(&[(24, 2), (32, 2), (48, 2)]));
+        assert_eq!(best_image(&images, 20.0).unwrap().nominal, 24);
+        assert_eq!(best_image(&images, 30.0).unwrap().nominal, 32);
+        // Larger than anything in the theme: upscale the largest
+        assert_eq!(best_image(&images, 96.0).unwrap().nominal, 48);
+        assert!(best_image(&[], 24.0).is_none());
+    }
+
+    #[test]
+    fn test_inherited_themes() {
+        let index = "[Icon Theme]\nName=Foo\nInherits = Adwaita, hicolor;\n";
+        assert_eq!(inherited_themes(index), ["Adwaita", "hicolor"]);
+        assert!(inherited_themes("[Icon Theme]\nName=Foo\n").is_empty());
+    }
+}
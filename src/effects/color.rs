@@ -0,0 +1,205 @@
+//! Accessibility colour transforms.
+//!
+//! Provides the colour modes (invert, smart invert, grayscale, high
+//! contrast) and brightness/contrast/gamma levels applied inside the lens.
+
+use crate::config::ColorMode;
+
+/// Colour transform settings for the magnified area.
+///
+/// The mode is applied first, then the brightness, contrast and gamma
+/// levels, so the levels always adjust what ends up on screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorFilter {
+    pub mode: ColorMode,
+    /// Added to every channel (-1.0..=1.0, 0.0 = unchanged)
+    pub brightness: f64,
+    /// Multiplier around mid-grey (0.0..=4.0, 1.0 = unchanged)
+    pub contrast: f64,
+    /// Gamma exponent (0.1..=5.0, 1.0 = unchanged, higher = brighter mid-tones)
+    pub gamma: f64,
+}
+
+impl Default for ColorFilter {
+    fn default() -> Self {
+        Self {
+            mode: ColorMode::Normal,
+            brightness: 0.0,
+            contrast: 1.0,
+            gamma: 1.0,
+        }
+    }
+}
+
+impl ColorFilter {
+    /// Set brightness, clamped to -1.0..=1.0.
+    pub fn set_brightness(&mut self, brightness: f64) {
+        self.brightness = brightness.clamp(-1.0, 1.0);
+    }
+
+    /// Set contrast, clamped to 0.0..=4.0.
+    pub fn set_contrast(&mut self, contrast: f64) {
+        self.contrast = contrast.clamp(0.0, 4.0);
+    }
+
+    /// Set gamma, clamped to 0.1..=5.0.
+    pub fn set_gamma(&mut self, gamma: f64) {
+        self.gamma = gamma.clamp(0.1, 5.0);
+    }
+
+    /// Whether the filter leaves every pixel unchanged.
+    pub fn is_identity(&self) -> bool {
+        self.mode == ColorMode::Normal
+            && self.brightness == 0.0
+            && self.contrast == 1.0
+            && self.gamma == 1.0
+    }
+
+    /// Build the per-pixel transform for the current settings.
+    ///
+    /// The levels are folded into a lookup table once, so the returned
+    /// closure is cheap enough to run on every pixel of the lens.
+    pub fn compile(&self) -> impl Fn([u8; 3]) -> [u8; 3] {
+        let mode = self.mode;
+        let lut = self.levels_lut();
+        move |rgb| {
+            let [r, g, b] = apply_mode(mode, rgb);
+            [lut[r as usize], lut[g as usize], lut[b as usize]]
+        }
+    }
+
+    fn levels_lut(&self) -> [u8; 256] {
+        let mut lut = [0u8; 256];
+        for (i, entry) in lut.iter_mut().enumerate() {
+            let mut v = i as f64 / 255.0;
+            v += self.brightness;
+            v = (v - 0.5) * self.contrast + 0.5;
+            v = v.clamp(0.0, 1.0).powf(1.0 / self.gamma);
+            *entry = (v * 255.0).round() as u8;
+        }
+        lut
+    }
+}
+
+fn apply_mode(mode: ColorMode, rgb: [u8; 3]) -> [u8; 3] {
+    let [r, g, b] = rgb;
+    match mode {
+        ColorMode::Normal => rgb,
+        ColorMode::Invert => [255 - r, 255 - g, 255 - b],
+        ColorMode::SmartInvert => {
+            let (h, s, l) = rgb_to_hsl(rgb);
+            hsl_to_rgb(h, s, 1.0 - l)
+        }
+        ColorMode::Grayscale => {
+            let y = luma(rgb).round() as u8;
+            [y, y, y]
+        }
+        ColorMode::HighContrast => {
+            let steep = |c: u8| {
+                let v = (c as f64 / 255.0 - 0.5) * 3.0 + 0.5;
+                (v.clamp(0.0, 1.0) * 255.0).round() as u8
+            };
+            [steep(r), steep(g), steep(b)]
+        }
+    }
+}
+
+/// Rec. 709 luma of an sRGB pixel, in 0.0..=255.0.
+pub fn luma([r, g, b]: [u8; 3]) -> f64 {
+    0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64
+}
+
+/// Convert RGB to hue (degrees), saturation and lightness (0.0..=1.0).
+pub fn rgb_to_hsl([r, g, b]: [u8; 3]) -> (f64, f64, f64) {
+    let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.0;
+    let d = max - min;
+
+    if d == 0.0 {
+        return (0.0, 0.0, l);
+    }
+
+    let s = d / (1.0 - (2.0 * l - 1.0).abs());
+    let h = if max == r {
+        60.0 * ((g - b) / d).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / d + 2.0)
+    } else {
+        60.0 * ((r - g) / d + 4.0)
+    };
+
+    (h, s, l)
+}
+
+/// Convert hue (degrees), saturation and lightness (0.0..=1.0) to RGB.
+pub fn hsl_to_rgb(h: f64, s: f64, l: f64) -> [u8; 3] {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let hp = h.rem_euclid(360.0) / 60.0;
+    let x = c * (1.0 - (hp % 2.0 - 1.0).abs());
+    let (r, g, b) = match hp as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    let to_u8 = |v: f64| ((v + m).clamp(0.0, 1.0) * 255.0).round() as u8;
+    [to_u8(r), to_u8(g), to_u8(b)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_filter() {
+        let filter = ColorFilter::default();
+        assert!(filter.is_identity());
+        let f = filter.compile();
+        for rgb in [[0, 0, 0], [12, 200, 99], [255, 255, 255]] {
+            assert_eq!(f(rgb), rgb);
+        }
+    }
+
+    #[test]
+    fn test_color_modes() {
+        assert_eq!(apply_mode(ColorMode::Invert, [0, 100, 255]), [255, 155, 0]);
+        assert_eq!(apply_mode(ColorMode::Grayscale, [255, 255, 255]), [255; 3]);
+
+        // Smart invert turns white into black and keeps the hue of colours
+        assert_eq!(apply_mode(ColorMode::SmartInvert, [255, 255, 255]), [0; 3]);
+        let dark_red = apply_mode(ColorMode::SmartInvert, [255, 200, 200]);
+        assert!(dark_red[0] > dark_red[1] && dark_red[1] == dark_red[2]);
+
+        assert_eq!(
+            apply_mode(ColorMode::HighContrast, [40, 128, 220]),
+            [0, 129, 255]
+        );
+    }
+
+    #[test]
+    fn test_levels() {
+        let mut filter = ColorFilter::default();
+        filter.set_brightness(0.5);
+        assert_eq!(filter.compile()([0, 0, 0]), [128, 128, 128]);
+
+        filter = ColorFilter::default();
+        filter.set_contrast(0.0);
+        assert_eq!(filter.compile()([0, 255, 10]), [128, 128, 128]);
+
+        filter.set_gamma(100.0);
+        assert_eq!(filter.gamma, 5.0);
+    }
+
+    #[test]
+    fn test_hsl_round_trip() {
+        for rgb in [[255, 0, 0], [12, 200, 99], [128, 128, 128], [3, 7, 250]] {
+            let (h, s, l) = rgb_to_hsl(rgb);
+            assert_eq!(hsl_to_rgb(h, s, l), rgb);
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! Colour-vision deficiency filters.
+//!
+//! Simulation uses the Machado et al. (2009) matrices at full severity,
+//! applied in linear RGB. Correction (daltonization) redistributes the
+//! information lost by the simulation onto channels the viewer can still
+//! tell apart.
+
+use std::sync::OnceLock;
+
+/// Type of colour-vision deficiency.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CvdKind {
+    /// Missing long-wavelength (red) cones.
+    Protanopia,
+    /// Missing medium-wavelength (green) cones.
+    Deuteranopia,
+    /// Missing short-wavelength (blue) cones.
+    Tritanopia,
+}
+
+/// Whether to show what a colour-blind viewer sees, or help them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CvdMode {
+    /// Show the screen as a viewer with the deficiency would see it.
+    #[default]
+    Simulate,
+    /// Daltonize: shift lost contrast to distinguishable channels.
+    Correct,
+}
+
+/// Colour-vision deficiency filter settings.
+///
+/// `kind` of `None` disables the filter while remembering the mode, so it
+/// can be toggled at runtime without losing the user's choice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CvdFilter {
+    pub kind: Option<CvdKind>,
+    pub mode: CvdMode,
+}
+
+type Mat3 = [[f32; 3]; 3];
+
+const PROTANOPIA: Mat3 = [
+    [0.152286, 1.052583, -0.204868],
+    [0.114503, 0.786281, 0.099216],
+    [-0.003882, -0.048116, 1.051998],
+];
+
+const DEUTERANOPIA: Mat3 = [
+    [0.367322, 0.860646, -0.227968],
+    [0.280085, 0.672501, 0.047413],
+    [-0.011820, 0.042940, 0.968881],
+];
+
+const TRITANOPIA: Mat3 = [
+    [1.255528, -0.076749, -0.178779],
+    [-0.078411, 0.930809, 0.147602],
+    [0.004733, 0.691367, 0.303900],
+];
+
+/// Moves red/green error into green and blue.
+const SHIFT_RED_GREEN: Mat3 = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+
+/// Moves blue/yellow error into red and green.
+const SHIFT_BLUE_YELLOW: Mat3 = [[1.0, 0.0, 0.7], [0.0, 1.0, 0.7], [0.0, 0.0, 0.0]];
+
+impl CvdKind {
+    /// The kind after this one; `None` after the last, `Some(Protanopia)` after `None`.
+    pub fn cycle(kind: Option<CvdKind>) -> Option<CvdKind> {
+        match kind {
+            None => Some(CvdKind::Protanopia),
+            Some(CvdKind::Protanopia) => Some(CvdKind::Deuteranopia),
+            Some(CvdKind::Deuteranopia) => Some(CvdKind::Tritanopia),
+            Some(CvdKind::Tritanopia) => None,
+        }
+    }
+
+    fn simulation(self) -> &'static Mat3 {
+        match self {
+            CvdKind::Protanopia => &PROTANOPIA,
+            CvdKind::Deuteranopia => &DEUTERANOPIA,
+            CvdKind::Tritanopia => &TRITANOPIA,
+        }
+    }
+
+    fn error_shift(self) -> &'static Mat3 {
+        match self {
+            CvdKind::Protanopia | CvdKind::Deuteranopia => &SHIFT_RED_GREEN,
+            CvdKind::Tritanopia => &SHIFT_BLUE_YELLOW,
+        }
+    }
+}
+
+impl CvdMode {
+    /// The other mode.
+    pub fn toggle(self) -> Self {
+        match self {
+            CvdMode::Simulate => CvdMode::Correct,
+            CvdMode::Correct => CvdMode::Simulate,
+        }
+    }
+}
+
+impl CvdFilter {
+    /// Whether the filter leaves every pixel unchanged.
+    pub fn is_identity(&self) -> bool {
+        self.kind.is_none()
+    }
+
+    /// Build the per-pixel transform for the current settings.
+    pub fn compile(&self) -> impl Fn([u8; 3]) -> [u8; 3] {
+        let kind = self.kind;
+        let mode = self.mode;
+        move |rgb| match kind {
+            None => rgb,
+            Some(kind) => transform(kind, mode, rgb),
+        }
+    }
+}
+
+fn transform(kind: CvdKind, mode: CvdMode, rgb: [u8; 3]) -> [u8; 3] {
+    let decode = decode_lut();
+    let linear = rgb.map(|c| decode[c as usize]);
+    let simulated = mul(kind.simulation(), linear);
+
+    let out = match mode {
+        CvdMode::Simulate => simulated,
+        CvdMode::Correct => {
+            let error = [
+                linear[0] - simulated[0],
+                linear[1] - simulated[1],
+                linear[2] - simulated[2],
+            ];
+            let shift = mul(kind.error_shift(), error);
+            [
+                linear[0] + shift[0],
+                linear[1] + shift[1],
+                linear[2] + shift[2],
+            ]
+        }
+    };
+
+    out.map(encode)
+}
+
+fn mul(m: &Mat3, v: [f32; 3]) -> [f32; 3] {
+    [
+        m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+        m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+        m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2],
+    ]
+}
+
+/// sRGB byte to linear light, tabulated once.
+fn decode_lut() -> &'static [f32; 256] {
+    static LUT: OnceLock<[f32; 256]> = OnceLock::new();
+    LUT.get_or_init(|| {
+        let mut lut = [0.0; 256];
+        for (i, v) in lut.iter_mut().enumerate() {
+            let c = i as f32 / 255.0;
+            *v = if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            };
+        }
+        lut
+    })
+}
+
+/// Linear light back to an sRGB byte, via a 4096-step table.
+fn encode(v: f32) -> u8 {
+    static LUT: OnceLock<Vec<u8>> = OnceLock::new();
+    let lut = LUT.get_or_init(|| {
+        (0..4096)
+            .map(|i| {
+                let c = i as f32 / 4095.0;
+                let s = if c <= 0.003_130_8 {
+                    c * 12.92
+                } else {
+                    1.055 * c.powf(1.0 / 2.4) - 0.055
+                };
+                (s * 255.0).round() as u8
+            })
+            .collect()
+    });
+    lut[(v.clamp(0.0, 1.0) * 4095.0).round() as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_greys_are_unchanged() {
+        // Every simulation matrix preserves achromatic colours
+        for kind in [
+            CvdKind::Protanopia,
+            CvdKind::Deuteranopia,
+            CvdKind::Tritanopia,
+        ] {
+            for mode in [CvdMode::Simulate, CvdMode::Correct] {
+                for grey in [0u8, 128, 255] {
+                    let out = transform(kind, mode, [grey; 3]);
+                    for c in out {
+                        assert!((c as i32 - grey as i32).abs() <= 1, "{:?} {:?}", kind, mode);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_red_green_confusion() {
+        let distance = |a: [u8; 3], b: [u8; 3]| {
+            a.iter()
+                .zip(b)
+                .map(|(x, y)| (*x as f64 - y as f64).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        };
+        let (red, green) = ([255, 0, 0], [0, 160, 0]);
+
+        // Protanopes and deuteranopes can barely tell red from green
+        for kind in [CvdKind::Protanopia, CvdKind::Deuteranopia] {
+            let seen = distance(
+                transform(kind, CvdMode::Simulate, red),
+                transform(kind, CvdMode::Simulate, green),
+            );
+            assert!(seen < 0.3 * distance(red, green), "{:?}", kind);
+        }
+
+        // Correction moves the lost red information into blue
+        let corrected = transform(CvdKind::Protanopia, CvdMode::Correct, red);
+        assert!(corrected[2] > 0);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut kind = None;
+        let mut seen = Vec::new();
+        for _ in 0..4 {
+            kind = CvdKind::cycle(kind);
+            seen.push(kind);
+        }
+        assert_eq!(
+            seen,
+            vec![
+                Some(CvdKind::Protanopia),
+                Some(CvdKind::Deuteranopia),
+                Some(CvdKind::Tritanopia),
+                None
+            ]
+        );
+        assert_eq!(CvdMode::Simulate.toggle(), CvdMode::Correct);
+    }
+}
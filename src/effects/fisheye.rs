@@ -0,0 +1,161 @@
+//! Fisheye remapping for the lens.
+//!
+//! A flat lens is an affine transform that Cairo applies itself. The fisheye
+//! lens magnifies most at its centre and eases back to 1x at its edge, so the
+//! border joins up with the unmagnified screen around it. That needs a
+//! per-pixel lookup into the capture, which Cairo's matrices can't express.
+
+use super::Image;
+use crate::utils::{Rect, Vector2D};
+
+/// Radial magnification that falls off toward the lens edge.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fisheye {
+    /// Magnification at the lens centre
+    pub magnification: f64,
+    /// How long the magnification holds before falling off; higher values
+    /// keep a larger undistorted centre and squeeze the border harder
+    pub strength: f64,
+}
+
+impl Fisheye {
+    /// Source radius shown at output radius `r`, both as fractions of the
+    /// lens radius.
+    ///
+    /// `r / (M - (M - 1) * r^s)` magnifies by M at the centre, reaches 1 at
+    /// the edge and grows monotonically in between. Outside the lens (the
+    /// corners of a rectangular one) the mapping is the identity.
+    pub fn source_radius(&self, r: f64) -> f64 {
+        if r >= 1.0 {
+            return r;
+        }
+        let m = self.magnification.max(1.0);
+        r / (m - (m - 1.0) * r.powf(self.strength))
+    }
+
+    /// Render the lens from `src`.
+    ///
+    /// The result is `size` pixels; `lens` is the lens ellipse in the same
+    /// coordinates. The lens centre shows `source` (in `src` pixels) and
+    /// `scale` converts output pixels to `src` pixels at 1x. `smooth` picks
+    /// bilinear over nearest-neighbour sampling.
+    pub fn remap(
+        &self,
+        src: &Image,
+        size: (usize, usize),
+        lens: Rect,
+        source: Vector2D,
+        scale: Vector2D,
+        smooth: bool,
+    ) -> Image {
+        let (width, height) = size;
+        let center = lens.pos + lens.size / 2.0;
+        let radius = lens.size / 2.0;
+        let mut dst = Image::new(width, height, 0);
+
+        for y in 0..height {
+            for x in 0..width {
+                let d = Vector2D::new(x as f64 + 0.5, y as f64 + 0.5) - center;
+                let r = (d / radius).length();
+                let k = if r > 0.0 {
+                    self.source_radius(r) / r
+                } else {
+                    1.0 / self.magnification.max(1.0)
+                };
+                let p = source + d * scale * k;
+
+                dst.pixels[y * width + x] = if smooth {
+                    bilinear(src, p.x - 0.5, p.y - 0.5)
+                } else {
+                    src.at(p.x.floor() as isize, p.y.floor() as isize)
+                };
+            }
+        }
+
+        dst
+    }
+}
+
+/// Interpolate `src` at pixel-centre coordinates `(x, y)`.
+fn bilinear(src: &Image, x: f64, y: f64) -> u32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (x0, y0) = (x0 as isize, y0 as isize);
+
+    let corners = [
+        (src.at(x0, y0), (1.0 - fx) * (1.0 - fy)),
+        (src.at(x0 + 1, y0), fx * (1.0 - fy)),
+        (src.at(x0, y0 + 1), (1.0 - fx) * fy),
+        (src.at(x0 + 1, y0 + 1), fx * fy),
+    ];
+
+    // Premultiplied channels interpolate independently
+    [24, 16, 8, 0].into_iter().fold(0, |out, shift| {
+        let value: f64 = corners
+            .iter()
+            .map(|(px, w)| ((px >> shift) & 0xff) as f64 * w)
+            .sum();
+        out | ((value.round().clamp(0.0, 255.0) as u32) << shift)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_radius() {
+        let fisheye = Fisheye {
+            magnification: 4.0,
+            strength: 2.0,
+        };
+
+        assert_eq!(fisheye.source_radius(0.0), 0.0);
+        // Full magnification near the centre
+        assert!((fisheye.source_radius(0.01) - 0.0025).abs() < 1e-5);
+        // No magnification at the edge, identity beyond it
+        assert_eq!(fisheye.source_radius(1.0), 1.0);
+        assert_eq!(fisheye.source_radius(1.3), 1.3);
+
+        let mut previous = 0.0;
+        for i in 1..=100 {
+            let r = fisheye.source_radius(i as f64 / 100.0);
+            assert!(r > previous);
+            previous = r;
+        }
+    }
+
+    #[test]
+    fn test_remap() {
+        // Horizontal gradient, one grey level per pixel
+        let mut src = Image::new(16, 16, 0);
+        for (i, px) in src.pixels.iter_mut().enumerate() {
+            let v = (i % 16) as u32 * 16;
+            *px = 0xff00_0000 | v << 16 | v << 8 | v;
+        }
+        let lens = Rect::new(Vector2D::default(), Vector2D::new(8.0, 8.0));
+        let source = Vector2D::new(8.0, 8.0);
+        let scale = Vector2D::new(1.0, 1.0);
+
+        // At 1x the remap is a plain copy of the area around the source
+        let flat = Fisheye {
+            magnification: 1.0,
+            strength: 2.0,
+        };
+        let out = flat.remap(&src, (8, 8), lens, source, scale, false);
+        assert_eq!(out.at(0, 0), src.at(4, 4));
+        assert_eq!(out.at(7, 3), src.at(11, 7));
+
+        // Magnified, the centre spreads out but the corners stay put
+        let fisheye = Fisheye {
+            magnification: 4.0,
+            strength: 2.0,
+        };
+        let out = fisheye.remap(&src, (8, 8), lens, source, scale, true);
+        // The centre pixel lands within a quarter pixel of the source point
+        let grey = out.at(4, 4) & 0xff;
+        assert!((7 * 16..8 * 16).contains(&grey), "{}", grey);
+        assert_eq!(out.at(0, 0), src.at(4, 4));
+        assert_eq!(out.at(7, 7), src.at(11, 11));
+    }
+}
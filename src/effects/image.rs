@@ -0,0 +1,106 @@
+//! In-memory images for effects that need more than one pixel at a time.
+//!
+//! Per-pixel colour effects work in place on the output buffer; resamplers
+//! and neighbourhood filters copy the area they need into an `Image`,
+//! process it, and hand it back to Cairo or write it back to the buffer.
+
+use crate::pool_buffer::PoolBuffer;
+use crate::utils::Rect;
+use anyhow::Result;
+use cairo::{Format, ImageSurface};
+
+/// A premultiplied ARGB32 image held in memory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// Row-major `0xAARRGGBB` pixels
+    pub pixels: Vec<u32>,
+}
+
+impl Image {
+    /// Create an image filled with `fill`.
+    pub fn new(width: usize, height: usize, fill: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![fill; width * height],
+        }
+    }
+
+    /// Copy `rect` (whole pixels) out of `buffer`.
+    ///
+    /// Parts of `rect` outside the buffer repeat the nearest edge pixel, so
+    /// resampling kernels see plausible neighbours at the screen border.
+    pub fn crop(buffer: &mut PoolBuffer, rect: Rect) -> Self {
+        let (x0, y0, width, height) = rect.to_pixels();
+        let (width, height) = (width.max(1) as usize, height.max(1) as usize);
+        let max_x = (buffer.pixel_size.x as i32 - 1).max(0);
+        let max_y = (buffer.pixel_size.y as i32 - 1).max(0);
+        let stride = buffer.pixel_stride();
+
+        let data = buffer.pixels_mut();
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height as i32 {
+            let row = (y0 + y).clamp(0, max_y) as usize * stride;
+            for x in 0..width as i32 {
+                let offset = row + (x0 + x).clamp(0, max_x) as usize * 4;
+                let px = &data[offset..offset + 4];
+                pixels.push(u32::from_ne_bytes([px[0], px[1], px[2], px[3]]));
+            }
+        }
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Pixel at `(x, y)`, clamped to the image bounds.
+    pub fn at(&self, x: isize, y: isize) -> u32 {
+        let x = x.clamp(0, self.width as isize - 1) as usize;
+        let y = y.clamp(0, self.height as isize - 1) as usize;
+        self.pixels[y * self.width + x]
+    }
+
+    /// Write the image back into `buffer` with its top-left corner at `(x, y)`.
+    ///
+    /// Pixels falling outside the buffer are dropped.
+    pub fn paste(&self, buffer: &mut PoolBuffer, x: i32, y: i32) {
+        let stride = buffer.pixel_stride();
+        let max_x = buffer.pixel_size.x as i32;
+        let max_y = buffer.pixel_size.y as i32;
+
+        let data = buffer.pixels_mut();
+        for (row, line) in self.pixels.chunks_exact(self.width).enumerate() {
+            let dy = y + row as i32;
+            if dy < 0 || dy >= max_y {
+                continue;
+            }
+            for (col, px) in line.iter().enumerate() {
+                let dx = x + col as i32;
+                if dx < 0 || dx >= max_x {
+                    continue;
+                }
+                let offset = dy as usize * stride + dx as usize * 4;
+                data[offset..offset + 4].copy_from_slice(&px.to_ne_bytes());
+            }
+        }
+
+        buffer.mark_dirty();
+    }
+
+    /// Wrap the pixels in a Cairo surface.
+    pub fn into_surface(self) -> Result<ImageSurface> {
+        let data: Vec<u8> = self.pixels.iter().flat_map(|p| p.to_ne_bytes()).collect();
+        let surface = ImageSurface::create_for_data(
+            data,
+            Format::ARgb32,
+            self.width as i32,
+            self.height as i32,
+            self.width as i32 * 4,
+        )?;
+        Ok(surface)
+    }
+}
@@ -0,0 +1,95 @@
+//! CPU-side image effects for the magnified area.
+//!
+//! Effects run on the output buffer after the magnified region has been
+//! painted and only touch the lens bounds, so their cost tracks the lens
+//! size rather than the monitor resolution. The upscalers and the fisheye
+//! remap are the exception: they enlarge the capture before it is painted.
+
+pub mod color;
+pub mod cvd;
+pub mod fisheye;
+pub mod image;
+pub mod text;
+pub mod upscale;
+
+pub use color::ColorFilter;
+pub use cvd::{CvdFilter, CvdKind, CvdMode};
+pub use fisheye::Fisheye;
+pub use image::Image;
+pub use text::TextFilter;
+
+use crate::pool_buffer::PoolBuffer;
+use crate::utils::{Rect, Vector2D};
+
+/// Apply `f` to every visible pixel of `buffer` inside `rect`.
+///
+/// The buffer holds premultiplied ARGB32; `f` receives and returns straight
+/// (un-premultiplied) RGB so the colour maths don't have to care about
+/// alpha. Fully transparent pixels are left untouched; everything else in
+/// `rect` is mapped, so shaped lenses go through [`map_image`] instead.
+pub fn map_pixels(buffer: &mut PoolBuffer, rect: Rect, mut f: impl FnMut([u8; 3]) -> [u8; 3]) {
+    let bounds = Rect::new(Vector2D::default(), buffer.pixel_size);
+    let clipped = rect.intersect(&bounds);
+    if clipped.is_empty() {
+        return;
+    }
+    let (x0, y0, width, height) = clipped.to_pixels();
+
+    let stride = buffer.pixel_stride();
+    let max_x = buffer.pixel_size.x as usize;
+    let max_y = buffer.pixel_size.y as usize;
+    let x_end = (x0 as usize + width as usize).min(max_x);
+    let y_end = (y0 as usize + height as usize).min(max_y);
+
+    let data = buffer.pixels_mut();
+    for y in y0 as usize..y_end {
+        let row = &mut data[y * stride..y * stride + max_x * 4];
+        for px in row[x0 as usize * 4..x_end * 4].chunks_exact_mut(4) {
+            let argb = u32::from_ne_bytes([px[0], px[1], px[2], px[3]]);
+            let out = map_premultiplied(argb, &mut f);
+            px.copy_from_slice(&out.to_ne_bytes());
+        }
+    }
+
+    buffer.mark_dirty();
+}
+
+/// Apply `f` to every visible pixel of `image`, as [`map_pixels`] does.
+pub fn map_image(image: &mut Image, mut f: impl FnMut([u8; 3]) -> [u8; 3]) {
+    for px in &mut image.pixels {
+        *px = map_premultiplied(*px, &mut f);
+    }
+}
+
+/// Run `f` on one premultiplied ARGB32 pixel.
+fn map_premultiplied(argb: u32, f: &mut impl FnMut([u8; 3]) -> [u8; 3]) -> u32 {
+    let a = argb >> 24;
+    if a == 0 {
+        return argb;
+    }
+
+    let unpremultiply = |c: u32| ((c * 255 + a / 2) / a).min(255) as u8;
+    let rgb = [
+        unpremultiply((argb >> 16) & 0xff),
+        unpremultiply((argb >> 8) & 0xff),
+        unpremultiply(argb & 0xff),
+    ];
+
+    let [r, g, b] = f(rgb);
+    let premultiply = |c: u8| (c as u32 * a + 127) / 255;
+    (a << 24) | (premultiply(r) << 16) | (premultiply(g) << 8) | premultiply(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_premultiplied_keeps_transparent_pixels() {
+        let mut invert = |[r, g, b]: [u8; 3]| [255 - r, 255 - g, 255 - b];
+        assert_eq!(map_premultiplied(0, &mut invert), 0);
+        assert_eq!(map_premultiplied(0xff10_2030, &mut invert), 0xffef_dfcf);
+        // Half-transparent white inverts to half-transparent black
+        assert_eq!(map_premultiplied(0x8080_8080, &mut invert), 0x8000_0000);
+    }
+}
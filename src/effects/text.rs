@@ -0,0 +1,251 @@
+//! Text-clarity filters.
+//!
+//! Magnified text goes soft at medium zoom. The unsharp mask restores edge
+//! contrast; reading mode goes further and binarizes against the local
+//! background so glyphs come out as solid ink on a flat page.
+
+use super::Image;
+use crate::config::ReadingMode;
+
+/// Sharpening and reading-mode settings for the lens area.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextFilter {
+    /// Unsharp mask on or off
+    pub sharpen: bool,
+    /// Blur radius of the unsharp mask in output pixels (0.5..=10.0);
+    /// three times the Gaussian's sigma
+    pub radius: f64,
+    /// Strength (0.0..=5.0): detail added back by the unsharp mask, and how
+    /// far below the local mean a pixel must be to count as ink
+    pub amount: f64,
+    pub reading: ReadingMode,
+}
+
+impl Default for TextFilter {
+    fn default() -> Self {
+        Self {
+            sharpen: false,
+            radius: 1.5,
+            amount: 1.0,
+            reading: ReadingMode::Off,
+        }
+    }
+}
+
+impl TextFilter {
+    /// Set the unsharp mask radius, clamped to 0.5..=10.0.
+    pub fn set_radius(&mut self, radius: f64) {
+        self.radius = radius.clamp(0.5, 10.0);
+    }
+
+    /// Set the strength, clamped to 0.0..=5.0.
+    pub fn set_amount(&mut self, amount: f64) {
+        self.amount = amount.clamp(0.0, 5.0);
+    }
+
+    /// Whether the filter leaves every pixel unchanged.
+    pub fn is_identity(&self) -> bool {
+        !self.sharpen && self.reading == ReadingMode::Off
+    }
+
+    /// Run the enabled filters over `image`.
+    ///
+    /// Reading mode replaces the pixels outright, so sharpening first would
+    /// only shift the threshold; it is skipped while reading mode is on.
+    pub fn apply(&self, image: &mut Image) {
+        match self.reading {
+            ReadingMode::Off if self.sharpen => unsharp_mask(image, self.radius, self.amount),
+            ReadingMode::Off => {}
+            mode => binarize(image, mode, self.amount),
+        }
+    }
+}
+
+/// Sharpen `image` by adding back `amount` times its difference from a
+/// Gaussian blur reaching `radius` pixels out.
+///
+/// The blur kernel is cut off at three standard deviations, so `radius`
+/// is three times the Gaussian's sigma.
+pub fn unsharp_mask(image: &mut Image, radius: f64, amount: f64) {
+    let blurred = gaussian_blur(image, radius as f32 / 3.0);
+    let amount = amount as f32;
+
+    for (px, blur) in image.pixels.iter_mut().zip(blurred) {
+        let a = *px >> 24;
+        if a == 0 {
+            continue;
+        }
+
+        let mut out = a << 24;
+        for (c, shift) in [16, 8, 0].into_iter().enumerate() {
+            let value = ((*px >> shift) & 0xff) as f32;
+            let sharp = value + amount * (value - blur[c]);
+            out |= (sharp.round().clamp(0.0, a as f32) as u32) << shift;
+        }
+        *px = out;
+    }
+}
+
+/// Separable Gaussian blur of the colour channels.
+fn gaussian_blur(image: &Image, sigma: f32) -> Vec<[f32; 3]> {
+    let reach = (sigma * 3.0).ceil() as isize;
+    let mut kernel: Vec<f32> = (-reach..=reach)
+        .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f32 = kernel.iter().sum();
+    kernel.iter_mut().for_each(|k| *k /= sum);
+
+    let channels = |p: u32| {
+        [
+            ((p >> 16) & 0xff) as f32,
+            ((p >> 8) & 0xff) as f32,
+            (p & 0xff) as f32,
+        ]
+    };
+    let (w, h) = (image.width as isize, image.height as isize);
+
+    let mut horizontal = vec![[0f32; 3]; image.pixels.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let mut acc = [0f32; 3];
+            for (k, weight) in kernel.iter().enumerate() {
+                let px = channels(image.at(x + k as isize - reach, y));
+                for c in 0..3 {
+                    acc[c] += px[c] * weight;
+                }
+            }
+            horizontal[(y * w + x) as usize] = acc;
+        }
+    }
+
+    let mut out = vec![[0f32; 3]; image.pixels.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let mut acc = [0f32; 3];
+            for (k, weight) in kernel.iter().enumerate() {
+                let sy = (y + k as isize - reach).clamp(0, h - 1);
+                let px = horizontal[(sy * w + x) as usize];
+                for c in 0..3 {
+                    acc[c] += px[c] * weight;
+                }
+            }
+            out[(y * w + x) as usize] = acc;
+        }
+    }
+
+    out
+}
+
+/// Adaptive binarization (Bradley–Roth).
+///
+/// A pixel is ink when its luma is more than `strength * 15%` below the
+/// mean of a window an eighth of the image wide, so uneven backgrounds and
+/// coloured panels still give clean glyphs.
+pub fn binarize(image: &mut Image, mode: ReadingMode, strength: f64) {
+    let (w, h) = (image.width, image.height);
+    let luma: Vec<u32> = image
+        .pixels
+        .iter()
+        .map(|&p| {
+            let (r, g, b) = ((p >> 16) & 0xff, (p >> 8) & 0xff, p & 0xff);
+            (299 * r + 587 * g + 114 * b) / 1000
+        })
+        .collect();
+
+    // Summed-area table with a zero row and column in front
+    let mut integral = vec![0u64; (w + 1) * (h + 1)];
+    for y in 0..h {
+        let mut row_sum = 0u64;
+        for x in 0..w {
+            row_sum += luma[y * w + x] as u64;
+            integral[(y + 1) * (w + 1) + x + 1] = integral[y * (w + 1) + x + 1] + row_sum;
+        }
+    }
+
+    let half = (w.max(h) / 16).max(4);
+    let t = (0.15 * strength).clamp(0.0, 0.9);
+    let (ink, paper) = match mode {
+        ReadingMode::LightOnDark => (0xff, 0x00),
+        _ => (0x00, 0xff),
+    };
+
+    for y in 0..h {
+        let (y0, y1) = (y.saturating_sub(half), (y + half + 1).min(h));
+        for x in 0..w {
+            let (x0, x1) = (x.saturating_sub(half), (x + half + 1).min(w));
+            let sum = integral[y1 * (w + 1) + x1] + integral[y0 * (w + 1) + x0]
+                - integral[y0 * (w + 1) + x1]
+                - integral[y1 * (w + 1) + x0];
+            let count = ((x1 - x0) * (y1 - y0)) as f64;
+
+            let px = &mut image.pixels[y * w + x];
+            let a = *px >> 24;
+            let is_ink = (luma[y * w + x] as f64) * count < sum as f64 * (1.0 - t);
+            let level = if is_ink { ink } else { paper } * a / 255;
+            *px = (a << 24) | (level << 16) | (level << 8) | level;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A white image with a black vertical bar down the middle.
+    fn bar() -> Image {
+        let mut image = Image::new(16, 8, 0xffc0_c0c0);
+        for y in 0..8 {
+            for x in 6..9 {
+                image.pixels[y * 16 + x] = 0xff40_4040;
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn test_unsharp_mask_increases_edge_contrast() {
+        let mut image = bar();
+        unsharp_mask(&mut image, 1.5, 1.0);
+
+        // Either side of the edge moves apart; flat areas stay put
+        assert!(image.pixels[4 * 16 + 5] & 0xff > 0xc0);
+        assert!(image.pixels[4 * 16 + 6] & 0xff < 0x40);
+        assert_eq!(image.pixels[4 * 16], 0xffc0_c0c0);
+        // Pixels beyond the radius are not reached by the blur
+        assert_eq!(image.pixels[4 * 16 + 3], 0xffc0_c0c0);
+
+        let mut flat = Image::new(8, 8, 0xff80_8080);
+        unsharp_mask(&mut flat, 2.0, 3.0);
+        assert_eq!(flat, Image::new(8, 8, 0xff80_8080));
+    }
+
+    #[test]
+    fn test_binarize_polarity() {
+        let mut image = bar();
+        binarize(&mut image, ReadingMode::DarkOnLight, 1.0);
+        assert_eq!(image.pixels[4 * 16 + 7], 0xff00_0000);
+        assert_eq!(image.pixels[4 * 16 + 1], 0xffff_ffff);
+
+        let mut image = bar();
+        binarize(&mut image, ReadingMode::LightOnDark, 1.0);
+        assert_eq!(image.pixels[4 * 16 + 7], 0xffff_ffff);
+        assert_eq!(image.pixels[4 * 16 + 1], 0xff00_0000);
+    }
+
+    #[test]
+    fn test_reading_mode_takes_precedence() {
+        let filter = TextFilter {
+            sharpen: true,
+            reading: ReadingMode::DarkOnLight,
+            ..TextFilter::default()
+        };
+        let mut image = bar();
+        filter.apply(&mut image);
+        assert!(image
+            .pixels
+            .iter()
+            .all(|&p| p == 0xff00_0000 || p == 0xffff_ffff));
+
+        assert!(TextFilter::default().is_identity());
+    }
+}
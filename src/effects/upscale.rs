@@ -0,0 +1,293 @@
+//! CPU resamplers for the magnified region.
+//!
+//! Cairo only offers nearest, bilinear and its "best" (cubic) filter. This
+//! module adds a Lanczos resampler and the Scale2x and 2xBR pixel-art
+//! scalers, which work on a small crop of the capture around the source
+//! point and hand the result back to Cairo as a surface.
+
+use super::Image;
+use std::f32::consts::PI;
+
+/// Double `src` with Scale2x (EPX).
+///
+/// Each pixel becomes a 2x2 block; a sub-pixel takes a neighbour's colour
+/// when two adjacent neighbours agree, which keeps diagonal edges of pixel
+/// art sharp instead of stair-stepped.
+pub fn scale2x(src: &Image) -> Image {
+    let mut dst = Image::new(src.width * 2, src.height * 2, 0);
+
+    for y in 0..src.height as isize {
+        for x in 0..src.width as isize {
+            let e = src.at(x, y);
+            let b = src.at(x, y - 1);
+            let d = src.at(x - 1, y);
+            let f = src.at(x + 1, y);
+            let h = src.at(x, y + 1);
+
+            let (mut e0, mut e1, mut e2, mut e3) = (e, e, e, e);
+            if b != h && d != f {
+                if d == b {
+                    e0 = d;
+                }
+                if b == f {
+                    e1 = f;
+                }
+                if d == h {
+                    e2 = d;
+                }
+                if h == f {
+                    e3 = f;
+                }
+            }
+
+            let (dx, dy) = (x as usize * 2, y as usize * 2);
+            let w = dst.width;
+            dst.pixels[dy * w + dx] = e0;
+            dst.pixels[dy * w + dx + 1] = e1;
+            dst.pixels[(dy + 1) * w + dx] = e2;
+            dst.pixels[(dy + 1) * w + dx + 1] = e3;
+        }
+    }
+
+    dst
+}
+
+/// Double `src` with 2xBR (level 1).
+///
+/// Like Scale2x but edge detection weighs colour distances over a 5x5
+/// neighbourhood and blends the corner sub-pixel, giving smoother curves
+/// on anti-aliased content.
+pub fn xbr2x(src: &Image) -> Image {
+    let mut dst = Image::new(src.width * 2, src.height * 2, 0);
+
+    for y in 0..src.height as isize {
+        for x in 0..src.width as isize {
+            let e = src.at(x, y);
+            let (dx, dy) = (x as usize * 2, y as usize * 2);
+
+            // Each output corner is the bottom-right case seen through a mirror
+            for (i, (sx, sy)) in [(-1, -1), (1, -1), (-1, 1), (1, 1)].into_iter().enumerate() {
+                let p = |ox: isize, oy: isize| src.at(x + ox * sx, y + oy * sy);
+                let out = xbr_corner(e, p);
+                let w = dst.width;
+                dst.pixels[(dy + i / 2) * w + dx + i % 2] = out;
+            }
+        }
+    }
+
+    dst
+}
+
+/// Colour of the bottom-right sub-pixel of `e` for 2xBR.
+///
+/// `p(dx, dy)` returns the neighbour at that offset in the corner's frame.
+fn xbr_corner(e: u32, p: impl Fn(isize, isize) -> u32) -> u32 {
+    let (b, c) = (p(0, -1), p(1, -1));
+    let (d, f) = (p(-1, 0), p(1, 0));
+    let (g, h, i) = (p(-1, 1), p(0, 1), p(1, 1));
+    let (f4, i4) = (p(2, 0), p(2, 1));
+    let (h5, i5) = (p(0, 2), p(1, 2));
+
+    if e == f || e == h {
+        return e;
+    }
+
+    let edge_e = yuv_distance(e, c)
+        + yuv_distance(e, g)
+        + yuv_distance(i, h5)
+        + yuv_distance(i, f4)
+        + 4 * yuv_distance(h, f);
+    let edge_i = yuv_distance(h, d)
+        + yuv_distance(h, i5)
+        + yuv_distance(f, i4)
+        + yuv_distance(f, b)
+        + 4 * yuv_distance(e, i);
+
+    let is_edge = (f != b && h != d) || (e == i && f != i4 && h != i5) || e == g || e == c;
+    if edge_e < edge_i && is_edge {
+        let nearer = if yuv_distance(e, f) <= yuv_distance(e, h) {
+            f
+        } else {
+            h
+        };
+        blend_half(e, nearer)
+    } else {
+        e
+    }
+}
+
+/// Perceptual distance between two pixels, weighted towards luma.
+fn yuv_distance(a: u32, b: u32) -> u32 {
+    let yuv = |p: u32| {
+        let (r, g, b) = (
+            ((p >> 16) & 0xff) as i32,
+            ((p >> 8) & 0xff) as i32,
+            (p & 0xff) as i32,
+        );
+        let y = (299 * r + 587 * g + 114 * b) / 1000;
+        (y, (b - y) * 493 / 1000, (r - y) * 877 / 1000)
+    };
+    let (ya, ua, va) = yuv(a);
+    let (yb, ub, vb) = yuv(b);
+    (48 * (ya - yb).unsigned_abs())
+        + (7 * (ua - ub).unsigned_abs())
+        + (6 * (va - vb).unsigned_abs())
+}
+
+/// Average of two pixels, channel by channel.
+fn blend_half(a: u32, b: u32) -> u32 {
+    (a & b) + (((a ^ b) & 0xfefe_fefe) >> 1)
+}
+
+/// Resample `src` to `width` x `height` with a Lanczos-3 kernel.
+///
+/// Output pixel `(x, y)` samples the source at
+/// `origin + (x + 0.5, y + 0.5) * step`, in source pixel units, so the
+/// caller controls both the zoom (`step` < 1 enlarges) and the offset.
+pub fn lanczos(
+    src: &Image,
+    width: usize,
+    height: usize,
+    origin: (f32, f32),
+    step: (f32, f32),
+) -> Image {
+    let columns = lanczos_weights(width, origin.0, step.0);
+    let rows = lanczos_weights(height, origin.1, step.1);
+
+    // Horizontal pass into a float buffer of src.height x width
+    let mut horizontal = vec![[0f32; 4]; src.height * width];
+    for y in 0..src.height {
+        for (x, (start, weights)) in columns.iter().enumerate() {
+            let mut acc = [0f32; 4];
+            for (k, w) in weights.iter().enumerate() {
+                let px = src.at(start + k as isize, y as isize);
+                for (c, a) in acc.iter_mut().enumerate() {
+                    *a += ((px >> (24 - 8 * c)) & 0xff) as f32 * w;
+                }
+            }
+            horizontal[y * width + x] = acc;
+        }
+    }
+
+    // Vertical pass into the output
+    let mut dst = Image::new(width, height, 0);
+    for (y, (start, weights)) in rows.iter().enumerate() {
+        for x in 0..width {
+            let mut acc = [0f32; 4];
+            for (k, w) in weights.iter().enumerate() {
+                let sy = (start + k as isize).clamp(0, src.height as isize - 1) as usize;
+                let px = horizontal[sy * width + x];
+                for c in 0..4 {
+                    acc[c] += px[c] * w;
+                }
+            }
+
+            // Ringing can overshoot; keep the result a valid premultiplied pixel
+            let a = acc[0].round().clamp(0.0, 255.0) as u32;
+            let channel = |v: f32| v.round().clamp(0.0, a as f32) as u32;
+            dst.pixels[y * width + x] =
+                (a << 24) | (channel(acc[1]) << 16) | (channel(acc[2]) << 8) | channel(acc[3]);
+        }
+    }
+
+    dst
+}
+
+/// Normalised kernel taps for each output sample along one axis.
+fn lanczos_weights(count: usize, origin: f32, step: f32) -> Vec<(isize, Vec<f32>)> {
+    const A: f32 = 3.0;
+    // When shrinking, widen the kernel so every source pixel contributes
+    let support = step.max(1.0);
+
+    (0..count)
+        .map(|i| {
+            let center = origin + (i as f32 + 0.5) * step - 0.5;
+            let start = (center - A * support).ceil() as isize;
+            let end = (center + A * support).floor() as isize;
+
+            let mut weights: Vec<f32> = (start..=end)
+                .map(|s| lanczos_kernel((s as f32 - center) / support, A))
+                .collect();
+            let sum: f32 = weights.iter().sum();
+            if sum != 0.0 {
+                weights.iter_mut().for_each(|w| *w /= sum);
+            }
+            (start, weights)
+        })
+        .collect()
+}
+
+fn lanczos_kernel(x: f32, a: f32) -> f32 {
+    if x.abs() < f32::EPSILON {
+        1.0
+    } else if x.abs() >= a {
+        0.0
+    } else {
+        let px = PI * x;
+        a * px.sin() * (px / a).sin() / (px * px)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const W: u32 = 0xffff_ffff;
+    const K: u32 = 0xff00_0000;
+
+    fn image(width: usize, pixels: &[u32]) -> Image {
+        Image {
+            width,
+            height: pixels.len() / width,
+            pixels: pixels.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_scale2x_flat_and_diagonal() {
+        // A flat image just doubles
+        let flat = scale2x(&Image::new(2, 2, W));
+        assert_eq!(flat, Image::new(4, 4, W));
+
+        // The black diagonal gains sub-pixels along the edge
+        let src = image(3, &[K, W, W, W, K, W, W, W, K]);
+        let dst = scale2x(&src);
+        assert_eq!(dst.width, 6);
+        // The centre pixel stays black and the step above it is filled in
+        assert_eq!(dst.pixels[2 * 6 + 2], K);
+        assert_eq!(dst.pixels[2 * 6 + 3], K);
+        assert_eq!(dst.pixels[6 + 2], K);
+        assert_eq!(dst.pixels[6 + 3], W);
+    }
+
+    #[test]
+    fn test_xbr_keeps_flat_areas() {
+        let src = Image::new(3, 3, 0xff33_6699);
+        assert_eq!(xbr2x(&src), Image::new(6, 6, 0xff33_6699));
+
+        // An isolated pixel is left alone rather than smeared
+        let src = image(3, &[W, W, W, W, K, W, W, W, W]);
+        let dst = xbr2x(&src);
+        assert_eq!(dst.pixels[2 * 6 + 2], K);
+    }
+
+    #[test]
+    fn test_lanczos_identity_and_flat() {
+        let src = image(3, &[K, W, K, W, K, W, K, W, K]);
+
+        // Unit step with no offset reproduces the source
+        let same = lanczos(&src, 3, 3, (0.0, 0.0), (1.0, 1.0));
+        assert_eq!(same, src);
+
+        // A flat image stays flat when enlarged
+        let flat = Image::new(4, 4, 0xff80_4020);
+        let big = lanczos(&flat, 16, 16, (0.0, 0.0), (0.25, 0.25));
+        assert!(big.pixels.iter().all(|&p| p == 0xff80_4020));
+    }
+
+    #[test]
+    fn test_blend_half() {
+        assert_eq!(blend_half(W, K), 0xff7f_7f7f);
+        assert_eq!(blend_half(0xff10_2030, 0xff10_2030), 0xff10_2030);
+    }
+}
@@ -0,0 +1,125 @@
+//! Heads-up label with the zoom level, monitor and pointer position.
+//!
+//! The label appears when the magnification or the monitor changes, stays
+//! for a moment and then fades out. Changes are noticed at render time, so
+//! every path that changes the zoom shows the label without extra wiring.
+
+use crate::utils::Vector2D;
+use std::time::{Duration, Instant};
+
+/// How long the label stays fully visible after a change.
+const HOLD: Duration = Duration::from_millis(1500);
+
+/// How long the fade-out takes.
+const FADE: Duration = Duration::from_millis(500);
+
+/// Fading readout of the current magnification.
+#[derive(Debug, Clone, Default)]
+pub struct Hud {
+    pub enabled: bool,
+    zoom: Option<f64>,
+    monitor: String,
+    changed_at: Option<Instant>,
+    /// Opacity as of the last `update`, which is what gets drawn
+    opacity: f64,
+}
+
+impl Hud {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+
+    /// Note the state about to be drawn, restarting the timer if it changed.
+    pub fn update(&mut self, zoom: f64, monitor: &str, now: Instant) {
+        if self.zoom != Some(zoom) || self.monitor != monitor {
+            self.zoom = Some(zoom);
+            self.monitor = monitor.to_string();
+            self.changed_at = Some(now);
+        }
+        self.opacity = self.opacity_at(now);
+    }
+
+    /// Opacity to draw the label with.
+    pub fn opacity(&self) -> f64 {
+        self.opacity
+    }
+
+    /// Label opacity at `now`, from 1.0 while held down to 0.0 once faded.
+    pub fn opacity_at(&self, now: Instant) -> f64 {
+        let Some(changed_at) = self.changed_at.filter(|_| self.enabled) else {
+            return 0.0;
+        };
+        let age = now.saturating_duration_since(changed_at);
+        match age.checked_sub(HOLD) {
+            None => 1.0,
+            Some(fading) => (1.0 - fading.as_secs_f64() / FADE.as_secs_f64()).max(0.0),
+        }
+    }
+
+    /// Whether the label on screen is out of date at `now`.
+    pub fn needs_redraw(&self, now: Instant) -> bool {
+        self.opacity_at(now) != self.opacity
+    }
+
+    /// Label text for the pointer at `pointer` (logical, monitor-relative).
+    pub fn text(&self, pointer: Vector2D) -> String {
+        let magnification = self.zoom.map_or(1.0, |zoom| 1.0 / zoom);
+        format!(
+            "{:.1}x  {}\n{:.0}, {:.0}",
+            magnification, self.monitor, pointer.x, pointer.y
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fades_after_change() {
+        let start = Instant::now();
+        let mut hud = Hud::new(true);
+        hud.update(0.25, "DP-1", start);
+
+        assert_eq!(hud.opacity(), 1.0);
+        assert_eq!(hud.opacity_at(start + HOLD), 1.0);
+        assert_eq!(hud.opacity_at(start + HOLD + FADE / 2), 0.5);
+        assert_eq!(hud.opacity_at(start + HOLD + FADE), 0.0);
+        assert!(!hud.needs_redraw(start + HOLD));
+        assert!(hud.needs_redraw(start + HOLD + FADE / 2));
+
+        // Redrawing the same state keeps the timer running
+        let later = start + HOLD + FADE * 2;
+        hud.update(0.25, "DP-1", later);
+        assert_eq!(hud.opacity(), 0.0);
+        assert!(!hud.needs_redraw(later + HOLD));
+
+        // A new zoom level or monitor brings it back
+        hud.update(0.2, "DP-1", later);
+        assert_eq!(hud.opacity(), 1.0);
+        hud.update(0.2, "HDMI-A-1", later + HOLD * 2);
+        assert_eq!(hud.opacity(), 1.0);
+    }
+
+    #[test]
+    fn test_disabled_stays_hidden() {
+        let now = Instant::now();
+        let mut hud = Hud::new(false);
+        hud.update(0.5, "DP-1", now);
+        assert_eq!(hud.opacity(), 0.0);
+        assert!(!hud.needs_redraw(now + HOLD));
+    }
+
+    #[test]
+    fn test_text() {
+        let mut hud = Hud::new(true);
+        hud.update(1.0 / 3.5, "eDP-1", Instant::now());
+        assert_eq!(
+            hud.text(Vector2D::new(812.4, 440.0)),
+            "3.5x  eDP-1\n812, 440"
+        );
+    }
+}
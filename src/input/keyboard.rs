@@ -1,48 +1,52 @@
-use wayland_client::protocol::wl_keyboard::WlKeyboard;
+use std::os::fd::OwnedFd;
 use xkbcommon::xkb;
 
 pub struct Keyboard {
-    pub keyboard: WlKeyboard,
     pub xkb_context: xkb::Context,
     pub xkb_state: Option<xkb::State>,
 }
 
 impl Keyboard {
-    pub fn new(keyboard: WlKeyboard) -> anyhow::Result<Self> {
+    pub fn new() -> anyhow::Result<Self> {
         let xkb_context = xkb::Context::new(xkb::CONTEXT_NO_FLAGS);
 
         Ok(Self {
-            keyboard,
             xkb_context,
             xkb_state: None,
         })
     }
 
-    pub fn handle_keymap(&mut self, format: u32, fd: i32, _size: u32) -> anyhow::Result<()> {
-        use std::os::unix::io::FromRawFd;
-
+    pub fn handle_keymap(&mut self, format: u32, fd: OwnedFd, size: u32) -> anyhow::Result<()> {
         if format != 1 {
             // XKB_KEYMAP_FORMAT_TEXT_V1
             log::warn!("Unsupported keymap format: {}", format);
             return Ok(());
         }
 
-        unsafe {
-            let mut file = std::fs::File::from_raw_fd(fd);
-            let keymap = xkb::Keymap::new_from_file(
+        // The keymap fd must be mapped (MAP_PRIVATE from wl_keyboard v7 on) rather
+        // than read, and the string it holds is NUL-terminated.
+        let keymap = unsafe {
+            xkb::Keymap::new_from_fd(
                 &self.xkb_context,
-                &mut file,
+                fd,
+                size as usize,
                 xkb::KEYMAP_FORMAT_TEXT_V1,
                 xkb::KEYMAP_COMPILE_NO_FLAGS,
-            )
-            .ok_or_else(|| anyhow::anyhow!("Failed to create XKB keymap"))?;
-
-            self.xkb_state = Some(xkb::State::new(&keymap));
+            )?
         }
+        .ok_or_else(|| anyhow::anyhow!("Failed to create XKB keymap"))?;
+
+        self.xkb_state = Some(xkb::State::new(&keymap));
 
         Ok(())
     }
 
+    pub fn handle_modifiers(&mut self, depressed: u32, latched: u32, locked: u32, group: u32) {
+        if let Some(xkb_state) = self.xkb_state.as_mut() {
+            xkb_state.update_mask(depressed, latched, locked, 0, 0, group);
+        }
+    }
+
     pub fn handle_key(&self, key: u32, state: u32) -> Option<xkb::Keysym> {
         if state == 0 {
             // Released
@@ -1,5 +1,5 @@
-// Input handling modules (currently unused but reserved for future modular input handling)
-#[allow(dead_code)]
+// Input handling modules
 pub mod keyboard;
+// Reserved for future modular pointer handling
 #[allow(dead_code)]
 pub mod pointer;
@@ -3,8 +3,18 @@ use crate::utils::Vector2D;
 use wayland_client::protocol::{wl_callback::WlCallback, wl_surface::WlSurface};
 use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1::ZwlrLayerSurfaceV1;
 
+/// What a layer surface is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurfaceRole {
+    /// Full-monitor overlay that receives pointer input and draws the lens.
+    Overlay,
+    /// Edge-anchored pane that shows the docked magnified view.
+    Dock,
+}
+
 pub struct LayerSurface {
     pub monitor_idx: usize,
+    pub role: SurfaceRole,
     pub surface: WlSurface,
     pub layer_surface: Option<ZwlrLayerSurfaceV1>,
     pub fractional_scale_value: f64,
@@ -35,6 +45,7 @@ impl LayerSurface {
     ) -> Self {
         Self {
             monitor_idx,
+            role: SurfaceRole::Overlay,
             surface,
             layer_surface: None,
             fractional_scale_value: monitor_scale as f64,
@@ -51,6 +62,11 @@ impl LayerSurface {
         }
     }
 
+    /// Whether this is the pointer-tracking overlay of the given monitor
+    pub fn is_overlay_for(&self, monitor_idx: usize) -> bool {
+        self.role == SurfaceRole::Overlay && self.monitor_idx == monitor_idx
+    }
+
     /// Get the logical size of the surface based on physical size and fractional scale
     pub fn get_logical_size(&self) -> Vector2D {
         Vector2D::new(
@@ -59,6 +75,18 @@ impl LayerSurface {
         )
     }
 
+    /// Take on a configured size in physical pixels, dropping any buffers
+    /// made for the old size so the next render creates matching ones.
+    pub fn resize(&mut self, monitor_size: Vector2D) {
+        if monitor_size == self.monitor_size {
+            return;
+        }
+        self.monitor_size = monitor_size;
+        for buffer in self.buffers.iter_mut().filter_map(Option::take) {
+            buffer.buffer.destroy();
+        }
+    }
+
     pub fn get_available_buffer(&mut self) -> Option<&mut PoolBuffer> {
         // With double buffering, always return the buffer that wasn't last used
         // The other buffer is attached to the surface
@@ -66,6 +94,38 @@ impl LayerSurface {
         self.buffers[next_buffer_idx].as_mut()
     }
 
+    /// Create any missing buffers at the surface's logical size.
+    pub fn ensure_buffers<T>(
+        &mut self,
+        shm: &wayland_client::protocol::wl_shm::WlShm,
+        qh: &wayland_client::QueueHandle<T>,
+    ) -> anyhow::Result<()>
+    where
+        T: wayland_client::Dispatch<wayland_client::protocol::wl_shm_pool::WlShmPool, ()>
+            + wayland_client::Dispatch<wayland_client::protocol::wl_buffer::WlBuffer, ()>
+            + 'static,
+    {
+        if self.buffers.iter().all(|b| b.is_some()) {
+            return Ok(());
+        }
+
+        log::debug!(
+            "Creating output buffers for layer surface {}",
+            self.monitor_idx
+        );
+
+        // Use logical size for buffer creation (physical size / fractional scale)
+        let pixel_size = self.get_logical_size();
+        let stride = (pixel_size.x as u32) * 4; // ARGB32 = 4 bytes per pixel
+        let format = wayland_client::protocol::wl_shm::Format::Argb8888 as u32;
+
+        for buffer in self.buffers.iter_mut().filter(|b| b.is_none()) {
+            *buffer = Some(PoolBuffer::new(pixel_size, format, stride, shm, qh)?);
+        }
+
+        Ok(())
+    }
+
     pub fn send_frame<T>(&mut self, qh: &wayland_client::QueueHandle<T>)
     where
         T: wayland_client::Dispatch<wayland_client::protocol::wl_callback::WlCallback, ()>
@@ -0,0 +1,210 @@
+//! Lens instances and the pins left on screen.
+//!
+//! The live lens follows the pointer. Pinning copies it into a `Lens` that
+//! keeps its source point, zoom and place on screen while the pointer goes
+//! on to drive a fresh live lens. Pins can be dragged, resized and closed;
+//! the one under the pointer is drawn on top so it is visible while being
+//! handled.
+
+use crate::utils::{Rect, Vector2D};
+
+/// A magnified view of one spot on a monitor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lens {
+    /// Point shown at the lens centre, in the monitor's logical coordinates
+    pub source: Vector2D,
+    /// Where the lens centre is drawn
+    pub center: Vector2D,
+    pub size: Vector2D,
+    /// Zoom level (0.01 = 1%, 1.0 = 100%)
+    pub zoom: f64,
+}
+
+impl Lens {
+    /// Area the lens covers on its monitor.
+    pub fn bounds(&self) -> Rect {
+        Rect::from_center(self.center, self.size)
+    }
+}
+
+/// Lenses pinned in place, with the monitor each one is on.
+#[derive(Debug, Clone, Default)]
+pub struct Pins {
+    lenses: Vec<(usize, Lens)>,
+    /// Index of the pin under the pointer
+    hovered: Option<usize>,
+    /// Offset from the pointer to the centre of the pin being dragged
+    grab: Option<Vector2D>,
+}
+
+impl Pins {
+    /// Leave `lens` on `monitor`.
+    pub fn pin(&mut self, monitor: usize, lens: Lens) {
+        self.lenses.push((monitor, lens));
+    }
+
+    /// Pins on `monitor`, bottom to top.
+    pub fn on(&self, monitor: usize) -> Vec<Lens> {
+        let mut pins: Vec<(usize, Lens)> = self
+            .lenses
+            .iter()
+            .enumerate()
+            .filter(|(_, (on, _))| *on == monitor)
+            .map(|(i, (_, lens))| (i, *lens))
+            .collect();
+        // The hovered pin is raised so it can be seen while handled
+        pins.sort_by_key(|(i, _)| Some(*i) == self.hovered);
+        pins.into_iter().map(|(_, lens)| lens).collect()
+    }
+
+    pub fn hovered(&self) -> Option<&Lens> {
+        self.hovered
+            .and_then(|i| self.lenses.get(i))
+            .map(|(_, lens)| lens)
+    }
+
+    /// Whether a pin is being dragged.
+    pub fn is_dragging(&self) -> bool {
+        self.grab.is_some()
+    }
+
+    /// Note the pointer position, picking the topmost pin under it. Returns
+    /// whether the hovered pin changed.
+    pub fn hover(&mut self, monitor: usize, pointer: Vector2D) -> bool {
+        if self.is_dragging() {
+            return false;
+        }
+        let under = |(on, lens): &(usize, Lens)| *on == monitor && lens.bounds().contains(pointer);
+        let hovered = self
+            .hovered
+            .filter(|&i| under(&self.lenses[i]))
+            .or_else(|| self.lenses.iter().rposition(under));
+        let changed = hovered != self.hovered;
+        self.hovered = hovered;
+        changed
+    }
+
+    /// Forget the hovered pin, e.g. when the pointer leaves the monitor.
+    pub fn unhover(&mut self) {
+        self.hovered = None;
+        self.grab = None;
+    }
+
+    /// Start dragging the hovered pin. Returns whether there was one.
+    pub fn grab(&mut self, pointer: Vector2D) -> bool {
+        match self.hovered() {
+            Some(lens) => {
+                self.grab = Some(lens.center - pointer);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move the dragged pin with the pointer. Returns whether it moved.
+    pub fn drag(&mut self, pointer: Vector2D) -> bool {
+        let (Some(offset), Some(i)) = (self.grab, self.hovered) else {
+            return false;
+        };
+        let center = (pointer + offset).floor();
+        let (_, lens) = &mut self.lenses[i];
+        let moved = lens.center != center;
+        lens.center = center;
+        moved
+    }
+
+    pub fn release(&mut self) {
+        self.grab = None;
+    }
+
+    /// Resize the hovered pin about its centre. Returns whether there was one.
+    pub fn resize_hovered(&mut self, resize: impl FnOnce(Vector2D) -> Vector2D) -> bool {
+        match self.hovered.and_then(|i| self.lenses.get_mut(i)) {
+            Some((_, lens)) => {
+                lens.size = resize(lens.size);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Close the hovered pin, or the newest one if none is hovered.
+    pub fn close(&mut self) -> Option<Lens> {
+        let i = self.hovered.or_else(|| self.lenses.len().checked_sub(1))?;
+        self.hovered = None;
+        self.grab = None;
+        Some(self.lenses.remove(i).1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lens(x: f64) -> Lens {
+        Lens {
+            source: Vector2D::new(x, 100.0),
+            center: Vector2D::new(x, 100.0),
+            size: Vector2D::new(100.0, 50.0),
+            zoom: 0.5,
+        }
+    }
+
+    #[test]
+    fn test_hover_raises_topmost() {
+        let mut pins = Pins::default();
+        pins.pin(0, lens(100.0));
+        pins.pin(0, lens(150.0));
+        pins.pin(1, lens(100.0));
+
+        // Over both pins on monitor 0: the newer one wins and is drawn last
+        assert!(pins.hover(0, Vector2D::new(120.0, 100.0)));
+        assert_eq!(pins.hovered().unwrap().center.x, 150.0);
+        assert_eq!(pins.on(0).len(), 2);
+
+        // Moving onto the older pin only raises it
+        assert!(pins.hover(0, Vector2D::new(60.0, 100.0)));
+        assert_eq!(pins.on(0).last().unwrap().center.x, 100.0);
+        // Within the raised pin it stays hovered even where the other overlaps
+        assert!(!pins.hover(0, Vector2D::new(120.0, 100.0)));
+        assert_eq!(pins.hovered().unwrap().center.x, 100.0);
+
+        assert!(pins.hover(0, Vector2D::new(500.0, 500.0)));
+        assert!(pins.hovered().is_none());
+    }
+
+    #[test]
+    fn test_drag_keeps_grab_offset() {
+        let mut pins = Pins::default();
+        pins.pin(0, lens(100.0));
+        pins.hover(0, Vector2D::new(110.0, 90.0));
+
+        assert!(pins.grab(Vector2D::new(110.0, 90.0)));
+        assert!(pins.drag(Vector2D::new(310.0, 190.0)));
+        assert_eq!(pins.hovered().unwrap().center, Vector2D::new(300.0, 200.0));
+        // The pointer can't leave a pin it is dragging
+        assert!(!pins.hover(0, Vector2D::new(900.0, 900.0)));
+
+        pins.release();
+        assert!(!pins.drag(Vector2D::new(0.0, 0.0)));
+        assert!(pins.hover(0, Vector2D::new(900.0, 900.0)));
+    }
+
+    #[test]
+    fn test_resize_and_close() {
+        let mut pins = Pins::default();
+        assert!(pins.close().is_none());
+        pins.pin(0, lens(100.0));
+        pins.pin(0, lens(400.0));
+
+        pins.hover(0, Vector2D::new(100.0, 100.0));
+        assert!(pins.resize_hovered(|size| size * 2.0));
+        assert_eq!(pins.hovered().unwrap().size, Vector2D::new(200.0, 100.0));
+
+        // Closes the hovered pin first, then the newest
+        assert_eq!(pins.close().unwrap().center.x, 100.0);
+        assert!(!pins.resize_hovered(|size| size * 2.0));
+        assert_eq!(pins.close().unwrap().center.x, 400.0);
+        assert!(pins.on(0).is_empty());
+    }
+}
@@ -0,0 +1,291 @@
+//! Live capture: keeping the screenshot current while magnifying.
+//!
+//! Every capture of a monitor also captures our own overlay on it, so only
+//! the parts of a frame our overlay left transparent can be trusted. Each
+//! refresh names the capture areas the views read from; if anything we draw
+//! covers them while the frame is in flight, the frame is discarded. When
+//! the lens sits on top of its own source, which is the usual case, the
+//! overlay is hidden for the one frame being captured instead.
+//!
+//! Fresh pixels are copied into the monitor's screenshot area by area, so
+//! everything else keeps the content from earlier captures. A region
+//! capture (`--capture-region`) is scheduled the same way, but replaces the
+//! screenshot whole.
+//!
+//! Backends that report damage let a frame whose areas didn't change skip
+//! the copy and the redraw, as long as the frame before it refreshed the
+//! same areas.
+
+use crate::capture::Capture;
+use crate::pool_buffer::PoolBuffer;
+use crate::utils::{Rect, Vector2D};
+use std::time::{Duration, Instant};
+
+/// Capture pixels refreshed around each source area, so small pointer
+/// moves and resampling kernels find fresh content.
+pub const SOURCE_MARGIN: f64 = 64.0;
+
+/// A capture on its way.
+#[derive(Debug, Clone)]
+struct Pending {
+    monitor: usize,
+    /// Capture areas the frame refreshes
+    areas: Vec<Rect>,
+    /// Whether the overlay was hidden for this frame
+    hidden: bool,
+    /// Whether something drawn since the request covered `areas`
+    tainted: bool,
+}
+
+/// Schedule and bookkeeping for live captures.
+#[derive(Debug, Clone)]
+pub struct LiveCapture {
+    /// Time between captures
+    pub interval: Duration,
+    /// When the last capture was requested
+    last: Option<Instant>,
+    pending: Option<Pending>,
+    /// Monitor and areas the last kept frame refreshed
+    fresh: Option<(usize, Vec<Rect>)>,
+}
+
+impl LiveCapture {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last: None,
+            pending: None,
+            fresh: None,
+        }
+    }
+
+    /// Time until the next capture is due, or None while a frame is in
+    /// flight and its arrival will wake us anyway.
+    pub fn wait(&self, now: Instant) -> Option<Duration> {
+        if self.pending.is_some() {
+            return None;
+        }
+        Some(match self.last {
+            Some(last) => (last + self.interval).saturating_duration_since(now),
+            None => Duration::ZERO,
+        })
+    }
+
+    /// Note a capture of `areas` on `monitor`, hiding the overlay for it
+    /// if `hidden`.
+    pub fn start(&mut self, monitor: usize, areas: Vec<Rect>, hidden: bool, now: Instant) {
+        self.last = Some(now);
+        self.pending = Some(Pending {
+            monitor,
+            areas,
+            hidden,
+            tainted: false,
+        });
+    }
+
+    /// Whether the overlay on `monitor` must stay hidden for a capture.
+    pub fn is_hiding(&self, monitor: usize) -> bool {
+        self.pending
+            .as_ref()
+            .is_some_and(|p| p.monitor == monitor && p.hidden)
+    }
+
+    /// Capture areas of the frame in flight on `monitor`.
+    pub fn areas(&self, monitor: usize) -> Option<&[Rect]> {
+        self.pending
+            .as_ref()
+            .filter(|p| p.monitor == monitor)
+            .map(|p| p.areas.as_slice())
+    }
+
+    /// Note that a buffer was drawn on `monitor`; `clear` tells whether it
+    /// left the capture areas transparent.
+    pub fn drawn(&mut self, monitor: usize, clear: bool) {
+        if let Some(pending) = self.pending.as_mut().filter(|p| p.monitor == monitor) {
+            pending.tainted |= !clear;
+        }
+    }
+
+    /// The frame for `monitor` arrived, with the `damage` its backend
+    /// reported if any: the areas it may refresh, empty if none of them
+    /// changed, or None if our overlay covered them while it was captured.
+    pub fn finish(&mut self, monitor: usize, damage: Option<&[Rect]>) -> Option<Vec<Rect>> {
+        self.areas(monitor)?;
+        let pending = self.pending.take()?;
+        if pending.tainted {
+            // Its changes are lost to the damage of the next frame
+            self.fresh = None;
+            return None;
+        }
+
+        let covered = self.fresh.as_ref().is_some_and(|(m, fresh)| {
+            *m == monitor
+                && pending
+                    .areas
+                    .iter()
+                    .all(|area| fresh.iter().any(|f| f.covers(area)))
+        });
+        let unchanged = damage.is_some_and(|damage| {
+            damage.iter().all(|d| {
+                pending
+                    .areas
+                    .iter()
+                    .all(|area| area.intersect(d).is_empty())
+            })
+        });
+        if covered && unchanged {
+            return Some(Vec::new());
+        }
+        self.fresh = Some((monitor, pending.areas.clone()));
+        Some(pending.areas)
+    }
+
+    /// The frame for `monitor` failed; try again when next due.
+    pub fn cancel(&mut self, monitor: usize) {
+        if self.areas(monitor).is_some() {
+            self.pending = None;
+            self.fresh = None;
+        }
+    }
+}
+
+/// Whether `overlay` is fully transparent over the capture `areas`, given
+/// the size of the capture they are in.
+pub fn is_clear(overlay: &mut PoolBuffer, areas: &[Rect], capture_size: Vector2D) -> bool {
+    let to_overlay = overlay.pixel_size / capture_size;
+    let (width, height) = (overlay.pixel_size.x as i32, overlay.pixel_size.y as i32);
+    let stride = overlay.pixel_stride();
+    let pixels = overlay.pixels_mut();
+
+    areas.iter().all(|area| {
+        let area = Rect::new(area.pos * to_overlay, area.size * to_overlay);
+        is_transparent(pixels, stride, width, height, area)
+    })
+}
+
+/// Whether every pixel of `area` in ARGB32 `pixels` has zero alpha. Parts
+/// outside the `width` x `height` image count as transparent.
+fn is_transparent(pixels: &[u8], stride: usize, width: i32, height: i32, area: Rect) -> bool {
+    let (x, y, w, h) = area.to_pixels();
+    let (x0, x1) = (x.clamp(0, width), (x + w).clamp(0, width));
+    let (y0, y1) = (y.clamp(0, height), (y + h).clamp(0, height));
+
+    (y0..y1).all(|row| {
+        let line = &pixels[row as usize * stride..];
+        (x0..x1).all(|col| {
+            let px = &line[col as usize * 4..col as usize * 4 + 4];
+            u32::from_ne_bytes([px[0], px[1], px[2], px[3]]) >> 24 == 0
+        })
+    })
+}
+
+/// Copy `areas` of a fresh `frame` into `screenshot`.
+pub fn refresh(screenshot: &mut Capture, frame: &mut Capture, areas: &[Rect]) {
+    for area in areas {
+        screenshot.copy_from(frame, *area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(x: f64, y: f64, w: f64, h: f64) -> Rect {
+        Rect::new(Vector2D::new(x, y), Vector2D::new(w, h))
+    }
+
+    #[test]
+    fn test_schedule() {
+        let start = Instant::now();
+        let mut live = LiveCapture::new(Duration::from_millis(100));
+        assert_eq!(live.wait(start), Some(Duration::ZERO));
+
+        live.start(0, vec![area(0.0, 0.0, 10.0, 10.0)], false, start);
+        // Nothing new while a frame is in flight, even once the interval passed
+        assert_eq!(live.wait(start + Duration::from_millis(200)), None);
+        assert!(live.finish(1, None).is_none());
+        assert!(live.finish(0, None).is_some());
+
+        assert_eq!(
+            live.wait(start + Duration::from_millis(30)),
+            Some(Duration::from_millis(70))
+        );
+        assert_eq!(
+            live.wait(start + Duration::from_millis(150)),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_drawing_over_areas_discards_frame() {
+        let now = Instant::now();
+        let mut live = LiveCapture::new(Duration::from_millis(100));
+
+        live.start(0, vec![area(0.0, 0.0, 10.0, 10.0)], true, now);
+        assert!(live.is_hiding(0));
+        assert!(!live.is_hiding(1));
+        // Other monitors don't matter
+        live.drawn(1, false);
+        live.drawn(0, true);
+        assert_eq!(live.finish(0, None).unwrap().len(), 1);
+        assert!(!live.is_hiding(0));
+
+        live.start(0, vec![area(0.0, 0.0, 10.0, 10.0)], false, now);
+        live.drawn(0, false);
+        live.drawn(0, true);
+        assert!(live.finish(0, None).is_none());
+
+        live.start(0, Vec::new(), false, now);
+        live.cancel(0);
+        assert!(live.areas(0).is_none());
+    }
+
+    #[test]
+    fn test_damage_skips_unchanged_areas() {
+        let now = Instant::now();
+        let mut live = LiveCapture::new(Duration::from_millis(100));
+        let lens = area(100.0, 100.0, 50.0, 50.0);
+        let elsewhere = [area(0.0, 0.0, 10.0, 10.0)];
+
+        // The first frame has nothing to compare with
+        live.start(0, vec![lens], false, now);
+        assert_eq!(live.finish(0, Some(&elsewhere)), Some(vec![lens]));
+
+        live.start(0, vec![lens], false, now);
+        assert_eq!(live.finish(0, Some(&elsewhere)), Some(Vec::new()));
+        live.start(0, vec![lens], false, now);
+        assert_eq!(
+            live.finish(0, Some(&[area(120.0, 0.0, 10.0, 110.0)])),
+            Some(vec![lens])
+        );
+
+        // Areas the last frame didn't refresh may be stale
+        let moved = area(130.0, 100.0, 50.0, 50.0);
+        live.start(0, vec![moved], false, now);
+        assert_eq!(live.finish(0, Some(&elsewhere)), Some(vec![moved]));
+
+        // Without damage, or after a discarded frame, everything counts
+        live.start(0, vec![moved], false, now);
+        assert_eq!(live.finish(0, None), Some(vec![moved]));
+        live.start(0, vec![moved], false, now);
+        live.drawn(0, false);
+        assert!(live.finish(0, Some(&elsewhere)).is_none());
+        live.start(0, vec![moved], false, now);
+        assert_eq!(live.finish(0, Some(&elsewhere)), Some(vec![moved]));
+    }
+
+    #[test]
+    fn test_is_transparent() {
+        // 4x3 image, opaque at (2, 1) only
+        let (width, height, stride) = (4, 3, 16);
+        let mut pixels = vec![0u8; stride * height as usize];
+        pixels[stride + 8..stride + 12].copy_from_slice(&0xff10_2030u32.to_ne_bytes());
+
+        let transparent = |a| is_transparent(&pixels, stride, width, height, a);
+        assert!(transparent(area(0.0, 0.0, 2.0, 3.0)));
+        assert!(!transparent(area(1.5, 0.5, 1.0, 1.0)));
+        assert!(!transparent(area(-10.0, -10.0, 100.0, 100.0)));
+        // Outside the image there is nothing of ours
+        assert!(transparent(area(10.0, 0.0, 5.0, 5.0)));
+    }
+}
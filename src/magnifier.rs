@@ -1,16 +1,35 @@
-use crate::config::Config;
-use crate::layer_surface::LayerSurface;
+use crate::animation::Animated;
+use crate::capture::{self, ext, wlr, BufferSpec, Capture, FrameRequest, Orientation, Started};
+use crate::clipboard::Clipboard;
+use crate::config::{CaptureBackend, Config, DockEdge, PickFormat, ViewMode};
+use crate::cursor::Cursor;
+use crate::effects::CvdKind;
+use crate::effects::Image;
+use crate::hud::Hud;
+use crate::input::keyboard::Keyboard;
+use crate::layer_surface::{LayerSurface, SurfaceRole};
+use crate::lens::{Lens, Pins};
+use crate::live::{self, LiveCapture};
+use crate::measure::{Guide, Measure};
 use crate::monitor::Monitor;
-use crate::renderer::Renderer;
-use crate::utils::Vector2D;
+use crate::picker;
+use crate::pixel_format::PixelFormat;
+use crate::renderer::{lens_center, Renderer};
+use crate::smoothing::Follower;
+use crate::snapshot::{self, SnapshotKind, Snapshots};
+use crate::utils::{Rect, Vector2D};
+use crate::zoom::SCROLL_NOTCH;
 use anyhow::{Context, Result};
+use cairo::ImageSurface;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 use wayland_client::protocol::{
     wl_compositor::WlCompositor, wl_keyboard::WlKeyboard, wl_output::WlOutput,
     wl_pointer::WlPointer, wl_registry, wl_seat::WlSeat, wl_shm::WlShm,
 };
 use wayland_client::{Connection, Dispatch, QueueHandle};
+use xkbcommon::xkb::Keysym;
 
 pub struct Magnifier {
     config: Config,
@@ -30,23 +49,51 @@ pub struct AppState {
     shm: Option<WlShm>,
     seat: Option<WlSeat>,
     pointer: Option<WlPointer>,
+    keyboard: Option<Keyboard>,
+    clipboard: Option<Clipboard>,
+    // Serial of the latest pointer button or key press, for wl_data_device
+    input_serial: u32,
     layer_shell: Option<wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_shell_v1::ZwlrLayerShellV1>,
     screencopy_manager: Option<wayland_protocols_wlr::screencopy::v1::client::zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1>,
+    ext_capture_sources: Option<ExtOutputImageCaptureSourceManagerV1>,
+    ext_capture_manager: Option<ExtImageCopyCaptureManagerV1>,
+    /// Where frames come from, chosen once the globals are known
+    capture_backend: Option<Box<dyn capture::Backend<AppState>>>,
     monitors: Vec<Monitor>,
     layer_surfaces: Vec<LayerSurface>,
     next_output_id: u32,
 
-    // Track screencopy frames
-    pending_frames: Vec<(ZwlrScreencopyFrameV1, usize)>, // (frame, monitor_idx)
+    // Monitors with a capture in flight
+    pending_captures: Vec<usize>,
+    /// Bake the compositor's cursor into captures
+    capture_cursor: bool,
+    /// Re-captures of the active monitor
+    live: LiveCapture,
+    /// Re-capture on a timer, with `--continuous`
+    continuous: bool,
+    /// Capture only around the views, with `--capture-region`
+    region_capture: bool,
+    dock_edge: DockEdge,
 
     // Magnifier state
     magnifier_position: Vector2D,
-    magnifier_size: Vector2D,
+    /// Raw pointer position on the active monitor, before smoothing
+    pointer_position: Vector2D,
+    /// Lenses left on screen with `k` or a middle click
+    pins: Pins,
+    /// Smooths the raw pointer into `magnifier_position`
+    follower: Follower,
+    /// Lens size, easing toward the last size set
+    magnifier_size: Animated<Vector2D>,
+    /// Target zoom level; `zoom_transition` eases the drawn zoom toward it
     zoom: f64,
-    zoom_speed: f64,
+    zoom_transition: Animated<f64>,
     exit_delay_ms: u64,
     hide_cursor: bool,
     active_monitor: Option<usize>, // Which monitor the cursor is currently on
+    pick_size: u32,
+    pick_format: PickFormat,
+    snapshots: Snapshots,
 
     // Renderer
     renderer: Renderer,
@@ -105,25 +152,64 @@ impl Magnifier {
         let qh = event_queue.handle();
 
         // Create initial state
+        let animation = Duration::from_millis(self.config.animation_ms);
+        // Start at 2x, or as close as the zoom limits allow
+        let zoom = self.config.zoom.clamp_zoom(0.5);
         let mut state = AppState {
             compositor: None,
             shm: None,
             seat: None,
             pointer: None,
+            keyboard: None,
+            clipboard: None,
+            input_serial: 0,
             layer_shell: None,
             screencopy_manager: None,
+            ext_capture_sources: None,
+            ext_capture_manager: None,
+            capture_backend: None,
             monitors: Vec::new(),
             layer_surfaces: Vec::new(),
             next_output_id: 0,
-            pending_frames: Vec::new(),
+            pending_captures: Vec::new(),
+            capture_cursor: self.config.capture_cursor,
+            live: LiveCapture::new(Duration::from_secs(1) / self.config.refresh_rate),
+            continuous: self.config.continuous_capture,
+            region_capture: self.config.region_capture,
+            dock_edge: self.config.dock_edge,
             magnifier_position: Vector2D::new(500.0, 500.0), // Default position
-            magnifier_size: self.config.size,
-            zoom: 0.5, // 2x zoom (zoom = 0.5 means we show half the area, effectively 2x magnification)
-            zoom_speed: self.config.zoom_speed,
+            pointer_position: Vector2D::new(500.0, 500.0),
+            pins: Pins::default(),
+            follower: Follower::new(self.config.smoothing, Vector2D::new(500.0, 500.0)),
+            magnifier_size: Animated::new(self.config.size, animation, self.config.easing),
+            zoom, // 2x zoom (zoom = 0.5 means we show half the area, effectively 2x magnification)
+            zoom_transition: Animated::new(zoom, animation, self.config.easing),
             exit_delay_ms: self.config.exit_delay_ms,
             hide_cursor: self.config.hide_cursor,
             active_monitor: None, // Will be set when pointer enters a surface
-            renderer: Renderer::new(),
+            pick_size: self.config.pick_size,
+            pick_format: self.config.pick_format,
+            snapshots: self.config.snapshots.clone(),
+            renderer: Renderer {
+                zoom_steps: self.config.zoom.clone(),
+                shape: self.config.shape,
+                lens: self.config.lens,
+                lens_offset: self.config.lens_offset,
+                mode: self.config.mode,
+                color: self.config.color,
+                cvd: self.config.cvd,
+                filter: self.config.filter,
+                filter_threshold: self.config.filter_threshold,
+                text: self.config.text,
+                pixel_grid: self.config.pixel_grid,
+                inspector: self.config.inspector,
+                measure: Measure::new(self.config.measure),
+                hud: Hud::new(self.config.hud),
+                cursor: Cursor::load(self.config.cursor),
+                spotlight: self.config.spotlight,
+                background: !self.config.continuous_capture && !self.config.region_capture,
+                ..Renderer::new()
+            },
             running: self.running.clone(),
             initial_render_done: false,
             pointer_position_confirmed: false,
@@ -185,11 +271,39 @@ impl Magnifier {
                 "Layer shell not available - your compositor doesn't support wlr-layer-shell"
             );
         }
-        if state.screencopy_manager.is_none() {
-            anyhow::bail!(
-                "Screencopy not available - your compositor doesn't support wlr-screencopy"
+        let backend = capture::choose(
+            self.config.capture_backend,
+            state.screencopy_manager.is_some(),
+            state.ext_capture_sources.is_some() && state.ext_capture_manager.is_some(),
+            state.region_capture,
+        )?;
+        let backend: Box<dyn capture::Backend<AppState>> = match backend {
+            CaptureBackend::WlrScreencopy => Box::new(wlr::WlrScreencopy::new(
+                state.screencopy_manager.clone().unwrap(),
+            )),
+            CaptureBackend::ExtImageCopy => Box::new(ext::ExtImageCopy::new(
+                state.ext_capture_sources.clone().unwrap(),
+                state.ext_capture_manager.clone().unwrap(),
+            )),
+            CaptureBackend::Image | CaptureBackend::Auto => {
+                let path = self
+                    .config
+                    .capture_image
+                    .as_deref()
+                    .context("The image capture backend needs --capture-image")?;
+                Box::new(capture::image::ImageFile::load(path)?)
+            }
+        };
+        if state.region_capture && !backend.supports_regions() {
+            log::warn!(
+                "The {} capture backend can't capture regions - capturing whole outputs",
+                backend.name()
             );
+            state.region_capture = false;
+            state.renderer.background = !state.continuous;
         }
+        log::info!("Capturing with {}", backend.name());
+        state.capture_backend = Some(backend);
 
         log::info!("All required protocols available - setting up surfaces...");
 
@@ -227,14 +341,78 @@ impl Magnifier {
 
             log::info!("Layer surface {} created and configured", idx);
 
-            // Create LayerSurface wrapper
-            let mut ls = LayerSurface::new(idx, surface, monitor.size, monitor.scale);
+            // Create LayerSurface wrapper, portrait on a rotated output as
+            // the surface and the captures are
+            let mut ls = LayerSurface::new(idx, surface, monitor.upright_size(), monitor.scale);
             ls.fractional_scale_value = monitor.fractional_scale;
             ls.layer_surface = Some(layer_surface);
             state.layer_surfaces.push(ls);
             monitor.layer_surface_idx = Some(idx);
         }
 
+        // Docked mode: an edge-anchored pane per monitor that reserves space
+        // with an exclusive zone so windows reflow around it
+        if self.config.mode == ViewMode::Docked {
+            use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_shell_v1::Layer;
+            use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1::{
+                Anchor, KeyboardInteractivity,
+            };
+
+            let thickness = self.config.dock_size;
+            let edge = self.config.dock_edge;
+            let anchor = match edge {
+                DockEdge::Top => Anchor::Top | Anchor::Left | Anchor::Right,
+                DockEdge::Bottom => Anchor::Bottom | Anchor::Left | Anchor::Right,
+                DockEdge::Left => Anchor::Left | Anchor::Top | Anchor::Bottom,
+                DockEdge::Right => Anchor::Right | Anchor::Top | Anchor::Bottom,
+            };
+            // Zero along the anchored axis lets the compositor stretch the pane
+            let (width, height) = if edge.is_horizontal() {
+                (0, thickness)
+            } else {
+                (thickness, 0)
+            };
+
+            for (idx, monitor) in state.monitors.iter().enumerate() {
+                log::info!("Creating docked pane for monitor {}", idx);
+
+                let surface = compositor.create_surface(&qh, ());
+
+                // The pane never takes input; the overlay keeps tracking the pointer
+                let region = compositor.create_region(&qh, ());
+                surface.set_input_region(Some(&region));
+                region.destroy();
+
+                let layer_surface = layer_shell.get_layer_surface(
+                    &surface,
+                    Some(&monitor.output),
+                    Layer::Overlay,
+                    "remagnify-dock".to_string(),
+                    &qh,
+                    (),
+                );
+                layer_surface.set_anchor(anchor);
+                layer_surface.set_size(width, height);
+                layer_surface.set_exclusive_zone(thickness as i32);
+                layer_surface.set_keyboard_interactivity(KeyboardInteractivity::None);
+
+                surface.commit();
+
+                // Real size arrives with the configure event
+                let logical = Vector2D::new(width as f64, height as f64);
+                let mut ls = LayerSurface::new(
+                    idx,
+                    surface,
+                    logical * monitor.fractional_scale,
+                    monitor.scale,
+                );
+                ls.role = SurfaceRole::Dock;
+                ls.fractional_scale_value = monitor.fractional_scale;
+                ls.layer_surface = Some(layer_surface);
+                state.layer_surfaces.push(ls);
+            }
+        }
+
         // Sync to get configure events and acknowledge them
         event_queue.roundtrip(&mut state)?;
 
@@ -301,6 +479,7 @@ impl Magnifier {
         if let Some((monitor_idx, x, y)) = state.first_enter_during_init {
             state.active_monitor = Some(monitor_idx);
             state.magnifier_position = Vector2D::new(x, y);
+            state.follower.reset(state.magnifier_position);
             // pointer_position_confirmed stays false - will be set by Motion event
             log::info!("→ Initial state from Enter: monitor {} at ({:.1}, {:.1}) - waiting for Motion to confirm", monitor_idx, x, y);
         } else {
@@ -310,17 +489,10 @@ impl Magnifier {
         log::info!("All layer surfaces mapped and ready for input");
 
         // Start screencopy for each monitor
-        let screencopy_mgr = state.screencopy_manager.as_ref().unwrap();
-        for (idx, monitor) in state.monitors.iter().enumerate() {
+        for idx in 0..state.monitors.len() {
             log::info!("Starting screencopy for monitor {}", idx);
-
-            // Capture the output (with overlay_cursor = 0 to not include cursor)
-            let frame = screencopy_mgr.capture_output(0, &monitor.output, &qh, ());
-
-            // Track this frame
-            state.pending_frames.push((frame, idx));
-
-            log::debug!("Screencopy frame requested for monitor {}", idx);
+            let region = state.capture_region(idx);
+            state.request_capture(idx, region, &qh);
         }
 
         // Flush and process initial screencopy events
@@ -349,6 +521,10 @@ impl Magnifier {
                 }
             }
 
+            // Let the HUD fade out without waiting for input
+            state.refresh_hud(&qh);
+            state.refresh_live(&qh);
+
             // Flush the connection
             if let Err(e) = conn.flush() {
                 log::error!("Failed to flush connection: {}", e);
@@ -362,14 +538,23 @@ impl Magnifier {
                 use std::os::unix::io::AsRawFd;
                 let fd = guard.connection_fd().as_raw_fd();
 
-                // Poll with 100ms timeout
+                // Poll with 100ms timeout, or about a frame while the HUD fades
                 let mut pollfd = libc::pollfd {
                     fd,
                     events: libc::POLLIN,
                     revents: 0,
                 };
+                let mut timeout = if state.renderer.hud.opacity() > 0.0 {
+                    16
+                } else {
+                    100
+                };
+                // Wake for the next live capture, rounding up so it is due
+                if let Some(wait) = state.live_wait(Instant::now()) {
+                    timeout = timeout.min(wait.as_micros().div_ceil(1000) as i32);
+                }
 
-                let poll_result = unsafe { libc::poll(&mut pollfd, 1, 100) };
+                let poll_result = unsafe { libc::poll(&mut pollfd, 1, timeout) };
 
                 if poll_result > 0 {
                     // Data is available to read
@@ -400,31 +585,47 @@ impl AppState {
         T: wayland_client::Dispatch<WlShmPool, ()> + 'static,
         T: wayland_client::Dispatch<WlCallback, ()> + 'static,
     {
+        let docked = self.renderer.mode == ViewMode::Docked;
+
+        // Only show magnifier on the active monitor AND if we have a confirmed pointer position
+        // We wait for the first Motion event to ensure accurate coordinates (Enter events
+        // during initialization can have wrong coordinates for offset monitors)
+        let is_active = !docked && self.is_active_monitor(monitor_idx);
+
+        // In docked mode the overlay only tracks the pointer and stays
+        // transparent, so it needs a single commit; the pane does the drawing.
+        if docked
+            && self
+                .layer_surfaces
+                .iter()
+                .any(|ls| ls.is_overlay_for(monitor_idx) && ls.rendered)
+        {
+            return self.render_dock(monitor_idx, qh);
+        }
+
         // Get the monitor's screen buffer
         let monitor = self
             .monitors
             .get_mut(monitor_idx)
             .context("Invalid monitor index")?;
+        self.renderer
+            .measure
+            .set_monitor(monitor_idx, monitor.fractional_scale);
+        if is_active {
+            self.renderer
+                .hud
+                .update(self.zoom, &monitor.name, Instant::now());
+        }
 
-        // Prefer the permanent snapshot over the raw screen_buffer.
-        // Hyprland's SHM screencopy calls copy() on every frame commit
-        // (m_copied is never set for SHM frames), which overwrites screen_buffer
-        // with the composited output that already includes our overlay.
-        // The permanent snapshot was captured once before any overlay was rendered.
-        let screen_buffer = if monitor.screenshot.is_some() {
-            monitor.screenshot.as_mut().unwrap()
-        } else {
-            monitor
-                .screen_buffer
-                .as_mut()
-                .context("No screen buffer available")?
-        };
+        let screen_buffer = monitor
+            .capture_buffer_mut()
+            .context("No screen buffer available")?;
 
         // Find the corresponding layer surface
         let layer_surface = self
             .layer_surfaces
             .iter_mut()
-            .find(|ls| ls.monitor_idx == monitor_idx)
+            .find(|ls| ls.is_overlay_for(monitor_idx))
             .context("No layer surface found for monitor")?;
 
         if !layer_surface.configured {
@@ -437,70 +638,1120 @@ impl AppState {
 
         // Get or create an output buffer
         let shm = self.shm.as_ref().context("No SHM available")?;
+        layer_surface.ensure_buffers(shm, qh)?;
 
-        let output_buffer = layer_surface.get_available_buffer();
-        if output_buffer.is_none() {
-            // Create new buffers if needed
-            log::debug!("Creating output buffers for layer surface {}", monitor_idx);
+        let output_buffer = layer_surface
+            .get_available_buffer()
+            .context("No available buffer after creation")?;
 
-            // Use logical size for buffer creation (physical size / fractional scale)
-            let pixel_size = layer_surface.get_logical_size();
-            let stride = (pixel_size.x as u32) * 4; // ARGB32 = 4 bytes per pixel
-            let format = wayland_client::protocol::wl_shm::Format::Argb8888 as u32;
+        // Step out of the way while the monitor is captured
+        if self.live.is_hiding(monitor_idx) {
+            let ctx = output_buffer.create_cairo_context()?;
+            ctx.set_operator(cairo::Operator::Clear);
+            ctx.paint()?;
+            drop(ctx);
+            layer_surface.send_frame(qh);
+            return Ok(());
+        }
 
-            for i in 0..2 {
-                if layer_surface.buffers[i].is_none() {
-                    use crate::pool_buffer::PoolBuffer;
-                    let buffer = PoolBuffer::new(pixel_size, format, stride, shm, qh)?;
-                    layer_surface.buffers[i] = Some(buffer);
+        // Sync zoom from AppState to renderer
+        let now = Instant::now();
+        self.renderer.set_zoom(self.zoom_transition.value_at(now));
+        let pins = self.pins.on(monitor_idx);
+
+        if is_active {
+            // Over a pin the live lens steps aside so the pin can be handled
+            let over_pin = self.pins.hovered().is_some();
+
+            // Render the magnified view on the active monitor
+            self.renderer.render_surface(
+                output_buffer,
+                screen_buffer,
+                self.magnifier_position,
+                self.pointer_position,
+                self.magnifier_size.value_at(now),
+                &pins,
+                over_pin, // force_inactive
+                over_pin, // render_inactive
+            )?;
+            log::debug!(
+                "Rendered magnifier on monitor {} at position {:?}",
+                monitor_idx,
+                self.magnifier_position
+            );
+        } else {
+            // Render inactive (no magnifier, only pins) on other monitors
+            self.renderer.render_surface(
+                output_buffer,
+                screen_buffer,
+                self.magnifier_position,
+                self.pointer_position,
+                self.magnifier_size.value_at(now),
+                &pins,
+                true,  // force_inactive
+                false, // render_inactive
+            )?;
+            log::trace!("Cleared inactive monitor {}", monitor_idx);
+        }
+
+        // A live frame in flight is spoiled if this buffer covers its areas
+        let screen_size = screen_buffer.pixel_size;
+        let clear = self
+            .live
+            .areas(monitor_idx)
+            .map(|areas| live::is_clear(output_buffer, areas, screen_size));
+        if let Some(clear) = clear {
+            self.live.drawn(monitor_idx, clear);
+        }
+
+        // Attach and commit the buffer
+        layer_surface.send_frame(qh);
+
+        if docked {
+            self.render_dock(monitor_idx, qh)?;
+        }
+
+        Ok(())
+    }
+
+    /// Render the docked pane of a monitor, if it has one
+    fn render_dock<T>(&mut self, monitor_idx: usize, qh: &QueueHandle<T>) -> Result<()>
+    where
+        T: wayland_client::Dispatch<WlBuffer, ()> + 'static,
+        T: wayland_client::Dispatch<WlShmPool, ()> + 'static,
+        T: wayland_client::Dispatch<WlCallback, ()> + 'static,
+    {
+        let is_active = self.is_active_monitor(monitor_idx);
+
+        let monitor = self
+            .monitors
+            .get_mut(monitor_idx)
+            .context("Invalid monitor index")?;
+        let source_space = monitor.get_logical_size();
+        self.renderer
+            .measure
+            .set_monitor(monitor_idx, monitor.fractional_scale);
+        if is_active {
+            self.renderer
+                .hud
+                .update(self.zoom, &monitor.name, Instant::now());
+        }
+        let screen_buffer = monitor
+            .capture_buffer_mut()
+            .context("No screen buffer available")?;
+
+        let Some(dock) = self
+            .layer_surfaces
+            .iter_mut()
+            .find(|ls| ls.role == SurfaceRole::Dock && ls.monitor_idx == monitor_idx)
+        else {
+            return Ok(());
+        };
+
+        if !dock.configured {
+            return Ok(());
+        }
+
+        let shm = self.shm.as_ref().context("No SHM available")?;
+        dock.ensure_buffers(shm, qh)?;
+        let output_buffer = dock
+            .get_available_buffer()
+            .context("No available dock buffer")?;
+
+        self.renderer
+            .set_zoom(self.zoom_transition.value_at(Instant::now()));
+
+        if is_active {
+            self.renderer.render_docked(
+                output_buffer,
+                screen_buffer,
+                self.magnifier_position,
+                self.pointer_position,
+                source_space,
+            )?;
+        } else {
+            // Keep the reserved strip visibly empty on inactive monitors
+            let ctx = output_buffer.create_cairo_context()?;
+            ctx.set_source_rgba(0.1, 0.1, 0.1, 1.0);
+            ctx.set_operator(cairo::Operator::Source);
+            ctx.paint()?;
+        }
+
+        dock.send_frame(qh);
+
+        Ok(())
+    }
+
+    /// Act on a key press
+    fn handle_keysym(&mut self, keysym: Keysym, qh: &QueueHandle<Self>) {
+        let color = &mut self.renderer.color;
+
+        match keysym {
+            Keysym::Escape => {
+                log::info!("Escape key pressed, exiting...");
+                self.running.store(false, Ordering::SeqCst);
+                return;
+            }
+            Keysym::i => color.mode = color.mode.next(),
+            Keysym::I => color.mode = color.mode.prev(),
+            Keysym::b => color.set_brightness(color.brightness - 0.05),
+            Keysym::B => color.set_brightness(color.brightness + 0.05),
+            Keysym::c => color.set_contrast(color.contrast - 0.1),
+            Keysym::C => color.set_contrast(color.contrast + 0.1),
+            Keysym::y => color.set_gamma(color.gamma - 0.1),
+            Keysym::Y => color.set_gamma(color.gamma + 0.1),
+            Keysym::d => {
+                let cvd = &mut self.renderer.cvd;
+                cvd.kind = CvdKind::cycle(cvd.kind);
+            }
+            Keysym::D => {
+                let cvd = &mut self.renderer.cvd;
+                cvd.mode = cvd.mode.toggle();
+            }
+            Keysym::s => self.renderer.text.sharpen = !self.renderer.text.sharpen,
+            Keysym::r => {
+                let text = &mut self.renderer.text;
+                text.reading = text.reading.next();
+            }
+            Keysym::comma => {
+                let text = &mut self.renderer.text;
+                text.set_amount(text.amount - 0.25);
+            }
+            Keysym::period => {
+                let text = &mut self.renderer.text;
+                text.set_amount(text.amount + 0.25);
+            }
+            Keysym::g => self.renderer.pixel_grid = !self.renderer.pixel_grid,
+            Keysym::x => self.renderer.inspector = !self.renderer.inspector,
+            Keysym::bracketleft => self.resize_lens(1.0 / LENS_RESIZE_STEP),
+            Keysym::bracketright => self.resize_lens(LENS_RESIZE_STEP),
+            Keysym::k => {
+                self.pin_lens(qh);
+                return;
+            }
+            Keysym::o => {
+                let spotlight = &mut self.renderer.spotlight;
+                spotlight.enabled = !spotlight.enabled;
+                log::info!("Spotlight: {}", spotlight.enabled);
+                // The dim covers every monitor
+                for idx in 0..self.monitors.len() {
+                    if let Err(e) = self.render_monitor(idx, qh) {
+                        log::error!("Failed to render spotlight: {}", e);
+                    }
+                }
+                return;
+            }
+            Keysym::K => {
+                self.close_pin(qh);
+                return;
+            }
+            preset if (Keysym::_1.raw()..=Keysym::_9.raw()).contains(&preset.raw()) => {
+                let n = (preset.raw() - Keysym::_0.raw()) as usize;
+                match self.renderer.zoom_steps.preset(n) {
+                    Some(magnification) => self.set_magnification(magnification),
+                    None => return,
                 }
             }
+            Keysym::p => {
+                self.pick_color(qh);
+                return;
+            }
+            Keysym::w => {
+                self.save_snapshot(SnapshotKind::View, qh);
+                return;
+            }
+            Keysym::W => {
+                self.save_snapshot(SnapshotKind::Source, qh);
+                return;
+            }
+            Keysym::m => self.renderer.measure.toggle(),
+            Keysym::a => {
+                self.place_measure(|measure, pointer| measure.anchor_at(pointer), qh);
+                return;
+            }
+            Keysym::h => {
+                self.place_measure(
+                    |measure, pointer| measure.add_guide(Guide::Horizontal(pointer.y)),
+                    qh,
+                );
+                return;
+            }
+            Keysym::v => {
+                self.place_measure(
+                    |measure, pointer| measure.add_guide(Guide::Vertical(pointer.x)),
+                    qh,
+                );
+                return;
+            }
+            Keysym::Delete => {
+                self.place_measure(|measure, _| measure.clear_guides(), qh);
+                return;
+            }
+            _ => return,
         }
 
-        let output_buffer = layer_surface
-            .get_available_buffer()
-            .context("No available buffer after creation")?;
+        let (color, cvd, text) = (
+            &self.renderer.color,
+            &self.renderer.cvd,
+            &self.renderer.text,
+        );
+        log::info!(
+            "Colour: {:?} brightness={:.2} contrast={:.2} gamma={:.2} cvd={:?} ({:?})",
+            color.mode,
+            color.brightness,
+            color.contrast,
+            color.gamma,
+            cvd.kind,
+            cvd.mode
+        );
+        log::info!(
+            "Text: sharpen={} amount={:.2} reading={:?}",
+            text.sharpen,
+            text.amount,
+            text.reading
+        );
+
+        if let Some(monitor_idx) = self.active_monitor {
+            if let Err(e) = self.render_monitor(monitor_idx, qh) {
+                log::error!("Failed to render after key press: {}", e);
+            }
+        }
+    }
 
-        // Sync zoom from AppState to renderer
-        self.renderer.set_zoom(self.zoom);
+    /// Sample the colour under the lens centre, print it and copy it.
+    fn pick_color(&mut self, qh: &QueueHandle<Self>) {
+        let Some(monitor_idx) = self.active_monitor else {
+            return;
+        };
+        let Some(monitor) = self.monitors.get(monitor_idx) else {
+            return;
+        };
+        let Some(screen) = monitor.capture_buffer() else {
+            log::warn!("No screen capture to pick from yet");
+            return;
+        };
+
+        let pixel = (self.magnifier_position.floor() / monitor.get_logical_size()
+            * screen.pixel_size)
+            .floor();
+        let Some(color) = picker::sample(screen, pixel.x as i32, pixel.y as i32, self.pick_size)
+        else {
+            return;
+        };
+
+        println!(
+            "{}  {}  {}  {}",
+            color.to_hex(),
+            color.to_rgb(),
+            color.to_hsl(),
+            color.to_oklch()
+        );
+        self.renderer.picks.push(color);
+
+        let text = color.format(self.pick_format);
+        match (self.clipboard.as_mut(), self.seat.as_ref()) {
+            (Some(clipboard), Some(seat)) => {
+                clipboard.set_text(&text, seat, self.input_serial, qh);
+                log::info!("Copied {} to the clipboard", text);
+            }
+            _ => log::warn!("No clipboard available, {} not copied", text),
+        }
+
+        if let Err(e) = self.render_monitor(monitor_idx, qh) {
+            log::error!("Failed to render after colour pick: {}", e);
+        }
+    }
+
+    /// Save the view, or the source area behind it, as a PNG and optionally
+    /// copy it to the clipboard.
+    fn save_snapshot(&mut self, kind: SnapshotKind, qh: &QueueHandle<Self>) {
+        let Some(monitor_idx) = self.active_monitor else {
+            return;
+        };
+        let png = match self
+            .snapshot_surface(monitor_idx, kind)
+            .and_then(|surface| snapshot::encode(&surface))
+        {
+            Ok(png) => png,
+            Err(e) => {
+                log::error!("Failed to take snapshot: {}", e);
+                return;
+            }
+        };
+
+        match self.snapshots.save(&png, kind, SystemTime::now()) {
+            Ok(path) => {
+                println!("{}", path.display());
+                log::info!("Saved snapshot to {}", path.display());
+            }
+            Err(e) => log::error!("Failed to save snapshot: {:#}", e),
+        }
+
+        if self.snapshots.clipboard {
+            match (self.clipboard.as_mut(), self.seat.as_ref()) {
+                (Some(clipboard), Some(seat)) => {
+                    clipboard.set_contents(
+                        &[snapshot::PNG_MIME_TYPE],
+                        png,
+                        seat,
+                        self.input_serial,
+                        qh,
+                    );
+                    log::info!("Copied snapshot to the clipboard");
+                }
+                _ => log::warn!("No clipboard available, snapshot not copied"),
+            }
+        }
+    }
+
+    /// Image of the view on `monitor_idx` as last drawn, or of the capture
+    /// area behind it.
+    ///
+    /// In lens mode the view is the pin under the pointer, or else the live
+    /// lens; in the other modes it is the whole magnified surface.
+    fn snapshot_surface(&mut self, monitor_idx: usize, kind: SnapshotKind) -> Result<ImageSurface> {
+        let mode = self.renderer.mode;
+        let role = match mode {
+            ViewMode::Docked => SurfaceRole::Dock,
+            _ => SurfaceRole::Overlay,
+        };
+        let surface_idx = self
+            .layer_surfaces
+            .iter()
+            .position(|ls| ls.role == role && ls.monitor_idx == monitor_idx && ls.rendered)
+            .context("Nothing drawn on this monitor yet")?;
+        let view_size = self.layer_surfaces[surface_idx].get_logical_size();
+
+        // The view as a lens: what it shows, and where it was drawn
+        let lens = match mode {
+            ViewMode::Lens => Some(
+                self.pins
+                    .hovered()
+                    .copied()
+                    .unwrap_or_else(|| self.live_lens(view_size)),
+            ),
+            ViewMode::Docked => Some(self.dock_lens(view_size)),
+            ViewMode::Fullscreen => None,
+        };
+
+        let monitor = self
+            .monitors
+            .get_mut(monitor_idx)
+            .context("Invalid monitor index")?;
+        let space = monitor.get_logical_size();
+        let screen = monitor
+            .capture_buffer_mut()
+            .context("No screen capture yet")?;
+        let surface = &mut self.layer_surfaces[surface_idx];
+        let view = surface.buffers[surface.last_buffer]
+            .as_mut()
+            .context("No rendered buffer")?;
+
+        match (kind, lens) {
+            (SnapshotKind::View, Some(lens)) if mode == ViewMode::Lens => {
+                self.renderer.snapshot_lens(view, lens.center, lens.size)
+            }
+            (SnapshotKind::View, _) => {
+                Image::crop(view, Rect::new(Vector2D::default(), view_size)).into_surface()
+            }
+            (SnapshotKind::Source, Some(lens)) => {
+                let area = self.renderer.lens_source(screen.pixel_size, space, &lens);
+                screen.crop(area).into_surface()
+            }
+            (SnapshotKind::Source, None) => {
+                let area = self.renderer.viewport_source(
+                    screen.pixel_size,
+                    view_size,
+                    self.magnifier_position,
+                );
+                screen.crop(area).into_surface()
+            }
+        }
+    }
+
+    /// The live lens as last drawn on a monitor of logical size `space`.
+    fn live_lens(&self, space: Vector2D) -> Lens {
+        let size = self.magnifier_size.value_at(Instant::now());
+        let bounds = Rect::new(Vector2D::default(), space);
+        Lens {
+            source: self.magnifier_position,
+            center: lens_center(
+                self.magnifier_position,
+                size,
+                bounds,
+                self.renderer.lens_offset,
+            )
+            .floor(),
+            size,
+            zoom: self.renderer.zoom,
+        }
+    }
+
+    /// The docked pane of size `pane` as a lens centred on the pane.
+    fn dock_lens(&self, pane: Vector2D) -> Lens {
+        Lens {
+            source: self.magnifier_position,
+            center: pane / 2.0,
+            size: pane,
+            zoom: self.renderer.zoom,
+        }
+    }
+
+    /// Ask the capture backend for a frame of `monitor_idx`, or of only its
+    /// `region` in capture pixels.
+    fn request_capture(
+        &mut self,
+        monitor_idx: usize,
+        region: Option<Rect>,
+        qh: &QueueHandle<Self>,
+    ) {
+        let (Some(backend), Some(monitor)) = (
+            self.capture_backend.as_mut(),
+            self.monitors.get(monitor_idx),
+        ) else {
+            return;
+        };
+
+        let request = FrameRequest {
+            monitor: monitor_idx,
+            output: &monitor.output,
+            region,
+            to_capture: monitor.capture_size() / monitor.get_logical_size(),
+            // Leave the cursor out of the capture (the renderer draws its
+            // own) unless asked to bake the compositor's in
+            cursor: self.capture_cursor,
+        };
+        let started = match backend.capture(&request, qh) {
+            Ok(started) => started,
+            Err(e) => {
+                log::error!("Failed to capture monitor {}: {}", monitor_idx, e);
+                return;
+            }
+        };
+        self.pending_captures.push(monitor_idx);
+        log::debug!("Capture requested for monitor {}", monitor_idx);
+
+        match started {
+            Started::Pending => {}
+            Started::NeedsBuffer(spec) => self.attach_frame(monitor_idx, spec, qh),
+            Started::Image(image) => self.frame_image(monitor_idx, image, qh),
+        }
+    }
+
+    /// Buffer a frame of `monitor_idx` is copied into, becoming its
+    /// screen_buffer; `origin` places a region frame on the output.
+    fn frame_buffer(
+        &mut self,
+        monitor_idx: usize,
+        origin: Option<Vector2D>,
+        spec: BufferSpec,
+        qh: &QueueHandle<Self>,
+    ) -> Option<WlBuffer> {
+        if PixelFormat::from_shm(spec.format).is_none() {
+            log::error!("Unsupported capture format {:#x}", spec.format);
+            return None;
+        }
+        let monitor = self.monitors.get_mut(monitor_idx)?;
+
+        // Live captures reuse the last frame's buffer while it fits
+        if let Some(capture) = monitor.screen_buffer.as_mut().filter(|c| {
+            c.buffer.pixel_size == spec.size
+                && c.buffer.stride == spec.stride
+                && c.buffer.format == spec.format
+        }) {
+            capture.origin = origin.unwrap_or_default();
+            return Some(capture.buffer.buffer.clone());
+        }
+
+        // Create a buffer to receive the screenshot
+        let shm = self.shm.as_ref()?;
+        match crate::pool_buffer::PoolBuffer::new(spec.size, spec.format, spec.stride, shm, qh) {
+            Ok(buffer) => {
+                log::debug!("Created capture buffer for monitor {}", monitor_idx);
+                let wl_buffer = buffer.buffer.clone();
+                let capture = match origin {
+                    Some(origin) => Capture {
+                        pixel_size: monitor.capture_size(),
+                        origin,
+                        orientation: monitor.orientation(),
+                        buffer,
+                    },
+                    None => Capture::oriented(buffer, monitor.orientation()),
+                };
+                monitor.screen_buffer = Some(capture);
+                Some(wl_buffer)
+            }
+            Err(e) => {
+                log::error!("Failed to create capture buffer: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Hand the backend a buffer for the frame of `monitor_idx`.
+    fn attach_frame(&mut self, monitor_idx: usize, spec: BufferSpec, qh: &QueueHandle<Self>) {
+        match self.frame_buffer(monitor_idx, None, spec, qh) {
+            Some(buffer) => {
+                if let Some(backend) = self.capture_backend.as_mut() {
+                    backend.attach(monitor_idx, &buffer, qh);
+                }
+            }
+            None => self.frame_failed(monitor_idx, qh),
+        }
+    }
+
+    /// Take a frame of `monitor_idx` the backend already had as an image,
+    /// stretched over the output.
+    fn frame_image(&mut self, monitor_idx: usize, image: Image, qh: &QueueHandle<Self>) {
+        let Some(size) = self.monitors.get(monitor_idx).map(|m| m.capture_size()) else {
+            return;
+        };
+        let spec = BufferSpec {
+            format: u32::from(wayland_client::protocol::wl_shm::Format::Argb8888),
+            size,
+            stride: size.x as u32 * 4,
+        };
+        let (width, height) = (image.width as f64, image.height as f64);
+
+        let painted = self
+            .frame_buffer(monitor_idx, None, spec, qh)
+            .and_then(|_| {
+                self.monitors
+                    .get_mut(monitor_idx)
+                    .and_then(|m| m.screen_buffer.as_mut())
+            })
+            .map(|capture| -> Result<()> {
+                let surface = image.into_surface()?;
+                let ctx = capture.buffer.create_cairo_context()?;
+                ctx.scale(size.x / width, size.y / height);
+                ctx.set_source_surface(&surface, 0.0, 0.0)?;
+                ctx.set_operator(cairo::Operator::Source);
+                ctx.paint()?;
+                Ok(())
+            });
+        match painted {
+            Some(Ok(())) => self.frame_ready(monitor_idx, Orientation::default(), None, qh),
+            Some(Err(e)) => {
+                log::error!("Failed to paint capture image: {}", e);
+                self.frame_failed(monitor_idx, qh);
+            }
+            None => self.frame_failed(monitor_idx, qh),
+        }
+    }
+
+    /// A frame of `monitor_idx` arrived in its screen_buffer, lying as
+    /// `orientation` says, with the damage its backend reported if any.
+    fn frame_ready(
+        &mut self,
+        monitor_idx: usize,
+        orientation: Orientation,
+        damage: Option<Vec<Rect>>,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let Some(pos) = self.pending_captures.iter().position(|&m| m == monitor_idx) {
+            self.pending_captures.remove(pos);
+        }
+        if let Some(capture) = self
+            .monitors
+            .get_mut(monitor_idx)
+            .and_then(|m| m.screen_buffer.as_mut())
+        {
+            capture.set_orientation(orientation);
+            capture.buffer.normalize();
+        }
+
+        if self.live.areas(monitor_idx).is_some() {
+            self.finish_live_capture(monitor_idx, damage, qh);
+            return;
+        }
+
+        log::info!("Monitor {} screen capture complete", monitor_idx);
+
+        // On the FIRST ready for each monitor, make a permanent deep copy of
+        // the screenshot before any render commits a buffer that would cause
+        // Hyprland to overwrite screen_buffer with an overlay-contaminated frame.
+        // We also guard against Hyprland's repeated Ready with
+        // initial_render_done below.
+        //
+        // A region frame is small and already destroyed, so it
+        // simply becomes the screenshot.
+        if self.region_capture {
+            if let Some(m) = self.monitors.get_mut(monitor_idx) {
+                m.screenshot = m.screen_buffer.take();
+            }
+        } else if self
+            .monitors
+            .get(monitor_idx)
+            .is_some_and(|m| m.screenshot.is_none())
+        {
+            // Extract pointer/size info without holding a borrow into monitors.
+            let copy_info = self.monitors.get(monitor_idx).and_then(|m| {
+                m.screen_buffer
+                    .as_ref()
+                    .map(|c| (&c.buffer, c.orientation))
+                    .map(|(src, orientation)| {
+                        (
+                            src.data,
+                            src.size,
+                            src.pixel_size,
+                            src.stride,
+                            src.format,
+                            orientation,
+                        )
+                    })
+            });
+
+            if let Some((src_ptr, src_size, pixel_size, stride, format, orientation)) = copy_info {
+                if let Some(shm) = &self.shm {
+                    match crate::pool_buffer::PoolBuffer::new(pixel_size, format, stride, shm, qh) {
+                        Ok(mut perm) => {
+                            // Safety: src_ptr and perm.data are disjoint SHM
+                            // mappings; screen_buffer is not dropped here.
+                            unsafe {
+                                std::ptr::copy_nonoverlapping(
+                                    src_ptr as *const u8,
+                                    perm.data,
+                                    src_size,
+                                );
+                            }
+                            // The copy has the frame's format, not its ARGB32 view
+                            perm.normalize();
+                            if let Some(m) = self.monitors.get_mut(monitor_idx) {
+                                m.screenshot = Some(Capture::oriented(perm, orientation));
+                            }
+                            log::info!("Permanent screenshot captured for monitor {}", monitor_idx);
+                        }
+                        Err(e) => {
+                            log::error!("Failed to allocate permanent screenshot buffer: {}", e);
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.pending_captures.is_empty() {
+            self.screencopy_complete = true;
+            log::info!("All screencopy complete - magnifier now active");
+        }
+
+        // Only render once from the Ready handler (to produce the initial
+        // transparent/inactive commit).  After that, rendering is driven
+        // exclusively by pointer events.  If we rendered on every Ready we
+        // would: render → commit buffer → onOutputCommit fires → copy() →
+        // Ready fires again → infinite loop.
+        if !self.initial_render_done {
+            if let Err(e) = self.render_monitor(monitor_idx, qh) {
+                log::error!("Failed to render monitor {}: {}", monitor_idx, e);
+            } else {
+                self.initial_render_done = true;
+                log::info!("Initial render completed");
+            }
+
+            // Also render other monitors so they commit their initial
+            // transparent state and receive proper input regions.
+            if self.screencopy_complete {
+                let monitor_count = self.monitors.len();
+                for m_idx in 0..monitor_count {
+                    if m_idx != monitor_idx {
+                        if let Err(e) = self.render_monitor(m_idx, qh) {
+                            log::error!(
+                                "Failed to render monitor {} after screencopy: {}",
+                                m_idx,
+                                e
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// A frame of `monitor_idx` failed.
+    fn frame_failed(&mut self, monitor_idx: usize, qh: &QueueHandle<Self>) {
+        if self.live.areas(monitor_idx).is_none() {
+            return;
+        }
+        if let Some(pos) = self.pending_captures.iter().position(|&m| m == monitor_idx) {
+            self.pending_captures.remove(pos);
+        }
+        self.live.cancel(monitor_idx);
+        // The overlay may have been hidden for the frame
+        if let Err(e) = self.render_monitor(monitor_idx, qh) {
+            log::error!("Failed to render monitor {}: {}", monitor_idx, e);
+        }
+    }
+
+    /// Time until the next live capture, if one will be taken.
+    fn live_wait(&self, now: Instant) -> Option<Duration> {
+        if !self.screencopy_complete || !self.pointer_position_confirmed {
+            return None;
+        }
+        self.active_monitor?;
+        if !self.continuous {
+            return None;
+        }
+        self.live.wait(now)
+    }
+
+    /// Re-capture the active monitor if a live capture is due, or if the
+    /// views have moved out of the region captured last.
+    fn refresh_live(&mut self, qh: &QueueHandle<Self>) {
+        if !self.screencopy_complete || !self.pointer_position_confirmed {
+            return;
+        }
+        let Some(monitor_idx) = self.active_monitor else {
+            return;
+        };
+        // Nothing new while a frame is in flight
+        let Some(wait) = self.live.wait(Instant::now()) else {
+            return;
+        };
+        let due = self.continuous && wait.is_zero();
+        if !due && !self.region_stale(monitor_idx) {
+            return;
+        }
+
+        let region = self.capture_region(monitor_idx);
+        let areas = match region {
+            Some(region) => Some(vec![region]),
+            None if self.region_capture => None,
+            None => self.live_areas(monitor_idx),
+        };
+        let Some(areas) = areas else {
+            return;
+        };
+
+        // Over its own source the overlay would be captured too; hide it
+        // for the frame. The docked pane can't be hidden, but the areas
+        // under it have already been left out.
+        let hidden =
+            self.renderer.mode != ViewMode::Docked && !self.overlay_clear(monitor_idx, &areas);
+        self.live.start(monitor_idx, areas, hidden, Instant::now());
+        if hidden {
+            if let Err(e) = self.render_monitor(monitor_idx, qh) {
+                log::error!("Failed to hide overlay for capture: {}", e);
+            }
+        }
+        self.request_capture(monitor_idx, region, qh);
+    }
+
+    /// With `--capture-region`, the part of `monitor_idx` to capture: what
+    /// its views read from and a margin, in capture pixels.
+    fn capture_region(&self, monitor_idx: usize) -> Option<Rect> {
+        if !self.region_capture {
+            return None;
+        }
+        let monitor = self.monitors.get(monitor_idx)?;
+        capture::region(&self.live_areas(monitor_idx)?, monitor.capture_size())
+    }
+
+    /// Whether the views on `monitor_idx` read from beyond the region
+    /// captured last.
+    fn region_stale(&self, monitor_idx: usize) -> bool {
+        if !self.region_capture {
+            return false;
+        }
+        let (Some(screen), Some(areas)) = (
+            self.monitors
+                .get(monitor_idx)
+                .and_then(|m| m.capture_buffer()),
+            self.live_areas(monitor_idx),
+        ) else {
+            return false;
+        };
+        !areas.iter().all(|area| screen.covers(area))
+    }
+
+    /// Capture areas the views on `monitor_idx` read from, with a margin.
+    fn live_areas(&self, monitor_idx: usize) -> Option<Vec<Rect>> {
+        let monitor = self.monitors.get(monitor_idx)?;
+        let space = monitor.get_logical_size();
+        let screen = monitor.capture_size();
+        let to_capture = screen / space;
+
+        let areas: Vec<Rect> = match self.renderer.mode {
+            ViewMode::Lens => {
+                let mut lenses = self.pins.on(monitor_idx);
+                lenses.push(self.live_lens(space));
+                lenses
+                    .iter()
+                    .map(|lens| self.renderer.lens_source(screen, space, lens))
+                    .collect()
+            }
+            ViewMode::Fullscreen => {
+                vec![self
+                    .renderer
+                    .viewport_source(screen, space, self.magnifier_position)]
+            }
+            ViewMode::Docked => {
+                let dock = self
+                    .layer_surfaces
+                    .iter()
+                    .find(|ls| ls.role == SurfaceRole::Dock && ls.monitor_idx == monitor_idx)?;
+                let pane = dock.get_logical_size();
+                vec![self
+                    .renderer
+                    .lens_source(screen, space, &self.dock_lens(pane))]
+            }
+        };
+
+        // Under the docked pane a frame would show the pane itself
+        let covered = self
+            .layer_surfaces
+            .iter()
+            .find(|ls| ls.role == SurfaceRole::Dock && ls.monitor_idx == monitor_idx)
+            .map(|dock| {
+                let area = self.dock_edge.area(space, dock.get_logical_size());
+                Rect::new(area.pos * to_capture, area.size * to_capture)
+            });
+        let areas: Vec<Rect> = areas
+            .iter()
+            .map(|area| area.inflate(live::SOURCE_MARGIN))
+            .filter(|area| covered.is_none_or(|c| area.intersect(&c).is_empty()))
+            .collect();
+
+        (!areas.is_empty()).then_some(areas)
+    }
+
+    /// Whether the buffer on screen on `monitor_idx` leaves `areas` clear.
+    fn overlay_clear(&mut self, monitor_idx: usize, areas: &[Rect]) -> bool {
+        let Some(screen) = self
+            .monitors
+            .get(monitor_idx)
+            .and_then(|m| m.capture_buffer())
+            .map(|b| b.pixel_size)
+        else {
+            return false;
+        };
+        let Some(overlay) = self
+            .layer_surfaces
+            .iter_mut()
+            .find(|ls| ls.is_overlay_for(monitor_idx))
+        else {
+            return true;
+        };
+        if !overlay.rendered {
+            return true;
+        }
+        match overlay.buffers[overlay.last_buffer].as_mut() {
+            Some(buffer) => live::is_clear(buffer, areas, screen),
+            None => true,
+        }
+    }
+
+    /// Take what a live frame of `monitor_idx` may refresh and redraw.
+    fn finish_live_capture(
+        &mut self,
+        monitor_idx: usize,
+        damage: Option<Vec<Rect>>,
+        qh: &QueueHandle<Self>,
+    ) {
+        let hidden = self.live.is_hiding(monitor_idx);
+        // A region frame replaces the screenshot, changed or not
+        let damage = damage.filter(|_| !self.region_capture);
+        let changed = match self.live.finish(monitor_idx, damage.as_deref()) {
+            Some(areas) if areas.is_empty() => {
+                log::debug!("Live frame of monitor {} brought no change", monitor_idx);
+                false
+            }
+            Some(areas) => {
+                if let Some(monitor) = self.monitors.get_mut(monitor_idx) {
+                    if let (Some(screenshot), Some(frame)) =
+                        (monitor.screenshot.as_mut(), monitor.screen_buffer.as_mut())
+                    {
+                        if self.region_capture {
+                            // The old screenshot takes the next frame
+                            std::mem::swap(screenshot, frame);
+                        } else {
+                            live::refresh(screenshot, frame, &areas);
+                        }
+                    }
+                }
+                true
+            }
+            None => {
+                log::debug!(
+                    "Discarded live frame of monitor {} covered by the overlay",
+                    monitor_idx
+                );
+                false
+            }
+        };
+
+        // The overlay comes back if it was hidden for the frame
+        if !changed && !hidden {
+            return;
+        }
+        if let Err(e) = self.render_monitor(monitor_idx, qh) {
+            log::error!("Failed to render live frame: {}", e);
+        }
+    }
+
+    /// Update the ruler or guides on the active monitor at the pointer.
+    fn place_measure(
+        &mut self,
+        place: impl FnOnce(&mut Measure, Vector2D),
+        qh: &QueueHandle<Self>,
+    ) {
+        let Some(monitor_idx) = self.active_monitor else {
+            return;
+        };
+        let Some(monitor) = self.monitors.get(monitor_idx) else {
+            return;
+        };
+
+        let measure = &mut self.renderer.measure;
+        measure.set_monitor(monitor_idx, monitor.fractional_scale);
+        place(measure, self.magnifier_position);
+
+        if let Err(e) = self.render_monitor(monitor_idx, qh) {
+            log::error!("Failed to render after measuring: {}", e);
+        }
+    }
+
+    /// Redraw the active monitor if the HUD has faded since it was drawn.
+    fn refresh_hud(&mut self, qh: &QueueHandle<Self>) {
+        if !self.renderer.hud.needs_redraw(Instant::now()) {
+            return;
+        }
+        let Some(monitor_idx) = self
+            .active_monitor
+            .filter(|&idx| self.is_active_monitor(idx))
+        else {
+            return;
+        };
+        if let Err(e) = self.render_monitor(monitor_idx, qh) {
+            log::error!("Failed to render HUD: {}", e);
+        }
+    }
+
+    /// Ease the zoom toward `magnification`.
+    fn set_magnification(&mut self, magnification: f64) {
+        self.zoom = 1.0 / magnification;
+        self.zoom_transition.set(self.zoom, Instant::now());
+        log::debug!(
+            "Zoom adjusted to {:.2}x (zoom factor: {:.2})",
+            magnification,
+            self.zoom
+        );
+    }
+
+    /// Scale the pin under the pointer by `factor`, or the live lens if
+    /// there is none; the live lens eases to its new size.
+    fn resize_lens(&mut self, factor: f64) {
+        if self
+            .pins
+            .resize_hovered(|size| clamp_lens_size(size * factor))
+        {
+            return;
+        }
+
+        let size = clamp_lens_size(self.magnifier_size.target() * factor);
+        self.magnifier_size.set(size, Instant::now());
+        log::info!("Lens size: {}x{}", size.x, size.y);
+    }
+
+    /// Leave a copy of the live lens on screen; the pointer then drives a
+    /// fresh one.
+    fn pin_lens(&mut self, qh: &QueueHandle<Self>) {
+        if self.renderer.mode != ViewMode::Lens {
+            log::info!("Lenses can only be pinned in lens mode");
+            return;
+        }
+        let Some(monitor_idx) = self.active_monitor else {
+            return;
+        };
+        let Some(monitor) = self.monitors.get(monitor_idx) else {
+            return;
+        };
+
+        // Pin where the transitions are heading rather than mid-way
+        let size = self.magnifier_size.target();
+        let bounds = Rect::new(Vector2D::default(), monitor.get_logical_size());
+        let center = lens_center(
+            self.magnifier_position,
+            size,
+            bounds,
+            self.renderer.lens_offset,
+        );
+        let lens = Lens {
+            source: self.magnifier_position,
+            center: center.floor(),
+            size,
+            zoom: self.zoom,
+        };
+        self.pins.pin(monitor_idx, lens);
+        self.pins.hover(monitor_idx, self.pointer_position);
+        log::info!(
+            "Pinned {:.1}x lens at ({:.0}, {:.0})",
+            1.0 / lens.zoom,
+            lens.source.x,
+            lens.source.y
+        );
+
+        if let Err(e) = self.render_monitor(monitor_idx, qh) {
+            log::error!("Failed to render after pinning: {}", e);
+        }
+    }
+
+    /// Close the pin under the pointer, or the newest pin.
+    fn close_pin(&mut self, qh: &QueueHandle<Self>) {
+        if self.pins.close().is_none() {
+            return;
+        }
+        if let Some(monitor_idx) = self.active_monitor {
+            self.pins.hover(monitor_idx, self.pointer_position);
+        }
+
+        // The newest pin may be on another monitor
+        for idx in 0..self.monitors.len() {
+            if let Err(e) = self.render_monitor(idx, qh) {
+                log::error!("Failed to render after closing a pin: {}", e);
+            }
+        }
+    }
+
+    /// Whether a zoom or size transition is still under way.
+    fn is_animating(&self, now: Instant) -> bool {
+        self.zoom_transition.is_running(now) || self.magnifier_size.is_running(now)
+    }
+
+    /// Whether a surface of this monitor is waiting for a frame callback.
+    fn frame_pending(&self, monitor_idx: usize) -> bool {
+        self.layer_surfaces
+            .iter()
+            .any(|ls| ls.monitor_idx == monitor_idx && ls.frame_callback.is_some())
+    }
+
+    /// A surface finished showing its last frame; step the lens and draw
+    /// the next one if it or a transition is still moving.
+    fn frame_done(&mut self, callback: &WlCallback, qh: &QueueHandle<Self>) {
+        let Some(layer_surface) = self
+            .layer_surfaces
+            .iter_mut()
+            .find(|ls| ls.frame_callback.as_ref() == Some(callback))
+        else {
+            return;
+        };
+        layer_surface.frame_callback = None;
+        let monitor_idx = layer_surface.monitor_idx;
 
-        // Only show magnifier on the active monitor AND if we have a confirmed pointer position
-        // We wait for the first Motion event to ensure accurate coordinates (Enter events
-        // during initialization can have wrong coordinates for offset monitors)
-        let is_active = self.screencopy_complete
-            && self.pointer_position_confirmed
-            && self.active_monitor == Some(monitor_idx);
+        let now = Instant::now();
+        let moving = self.follower.step(now);
+        self.magnifier_position = self.follower.position();
 
-        if is_active {
-            // Render the magnified view on the active monitor
-            self.renderer.render_surface(
-                output_buffer,
-                screen_buffer,
-                self.magnifier_position,
-                self.magnifier_size,
-                false, // force_inactive
-                false, // render_inactive
-            )?;
-            log::debug!(
-                "Rendered magnifier on monitor {} at position {:?}",
-                monitor_idx,
-                self.magnifier_position
-            );
-        } else {
-            // Render inactive (no magnifier) on other monitors
-            let ctx = output_buffer.create_cairo_context()?;
-            ctx.save()?;
-            ctx.set_source_rgba(0.0, 0.0, 0.0, 0.0);
-            ctx.set_operator(cairo::Operator::Source);
-            ctx.paint()?;
-            ctx.restore()?;
-            log::trace!("Cleared inactive monitor {}", monitor_idx);
+        if (moving || self.is_animating(now)) && self.is_active_monitor(monitor_idx) {
+            if let Err(e) = self.render_monitor(monitor_idx, qh) {
+                log::error!("Failed to render animation frame: {}", e);
+            }
         }
+    }
 
-        // Attach and commit the buffer
-        layer_surface.send_frame(qh);
-
-        Ok(())
+    /// Whether the magnifier should be drawn on this monitor right now
+    fn is_active_monitor(&self, monitor_idx: usize) -> bool {
+        self.screencopy_complete
+            && self.pointer_position_confirmed
+            && self.active_monitor == Some(monitor_idx)
     }
 }
 
@@ -547,6 +1798,21 @@ impl Dispatch<wl_registry::WlRegistry, ()> for AppState {
                     state.layer_shell = Some(layer_shell);
                     log::info!("Layer shell available");
                 }
+                "zwlr_data_control_manager_v1" => {
+                    let manager = registry.bind::<ZwlrDataControlManagerV1, _, _>(name, 1, qh, ());
+                    // Preferred over wl_data_device whichever is announced first
+                    state.clipboard = Some(Clipboard::data_control(manager));
+                    log::info!("Data control manager available");
+                }
+                "wl_data_device_manager"
+                    if !state
+                        .clipboard
+                        .as_ref()
+                        .is_some_and(|c| c.is_data_control()) =>
+                {
+                    let manager = registry.bind::<WlDataDeviceManager, _, _>(name, 3, qh, ());
+                    state.clipboard = Some(Clipboard::data_device(manager));
+                }
                 "zwlr_screencopy_manager_v1" => {
                     use wayland_protocols_wlr::screencopy::v1::client::zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1;
                     let screencopy_mgr =
@@ -554,6 +1820,21 @@ impl Dispatch<wl_registry::WlRegistry, ()> for AppState {
                     state.screencopy_manager = Some(screencopy_mgr);
                     log::info!("Screencopy manager available");
                 }
+                "ext_output_image_capture_source_manager_v1" => {
+                    let sources = registry.bind::<ExtOutputImageCaptureSourceManagerV1, _, _>(
+                        name,
+                        1,
+                        qh,
+                        (),
+                    );
+                    state.ext_capture_sources = Some(sources);
+                }
+                "ext_image_copy_capture_manager_v1" => {
+                    let manager =
+                        registry.bind::<ExtImageCopyCaptureManagerV1, _, _>(name, 1, qh, ());
+                    state.ext_capture_manager = Some(manager);
+                    log::info!("Image copy capture manager available");
+                }
                 _ => {}
             }
         }
@@ -624,6 +1905,10 @@ impl Dispatch<WlSeat, ()> for AppState {
                 if caps & keyboard_cap != 0 {
                     log::info!("Getting keyboard from seat...");
                     seat.get_keyboard(qh, ());
+                    match Keyboard::new() {
+                        Ok(keyboard) => state.keyboard = Some(keyboard),
+                        Err(e) => log::error!("Failed to set up keyboard: {}", e),
+                    }
                     log::info!("Keyboard object requested");
                 }
             }
@@ -656,9 +1941,13 @@ impl Dispatch<WlOutput, ()> for AppState {
                     y,
                     physical_width,
                     physical_height,
+                    transform,
                     ..
                 } => {
                     monitor.set_geometry(x, y, physical_width, physical_height);
+                    if let wayland_client::WEnum::Value(transform) = transform {
+                        monitor.set_transform(transform);
+                    }
                 }
                 Event::Mode {
                     width,
@@ -690,12 +1979,13 @@ impl Dispatch<WlKeyboard, ()> for AppState {
         event: <WlKeyboard as wayland_client::Proxy>::Event,
         _: &(),
         _: &Connection,
-        _qh: &QueueHandle<Self>,
+        qh: &QueueHandle<Self>,
     ) {
         use wayland_client::protocol::wl_keyboard::Event;
 
         match event {
             Event::Key {
+                serial,
                 key,
                 state: key_state,
                 ..
@@ -705,21 +1995,40 @@ impl Dispatch<WlKeyboard, ()> for AppState {
 
                 // Only handle key presses, not releases
                 if let WEnum::Value(KeyState::Pressed) = key_state {
-                    // XKB keycode is key + 8
-                    let keycode = key + 8;
-
-                    // Escape key is keycode 9 (XKB_KEY_Escape = 0xff1b, but as keycode it's 9)
-                    if keycode == 9 {
-                        log::info!("Escape key pressed, exiting...");
-                        state.running.store(false, Ordering::SeqCst);
+                    state.input_serial = serial;
+                    let keysym = state
+                        .keyboard
+                        .as_ref()
+                        .and_then(|kb| kb.handle_key(key, KeyState::Pressed.into()));
+
+                    match keysym {
+                        Some(keysym) => state.handle_keysym(keysym, qh),
+                        // No keymap yet: Escape is keycode 9 (evdev 1 + 8)
+                        None if key + 8 == 9 => {
+                            log::info!("Escape key pressed, exiting...");
+                            state.running.store(false, Ordering::SeqCst);
+                        }
+                        None => {}
                     }
                 }
             }
-            Event::Modifiers { .. } => {
-                // Handle modifier keys if needed
+            Event::Modifiers {
+                mods_depressed,
+                mods_latched,
+                mods_locked,
+                group,
+                ..
+            } => {
+                if let Some(keyboard) = state.keyboard.as_mut() {
+                    keyboard.handle_modifiers(mods_depressed, mods_latched, mods_locked, group);
+                }
             }
-            Event::Keymap { .. } => {
-                // Keymap setup
+            Event::Keymap { format, fd, size } => {
+                if let Some(keyboard) = state.keyboard.as_mut() {
+                    if let Err(e) = keyboard.handle_keymap(format.into(), fd, size) {
+                        log::error!("Failed to load keymap: {}", e);
+                    }
+                }
             }
             _ => {}
         }
@@ -758,7 +2067,7 @@ impl Dispatch<WlPointer, ()> for AppState {
                 let monitor_idx = state
                     .layer_surfaces
                     .iter()
-                    .find(|ls| ls.surface == surface)
+                    .find(|ls| ls.surface == surface && ls.role == SurfaceRole::Overlay)
                     .map(|ls| ls.monitor_idx);
 
                 if let Some(idx) = monitor_idx {
@@ -815,6 +2124,10 @@ impl Dispatch<WlPointer, ()> for AppState {
 
                     state.active_monitor = Some(idx);
                     state.magnifier_position = Vector2D::new(local_x, local_y);
+                    // Jump rather than glide in from the previous monitor
+                    state.follower.reset(state.magnifier_position);
+                    state.pointer_position = state.magnifier_position;
+                    state.pins.hover(idx, state.pointer_position);
 
                     // Note: We don't confirm position from Enter events (even after init)
                     // because they can still be inaccurate. We wait for Motion to confirm.
@@ -824,7 +2137,7 @@ impl Dispatch<WlPointer, ()> for AppState {
                     if state
                         .monitors
                         .get(idx)
-                        .and_then(|m| m.screen_buffer.as_ref())
+                        .and_then(|m| m.capture_buffer())
                         .is_some()
                     {
                         if let Err(e) = Self::render_monitor(state, idx, _qh) {
@@ -842,6 +2155,7 @@ impl Dispatch<WlPointer, ()> for AppState {
                 // Set active_monitor to None FIRST so render knows to clear it
                 let old_monitor = state.active_monitor;
                 state.active_monitor = None;
+                state.pins.unhover();
 
                 if let Some(monitor_idx) = old_monitor {
                     if let Err(e) = Self::render_monitor(state, monitor_idx, _qh) {
@@ -883,22 +2197,36 @@ impl Dispatch<WlPointer, ()> for AppState {
                         surface_y
                     };
 
-                    state.magnifier_position = Vector2D::new(local_x, local_y);
+                    state.pointer_position = Vector2D::new(local_x, local_y);
                     log::trace!("Pointer motion: ({:.0}, {:.0})", local_x, local_y);
                 } else {
                     // Fallback if active_monitor not set
-                    state.magnifier_position = Vector2D::new(surface_x.abs(), surface_y.abs());
+                    state.pointer_position = Vector2D::new(surface_x.abs(), surface_y.abs());
                     log::trace!("Pointer motion: ({:.0}, {:.0})", surface_x, surface_y);
                 }
+                state.follower.set_pointer(state.pointer_position);
+                state.magnifier_position = state.follower.position();
+
+                // A dragged or newly hovered pin redraws even while the
+                // smoothed lens waits for its frame
+                let pins_changed = match state.active_monitor {
+                    Some(_) if state.pins.is_dragging() => state.pins.drag(state.pointer_position),
+                    Some(monitor_idx) => state.pins.hover(monitor_idx, state.pointer_position),
+                    None => false,
+                };
 
-                // Render the magnifier at the new position
+                // Render the magnifier at the new position. A smoothed lens
+                // moves on frame callbacks, so only start the chain here.
                 if let Some(monitor_idx) = state.active_monitor {
                     // Only render if screencopy is ready
                     if state
                         .monitors
                         .get(monitor_idx)
-                        .and_then(|m| m.screen_buffer.as_ref())
+                        .and_then(|m| m.capture_buffer())
                         .is_some()
+                        && (state.follower.is_instant()
+                            || pins_changed
+                            || !state.frame_pending(monitor_idx))
                     {
                         if let Err(e) = Self::render_monitor(state, monitor_idx, _qh) {
                             log::error!("Failed to render on motion: {}", e);
@@ -906,25 +2234,48 @@ impl Dispatch<WlPointer, ()> for AppState {
                     }
                 }
             }
-            Event::Button { .. } => {
-                // Handle button clicks if needed
+            Event::Button {
+                serial,
+                button,
+                state: button_state,
+                ..
+            } => {
+                use wayland_client::protocol::wl_pointer::ButtonState;
+                use wayland_client::WEnum;
+
+                if let WEnum::Value(ButtonState::Released) = button_state {
+                    if button == BTN_LEFT {
+                        state.pins.release();
+                    }
+                }
+                if let WEnum::Value(ButtonState::Pressed) = button_state {
+                    state.input_serial = serial;
+                    if button == BTN_LEFT && state.pins.grab(state.pointer_position) {
+                        log::debug!("Dragging a pinned lens");
+                    } else if button == BTN_MIDDLE && state.pins.hovered().is_some() {
+                        state.close_pin(_qh);
+                    } else if button == BTN_MIDDLE {
+                        state.pin_lens(_qh);
+                    } else if button == BTN_LEFT && state.renderer.measure.enabled {
+                        state.place_measure(|measure, pointer| measure.anchor_at(pointer), _qh);
+                    } else if button == BTN_LEFT {
+                        state.pick_color(_qh);
+                    }
+                }
             }
             Event::Axis { axis, value, .. } => {
                 // Handle scroll wheel for zoom
                 use wayland_client::protocol::wl_pointer::Axis;
                 use wayland_client::WEnum;
                 if let WEnum::Value(Axis::VerticalScroll) = axis {
-                    let delta = -value / 120.0; // Normalize scroll delta
-                    state.zoom = (state.zoom + delta * state.zoom_speed).clamp(0.01, 1.0);
-                    state.renderer.set_zoom(state.zoom);
-                    log::debug!(
-                        "Zoom adjusted to {:.2}x (zoom factor: {:.2})",
-                        1.0 / state.zoom,
-                        state.zoom
-                    );
-
-                    // Exit when zoomed all the way out (no magnification)
-                    if state.zoom >= 1.0 {
+                    // Scrolling down zooms in, by whole or partial notches
+                    let notches = value / SCROLL_NOTCH;
+                    let magnification = state.renderer.zoom_steps.scroll(1.0 / state.zoom, notches);
+                    state.set_magnification(magnification);
+
+                    // Exit when zoomed all the way out (no magnification),
+                    // unless the lens is a spotlight that is still useful at 1x
+                    if state.zoom >= 1.0 && !state.renderer.spotlight.enabled {
                         log::debug!(
                             "Zoomed to 1.0 (no magnification), clearing overlay and exiting..."
                         );
@@ -970,13 +2321,29 @@ impl Dispatch<WlPointer, ()> for AppState {
 
 use wayland_client::protocol::wl_buffer::WlBuffer;
 use wayland_client::protocol::wl_callback::WlCallback;
+use wayland_client::protocol::wl_region::WlRegion;
 use wayland_client::protocol::wl_shm_pool::WlShmPool;
 
 impl Dispatch<WlCallback, ()> for AppState {
+    fn event(
+        state: &mut Self,
+        callback: &WlCallback,
+        event: <WlCallback as wayland_client::Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        if let wayland_client::protocol::wl_callback::Event::Done { .. } = event {
+            state.frame_done(callback, qh);
+        }
+    }
+}
+
+impl Dispatch<WlRegion, ()> for AppState {
     fn event(
         _: &mut Self,
-        _: &WlCallback,
-        _: <WlCallback as wayland_client::Proxy>::Event,
+        _: &WlRegion,
+        _: <WlRegion as wayland_client::Proxy>::Event,
         _: &(),
         _: &Connection,
         _: &QueueHandle<Self>,
@@ -1014,6 +2381,15 @@ impl Dispatch<WlBuffer, ()> for AppState {
 }
 
 // Layer shell protocol implementations
+use crate::protocols::ext_image_capture_source::{
+    ext_image_capture_source_v1::ExtImageCaptureSourceV1,
+    ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1,
+};
+use crate::protocols::ext_image_copy_capture::{
+    ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1,
+    ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1,
+    ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1,
+};
 use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_shell_v1::ZwlrLayerShellV1;
 use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1::ZwlrLayerSurfaceV1;
 use wayland_protocols_wlr::screencopy::v1::client::zwlr_screencopy_frame_v1::ZwlrScreencopyFrameV1;
@@ -1042,8 +2418,17 @@ impl Dispatch<ZwlrLayerSurfaceV1, ()> for AppState {
     ) {
         use wayland_protocols_wlr::layer_shell::v1::client::zwlr_layer_surface_v1::Event;
         match event {
-            Event::Configure { serial, .. } => {
-                log::debug!("Layer surface configure: serial={}", serial);
+            Event::Configure {
+                serial,
+                width,
+                height,
+            } => {
+                log::debug!(
+                    "Layer surface configure: serial={} size={}x{}",
+                    serial,
+                    width,
+                    height
+                );
 
                 // Find the matching layer surface and acknowledge
                 for ls in &mut state.layer_surfaces {
@@ -1052,6 +2437,14 @@ impl Dispatch<ZwlrLayerSurfaceV1, ()> for AppState {
                             ls_handle.ack_configure(serial);
                             ls.configured = true;
                             ls.ack_serial = serial;
+
+                            // Docked panes are sized by the compositor along
+                            // their anchored edge, and overlays follow the
+                            // output's rotation; keep buffers in sync
+                            if width > 0 && height > 0 {
+                                let logical = Vector2D::new(width as f64, height as f64);
+                                ls.resize(logical * ls.fractional_scale_value);
+                            }
                             log::debug!("Acknowledged configure for surface {}", ls.monitor_idx);
                             break;
                         }
@@ -1078,24 +2471,18 @@ impl Dispatch<ZwlrScreencopyManagerV1, ()> for AppState {
     }
 }
 
-impl Dispatch<ZwlrScreencopyFrameV1, ()> for AppState {
+impl Dispatch<ZwlrScreencopyFrameV1, wlr::FrameData> for AppState {
     fn event(
         state: &mut Self,
         frame: &ZwlrScreencopyFrameV1,
         event: <ZwlrScreencopyFrameV1 as wayland_client::Proxy>::Event,
-        _: &(),
+        data: &wlr::FrameData,
         _: &Connection,
         qh: &QueueHandle<Self>,
     ) {
         use wayland_protocols_wlr::screencopy::v1::client::zwlr_screencopy_frame_v1::Event;
 
-        // Find which monitor this frame belongs to
-        let monitor_idx = state
-            .pending_frames
-            .iter()
-            .find(|(f, _)| f == frame)
-            .map(|(_, idx)| *idx);
-
+        let idx = data.monitor;
         match event {
             Event::Buffer {
                 format,
@@ -1111,132 +2498,172 @@ impl Dispatch<ZwlrScreencopyFrameV1, ()> for AppState {
                     stride
                 );
 
-                if let Some(idx) = monitor_idx {
-                    if let Some(shm) = &state.shm {
-                        // Create a buffer to receive the screenshot
-                        let pixel_size = Vector2D::new(width as f64, height as f64);
-
-                        // Convert format enum to u32
-                        let format_u32: u32 = format.into();
-
-                        match crate::pool_buffer::PoolBuffer::new(
-                            pixel_size, format_u32, stride, shm, qh,
-                        ) {
-                            Ok(buffer) => {
-                                log::debug!("Created screencopy buffer for monitor {}", idx);
-
-                                // Copy the screen to our buffer
-                                frame.copy(&buffer.buffer);
-
-                                // Store buffer info in monitor
-                                if let Some(monitor) = state.monitors.get_mut(idx) {
-                                    monitor.screen_buffer = Some(buffer);
-                                    monitor.screen_buffer_format = format_u32;
-                                }
-                            }
-                            Err(e) => {
-                                log::error!("Failed to create screencopy buffer: {}", e);
-                            }
-                        }
+                let spec = BufferSpec {
+                    format: format.into(),
+                    size: Vector2D::new(width as f64, height as f64),
+                    stride,
+                };
+                match state.frame_buffer(idx, data.origin, spec, qh) {
+                    Some(buffer) => frame.copy(&buffer),
+                    None => {
+                        frame.destroy();
+                        state.frame_failed(idx, qh);
                     }
                 }
             }
             Event::Ready { .. } => {
-                log::debug!("Screencopy frame ready for monitor {:?}", monitor_idx);
+                log::debug!("Screencopy frame ready for monitor {}", idx);
+
+                // Hyprland's SHM path never sets m_copied=true so onOutputCommit
+                // keeps calling copy() (and sending Ready) on every frame commit
+                // until the frame is destroyed.
+                frame.destroy();
+                let orientation = state
+                    .monitors
+                    .get(idx)
+                    .map(|m| m.orientation())
+                    .unwrap_or_default();
+                state.frame_ready(idx, orientation, None, qh);
+            }
+            Event::Flags { flags } => {
+                if let Some(monitor) = state.monitors.get_mut(idx) {
+                    monitor.screen_flags = match flags {
+                        wayland_client::WEnum::Value(flags) => flags.bits(),
+                        wayland_client::WEnum::Unknown(bits) => bits,
+                    };
+                }
+            }
+            Event::Failed => {
+                log::warn!("Screencopy frame failed for monitor {}", idx);
+                frame.destroy();
+                state.frame_failed(idx, qh);
+            }
+            _ => {}
+        }
+    }
+}
 
-                if let Some(idx) = monitor_idx {
-                    log::info!("Monitor {} screen capture complete", idx);
-
-                    // Releasing the frame object sends wl_destroy to the compositor,
-                    // but Hyprland's SHM path never sets m_copied=true so onOutputCommit
-                    // keeps calling copy() (and sending Ready) on every frame commit.
-                    // We guard against that loop with initial_render_done below.
-                    state.pending_frames.retain(|(f, _)| f != frame);
-
-                    // On the FIRST ready for each monitor, make a permanent deep copy of
-                    // the screenshot before any render commits a buffer that would cause
-                    // Hyprland to overwrite screen_buffer with an overlay-contaminated frame.
-                    if state.monitors.get(idx).map_or(false, |m| m.screenshot.is_none()) {
-                        // Extract pointer/size info without holding a borrow into monitors.
-                        let copy_info = state.monitors.get(idx).and_then(|m| {
-                            m.screen_buffer.as_ref().map(|src| {
-                                (src.data, src.size, src.pixel_size, src.stride, src.format)
-                            })
-                        });
-
-                        if let Some((src_ptr, src_size, pixel_size, stride, format)) = copy_info {
-                            if let Some(shm) = &state.shm {
-                                match crate::pool_buffer::PoolBuffer::new(
-                                    pixel_size, format, stride, shm, qh,
-                                ) {
-                                    Ok(perm) => {
-                                        // Safety: src_ptr and perm.data are disjoint SHM
-                                        // mappings; screen_buffer is not dropped here.
-                                        unsafe {
-                                            std::ptr::copy_nonoverlapping(
-                                                src_ptr as *const u8,
-                                                perm.data,
-                                                src_size,
-                                            );
-                                        }
-                                        if let Some(m) = state.monitors.get_mut(idx) {
-                                            m.screenshot = Some(perm);
-                                        }
-                                        log::info!(
-                                            "Permanent screenshot captured for monitor {}",
-                                            idx
-                                        );
-                                    }
-                                    Err(e) => {
-                                        log::error!(
-                                            "Failed to allocate permanent screenshot buffer: {}",
-                                            e
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                    }
+impl Dispatch<ExtOutputImageCaptureSourceManagerV1, ()> for AppState {
+    fn event(
+        _: &mut Self,
+        _: &ExtOutputImageCaptureSourceManagerV1,
+        _: <ExtOutputImageCaptureSourceManagerV1 as wayland_client::Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
 
-                    if state.pending_frames.is_empty() {
-                        state.screencopy_complete = true;
-                        log::info!("All screencopy complete - magnifier now active");
-                    }
+impl Dispatch<ExtImageCaptureSourceV1, ()> for AppState {
+    fn event(
+        _: &mut Self,
+        _: &ExtImageCaptureSourceV1,
+        _: <ExtImageCaptureSourceV1 as wayland_client::Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
 
-                    // Only render once from the Ready handler (to produce the initial
-                    // transparent/inactive commit).  After that, rendering is driven
-                    // exclusively by pointer events.  If we rendered on every Ready we
-                    // would: render → commit buffer → onOutputCommit fires → copy() →
-                    // Ready fires again → infinite loop.
-                    if !state.initial_render_done {
-                        if let Err(e) = Self::render_monitor(state, idx, qh) {
-                            log::error!("Failed to render monitor {}: {}", idx, e);
-                        } else {
-                            state.initial_render_done = true;
-                            log::info!("Initial render completed");
-                        }
+impl Dispatch<ExtImageCopyCaptureManagerV1, ()> for AppState {
+    fn event(
+        _: &mut Self,
+        _: &ExtImageCopyCaptureManagerV1,
+        _: <ExtImageCopyCaptureManagerV1 as wayland_client::Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
 
-                        // Also render other monitors so they commit their initial
-                        // transparent state and receive proper input regions.
-                        if state.screencopy_complete {
-                            let monitor_count = state.monitors.len();
-                            for m_idx in 0..monitor_count {
-                                if m_idx != idx {
-                                    if let Err(e) = Self::render_monitor(state, m_idx, qh) {
-                                        log::error!(
-                                            "Failed to render monitor {} after screencopy: {}",
-                                            m_idx,
-                                            e
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                    }
+impl Dispatch<ExtImageCopyCaptureSessionV1, ext::SessionData> for AppState {
+    fn event(
+        state: &mut Self,
+        _: &ExtImageCopyCaptureSessionV1,
+        event: <ExtImageCopyCaptureSessionV1 as wayland_client::Proxy>::Event,
+        data: &ext::SessionData,
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        use crate::protocols::ext_image_copy_capture::ext_image_copy_capture_session_v1::Event;
+
+        match event {
+            Event::BufferSize { width, height } => data.buffer_size(width, height),
+            Event::ShmFormat { format } => data.shm_format(match format {
+                wayland_client::WEnum::Value(format) => format.into(),
+                wayland_client::WEnum::Unknown(format) => format,
+            }),
+            Event::Done => {
+                if let Some(spec) = data.done() {
+                    state.attach_frame(data.monitor, spec, qh);
                 }
             }
-            Event::Failed => {
-                log::warn!("Screencopy frame failed for monitor {:?}", monitor_idx);
+            Event::Stopped => {
+                log::warn!("Capture session of monitor {} stopped", data.monitor);
+                if data.stop() {
+                    state.frame_failed(data.monitor, qh);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ExtImageCopyCaptureFrameV1, ext::FrameData> for AppState {
+    fn event(
+        state: &mut Self,
+        frame: &ExtImageCopyCaptureFrameV1,
+        event: <ExtImageCopyCaptureFrameV1 as wayland_client::Proxy>::Event,
+        data: &ext::FrameData,
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        use crate::protocols::ext_image_copy_capture::ext_image_copy_capture_frame_v1::Event;
+
+        match event {
+            Event::Damage {
+                x,
+                y,
+                width,
+                height,
+            } => data.damage(x, y, width, height),
+            Event::Transform {
+                transform: wayland_client::WEnum::Value(transform),
+            } => data.set_transform(transform),
+            Event::Ready => {
+                log::debug!("Capture frame ready for monitor {}", data.monitor);
+                frame.destroy();
+                let output = state.monitors.get(data.monitor).map(|m| m.transform);
+                let transform = data.transform().or(output);
+                let orientation = Orientation::new(
+                    transform.unwrap_or(wayland_client::protocol::wl_output::Transform::Normal),
+                    false,
+                );
+                // Damage comes in buffer coordinates; live areas are upright
+                let buffer = state
+                    .monitors
+                    .get(data.monitor)
+                    .and_then(|m| m.screen_buffer.as_ref())
+                    .map(|capture| capture.buffer.pixel_size);
+                let damage = buffer.map(|buffer| {
+                    data.take_damage()
+                        .into_iter()
+                        .map(|rect| orientation.upright_rect(buffer, rect))
+                        .collect()
+                });
+                state.frame_ready(data.monitor, orientation, damage, qh);
+            }
+            Event::Failed { reason } => {
+                log::warn!(
+                    "Capture frame failed for monitor {}: {:?}",
+                    data.monitor,
+                    reason
+                );
+                frame.destroy();
+                state.frame_failed(data.monitor, qh);
             }
             _ => {}
         }
@@ -1256,3 +2683,159 @@ impl Dispatch<WlSurface, ()> for AppState {
     ) {
     }
 }
+
+// Clipboard protocol implementations
+use wayland_client::protocol::wl_data_device::{self, WlDataDevice};
+use wayland_client::protocol::wl_data_device_manager::WlDataDeviceManager;
+use wayland_client::protocol::wl_data_offer::WlDataOffer;
+use wayland_client::protocol::wl_data_source::{self, WlDataSource};
+use wayland_client::{event_created_child, Proxy};
+use wayland_protocols_wlr::data_control::v1::client::{
+    zwlr_data_control_device_v1::{self, ZwlrDataControlDeviceV1},
+    zwlr_data_control_manager_v1::ZwlrDataControlManagerV1,
+    zwlr_data_control_offer_v1::ZwlrDataControlOfferV1,
+    zwlr_data_control_source_v1::{self, ZwlrDataControlSourceV1},
+};
+
+/// Linux evdev codes of the left and middle mouse buttons
+const BTN_LEFT: u32 = 0x110;
+const BTN_MIDDLE: u32 = 0x112;
+
+/// Factor applied to the lens size by `[` and `]`.
+const LENS_RESIZE_STEP: f64 = 1.1;
+
+/// Smallest and largest lens side reachable with `[` and `]`, in logical pixels.
+const LENS_MIN_SIZE: f64 = 50.0;
+const LENS_MAX_SIZE: f64 = 4000.0;
+
+/// Keep a lens size within the reachable range, in whole pixels.
+fn clamp_lens_size(size: Vector2D) -> Vector2D {
+    Vector2D::new(
+        size.x.clamp(LENS_MIN_SIZE, LENS_MAX_SIZE),
+        size.y.clamp(LENS_MIN_SIZE, LENS_MAX_SIZE),
+    )
+    .round()
+}
+
+impl Dispatch<ZwlrDataControlManagerV1, ()> for AppState {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrDataControlManagerV1,
+        _: <ZwlrDataControlManagerV1 as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrDataControlDeviceV1, ()> for AppState {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrDataControlDeviceV1,
+        _: zwlr_data_control_device_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        // Other clients' selections are of no interest
+    }
+
+    event_created_child!(AppState, ZwlrDataControlDeviceV1, [
+        zwlr_data_control_device_v1::EVT_DATA_OFFER_OPCODE => (ZwlrDataControlOfferV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrDataControlOfferV1, ()> for AppState {
+    fn event(
+        _: &mut Self,
+        _: &ZwlrDataControlOfferV1,
+        _: <ZwlrDataControlOfferV1 as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrDataControlSourceV1, ()> for AppState {
+    fn event(
+        state: &mut Self,
+        source: &ZwlrDataControlSourceV1,
+        event: zwlr_data_control_source_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(clipboard) = state.clipboard.as_mut() else {
+            return;
+        };
+        match event {
+            zwlr_data_control_source_v1::Event::Send { mime_type, fd } => {
+                clipboard.send(&mime_type, fd);
+            }
+            zwlr_data_control_source_v1::Event::Cancelled => clipboard.cancelled(source.id()),
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<WlDataDeviceManager, ()> for AppState {
+    fn event(
+        _: &mut Self,
+        _: &WlDataDeviceManager,
+        _: <WlDataDeviceManager as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlDataDevice, ()> for AppState {
+    fn event(
+        _: &mut Self,
+        _: &WlDataDevice,
+        _: wl_data_device::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+
+    event_created_child!(AppState, WlDataDevice, [
+        wl_data_device::EVT_DATA_OFFER_OPCODE => (WlDataOffer, ()),
+    ]);
+}
+
+impl Dispatch<WlDataOffer, ()> for AppState {
+    fn event(
+        _: &mut Self,
+        _: &WlDataOffer,
+        _: <WlDataOffer as Proxy>::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<WlDataSource, ()> for AppState {
+    fn event(
+        state: &mut Self,
+        source: &WlDataSource,
+        event: wl_data_source::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(clipboard) = state.clipboard.as_mut() else {
+            return;
+        };
+        match event {
+            wl_data_source::Event::Send { mime_type, fd } => clipboard.send(&mime_type, fd),
+            wl_data_source::Event::Cancelled => clipboard.cancelled(source.id()),
+            _ => {}
+        }
+    }
+}
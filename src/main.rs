@@ -1,12 +1,27 @@
+mod animation;
+mod capture;
+mod clipboard;
 mod config;
+mod cursor;
+mod effects;
+mod hud;
 mod input;
 mod layer_surface;
+mod lens;
+mod live;
 mod magnifier;
+mod measure;
 mod monitor;
+mod overlay;
+mod picker;
+mod pixel_format;
 mod pool_buffer;
 mod protocols;
 mod renderer;
+mod smoothing;
+mod snapshot;
 mod utils;
+mod zoom;
 
 use clap::Parser;
 use config::{Cli, Config};
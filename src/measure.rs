@@ -0,0 +1,232 @@
+//! Ruler and alignment guides.
+//!
+//! Measure mode anchors a point and reports the offset from it to the
+//! pointer. Guides are horizontal or vertical lines that stay put while the
+//! pointer moves. Everything is stored in the logical coordinates of the
+//! monitor it was placed on and only drawn on that monitor.
+
+use crate::utils::Vector2D;
+
+/// A full-length alignment line at a logical coordinate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Guide {
+    Horizontal(f64),
+    Vertical(f64),
+}
+
+impl Guide {
+    /// The guide moved through the axis-aligned transform `f`.
+    pub fn map(self, f: impl Fn(Vector2D) -> Vector2D) -> Self {
+        match self {
+            Guide::Horizontal(y) => Guide::Horizontal(f(Vector2D::new(0.0, y)).y),
+            Guide::Vertical(x) => Guide::Vertical(f(Vector2D::new(x, 0.0)).x),
+        }
+    }
+}
+
+/// Offset between two points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    pub dx: f64,
+    pub dy: f64,
+}
+
+impl Measurement {
+    /// The offset from `from` to `to`.
+    pub fn between(from: Vector2D, to: Vector2D) -> Self {
+        Self {
+            dx: to.x - from.x,
+            dy: to.y - from.y,
+        }
+    }
+
+    /// The same offset in units `scale` times smaller.
+    pub fn scaled(self, scale: f64) -> Self {
+        Self {
+            dx: self.dx * scale,
+            dy: self.dy * scale,
+        }
+    }
+
+    /// Euclidean length.
+    pub fn distance(&self) -> f64 {
+        self.dx.hypot(self.dy)
+    }
+
+    /// Direction in degrees, counter-clockwise from the positive x axis as
+    /// seen on screen (so pointing up-right is 45°).
+    pub fn angle(&self) -> f64 {
+        // 0.0 - dy rather than -dy: a negative zero would turn 180° into -180°
+        (0.0 - self.dy).atan2(self.dx).to_degrees()
+    }
+}
+
+/// Measure mode and guide state.
+#[derive(Debug, Clone)]
+pub struct Measure {
+    /// Whether clicks anchor the ruler instead of picking colours
+    pub enabled: bool,
+    anchor: Option<(usize, Vector2D)>,
+    guides: Vec<(usize, Guide)>,
+    /// Monitor being drawn and its fractional scale
+    monitor: usize,
+    scale: f64,
+}
+
+impl Default for Measure {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            anchor: None,
+            guides: Vec::new(),
+            monitor: 0,
+            scale: 1.0,
+        }
+    }
+}
+
+impl Measure {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+
+    /// Switch measure mode on or off. Turning it off drops the anchor.
+    pub fn toggle(&mut self) {
+        self.enabled = !self.enabled;
+        if !self.enabled {
+            self.anchor = None;
+        }
+    }
+
+    /// Select the monitor that placements and queries refer to.
+    pub fn set_monitor(&mut self, monitor: usize, scale: f64) {
+        self.monitor = monitor;
+        self.scale = scale;
+    }
+
+    /// Anchor the ruler at `point`.
+    pub fn anchor_at(&mut self, point: Vector2D) {
+        self.anchor = Some((self.monitor, point));
+    }
+
+    /// The anchor, if it was placed on the current monitor.
+    pub fn anchor(&self) -> Option<Vector2D> {
+        self.anchor
+            .filter(|(monitor, _)| *monitor == self.monitor)
+            .map(|(_, point)| point)
+    }
+
+    pub fn add_guide(&mut self, guide: Guide) {
+        self.guides.push((self.monitor, guide));
+    }
+
+    /// Remove the guides on the current monitor.
+    pub fn clear_guides(&mut self) {
+        let monitor = self.monitor;
+        self.guides.retain(|(m, _)| *m != monitor);
+    }
+
+    /// Guides on the current monitor.
+    pub fn guides(&self) -> impl Iterator<Item = Guide> + '_ {
+        self.guides
+            .iter()
+            .filter(|(m, _)| *m == self.monitor)
+            .map(|(_, guide)| *guide)
+    }
+
+    /// Readout for the ruler from the anchor to `pointer`, if anchored.
+    ///
+    /// Physical pixels are the monitor's native pixels, i.e. logical
+    /// pixels times the fractional scale.
+    pub fn label(&self, pointer: Vector2D) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+        let logical = Measurement::between(self.anchor()?, pointer);
+        let physical = logical.scaled(self.scale);
+
+        Some(format!(
+            "dx {:.0}  dy {:.0}  {:.1} px  {:.1}°\nlogical  dx {}  dy {}  {}",
+            physical.dx,
+            physical.dy,
+            physical.distance(),
+            logical.angle(),
+            trim(logical.dx),
+            trim(logical.dy),
+            trim(logical.distance()),
+        ))
+    }
+}
+
+/// Format with up to two decimals, dropping trailing zeros.
+fn trim(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measurement() {
+        let m = Measurement::between(Vector2D::new(10.0, 20.0), Vector2D::new(40.0, -20.0));
+        assert_eq!((m.dx, m.dy), (30.0, -40.0));
+        assert_eq!(m.distance(), 50.0);
+
+        // Straight up on screen is +90°, straight left is 180°
+        let up = Measurement::between(Vector2D::new(0.0, 0.0), Vector2D::new(0.0, -5.0));
+        assert_eq!(up.angle(), 90.0);
+        let left = Measurement::between(Vector2D::new(0.0, 0.0), Vector2D::new(-5.0, 0.0));
+        assert_eq!(left.angle(), 180.0);
+
+        assert_eq!(m.scaled(1.5).distance(), 75.0);
+    }
+
+    #[test]
+    fn test_label_reports_both_units() {
+        let mut measure = Measure::default();
+        measure.set_monitor(0, 1.5);
+        assert_eq!(measure.label(Vector2D::new(1.0, 1.0)), None);
+
+        measure.toggle();
+        measure.anchor_at(Vector2D::new(100.0, 100.0));
+        let label = measure.label(Vector2D::new(180.0, 160.0)).unwrap();
+        assert_eq!(
+            label,
+            "dx 120  dy 90  150.0 px  -36.9°\nlogical  dx 80  dy 60  100"
+        );
+
+        // Turning measure mode off drops the anchor
+        measure.toggle();
+        measure.toggle();
+        assert_eq!(measure.anchor(), None);
+    }
+
+    #[test]
+    fn test_placements_are_per_monitor() {
+        let mut measure = Measure::default();
+        measure.set_monitor(0, 1.0);
+        measure.anchor_at(Vector2D::new(5.0, 5.0));
+        measure.add_guide(Guide::Vertical(10.0));
+
+        measure.set_monitor(1, 2.0);
+        measure.add_guide(Guide::Horizontal(20.0));
+        assert_eq!(measure.anchor(), None);
+        assert_eq!(
+            measure.guides().collect::<Vec<_>>(),
+            [Guide::Horizontal(20.0)]
+        );
+
+        measure.clear_guides();
+        measure.set_monitor(0, 1.0);
+        assert_eq!(measure.anchor(), Some(Vector2D::new(5.0, 5.0)));
+        assert_eq!(
+            measure.guides().collect::<Vec<_>>(),
+            [Guide::Vertical(10.0)]
+        );
+    }
+}
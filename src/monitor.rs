@@ -1,4 +1,4 @@
-use crate::pool_buffer::PoolBuffer;
+use crate::capture::{Capture, Orientation};
 use crate::utils::Vector2D;
 use wayland_client::protocol::wl_output::{Transform, WlOutput};
 
@@ -9,14 +9,12 @@ pub struct Monitor {
     pub size: Vector2D,
     pub scale: i32,
     pub fractional_scale: f64, // Actual fractional scale (e.g., 1.5)
-    #[allow(dead_code)]
     pub transform: Transform,
     pub ready: bool,
 
     // Screen capture buffer
-    pub screen_buffer: Option<PoolBuffer>,
-    pub screen_buffer_format: u32,
-    #[allow(dead_code)]
+    pub screen_buffer: Option<Capture>,
+    // Screencopy flags of the last frame (Y_INVERT)
     pub screen_flags: u32,
 
     // Permanent deep copy of the initial clean screenshot.
@@ -24,8 +22,9 @@ pub struct Monitor {
     // overwrites screen_buffer on every frame commit.  We capture a snapshot
     // here the moment the first Ready event fires (before any renders can
     // commit buffers that would loop back into the capture) and use it for
-    // all magnifier rendering, breaking the cascade.
-    pub screenshot: Option<PoolBuffer>,
+    // all magnifier rendering, breaking the cascade.  Region captures are
+    // moved here instead; their frame is destroyed before anything renders.
+    pub screenshot: Option<Capture>,
 
     // Layer surface index
     pub layer_surface_idx: Option<usize>,
@@ -43,19 +42,53 @@ impl Monitor {
             transform: Transform::Normal,
             ready: false,
             screen_buffer: None,
-            screen_buffer_format: 0,
             screen_flags: 0,
             screenshot: None,
             layer_surface_idx: None,
         }
     }
 
+    /// Buffer to magnify from: the permanent snapshot if taken, else the raw capture.
+    ///
+    /// Hyprland's SHM screencopy calls copy() on every frame commit (m_copied is
+    /// never set for SHM frames), which overwrites screen_buffer with the
+    /// composited output that already includes our overlay.  The permanent
+    /// snapshot was captured once before any overlay was rendered.
+    pub fn capture_buffer_mut(&mut self) -> Option<&mut Capture> {
+        match self.screenshot.as_mut() {
+            Some(screenshot) => Some(screenshot),
+            None => self.screen_buffer.as_mut(),
+        }
+    }
+
+    /// Shared-reference counterpart of `capture_buffer_mut`.
+    pub fn capture_buffer(&self) -> Option<&Capture> {
+        self.screenshot.as_ref().or(self.screen_buffer.as_ref())
+    }
+
+    /// Size of the whole output in capture pixels; the upright mode size
+    /// until something has been captured.
+    pub fn capture_size(&self) -> Vector2D {
+        self.capture_buffer()
+            .map(|capture| capture.pixel_size)
+            .unwrap_or(self.upright_size())
+    }
+
+    /// How a screencopy frame of the output lies, given its last flags.
+    pub fn orientation(&self) -> Orientation {
+        use wayland_protocols_wlr::screencopy::v1::client::zwlr_screencopy_frame_v1::Flags;
+        let y_invert = self.screen_flags & Flags::YInvert.bits() != 0;
+        Orientation::new(self.transform, y_invert)
+    }
+
+    /// Mode size as the user sees it, which a rotated output swaps.
+    pub fn upright_size(&self) -> Vector2D {
+        Orientation::new(self.transform, false).upright_size(self.size)
+    }
+
     /// Get the logical size of the monitor based on physical size and fractional scale
     pub fn get_logical_size(&self) -> Vector2D {
-        Vector2D::new(
-            self.size.x / self.fractional_scale,
-            self.size.y / self.fractional_scale,
-        )
+        self.upright_size() / self.fractional_scale
     }
 
     pub fn set_geometry(&mut self, x: i32, y: i32, width: i32, height: i32) {
@@ -69,6 +102,11 @@ impl Monitor {
         );
     }
 
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+        log::debug!("Monitor {} transform: {:?}", self.wayland_name, transform);
+    }
+
     pub fn set_mode(&mut self, width: i32, height: i32, refresh: i32) {
         self.size = Vector2D::new(width as f64, height as f64);
         log::debug!(
@@ -166,6 +204,22 @@ mod tests {
         assert_eq!(logical_y, 1080.0);
     }
 
+    #[test]
+    fn test_rotated_output_scales_evenly() {
+        // A 1920x1080 panel turned to portrait at 1.5x
+        let mode = Vector2D::new(1920.0, 1080.0);
+        let orientation = Orientation::new(Transform::_90, false);
+        let upright = orientation.upright_size(mode);
+        assert_eq!(upright, Vector2D::new(1080.0, 1920.0));
+
+        // The overlay is as portrait as the capture, so pointer positions
+        // map to capture pixels by the same factor on both axes
+        let overlay = upright / 1.5;
+        assert_eq!(overlay, Vector2D::new(720.0, 1280.0));
+        let to_capture = upright / overlay;
+        assert_eq!(to_capture.x, to_capture.y);
+    }
+
     #[test]
     fn test_fractional_scale_calculations() {
         // Test various fractional scales
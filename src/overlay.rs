@@ -0,0 +1,369 @@
+//! Annotations drawn on top of the magnified view.
+//!
+//! These are vector overlays (grid lines, text readouts) drawn with Cairo
+//! after the pixel effects have run, so they stay crisp and uncoloured
+//! regardless of the active filters.
+
+use crate::measure::Guide;
+use crate::picker::PickHistory;
+use crate::utils::{Rect, Vector2D};
+use anyhow::Result;
+use pango::FontDescription;
+
+/// Smallest on-screen size of a source pixel, in output pixels, at which
+/// the pixel grid is drawn. Below this the lines would swamp the content.
+pub const GRID_MIN_CELL: f64 = 8.0;
+
+/// Font used for all text readouts.
+const LABEL_FONT: &str = "monospace 9";
+
+/// Padding between a label's text and its background, in pixels.
+const LABEL_PADDING: f64 = 4.0;
+
+/// Draw lines between source pixels over `area`.
+///
+/// `origin` and `scale` describe the mapping from output to capture
+/// coordinates (`origin + p * scale`), so lines fall where the capture
+/// coordinate is a whole number. Nothing is drawn if the pixels are
+/// smaller than `GRID_MIN_CELL`.
+pub fn draw_pixel_grid(
+    ctx: &cairo::Context,
+    origin: Vector2D,
+    scale: Vector2D,
+    area: Rect,
+) -> Result<()> {
+    let cell = Vector2D::new(1.0 / scale.x, 1.0 / scale.y);
+    if cell.x < GRID_MIN_CELL || cell.y < GRID_MIN_CELL {
+        return Ok(());
+    }
+
+    let source_start = origin + area.pos * scale;
+    let source_end = origin + area.end() * scale;
+
+    ctx.save()?;
+    ctx.new_path();
+    for column in source_start.x.ceil() as i64..=source_end.x.floor() as i64 {
+        // Centre the 1px line on a pixel so it isn't smeared over two
+        let x = ((column as f64 - origin.x) / scale.x).round() + 0.5;
+        ctx.move_to(x, area.pos.y);
+        ctx.line_to(x, area.end().y);
+    }
+    for row in source_start.y.ceil() as i64..=source_end.y.floor() as i64 {
+        let y = ((row as f64 - origin.y) / scale.y).round() + 0.5;
+        ctx.move_to(area.pos.x, y);
+        ctx.line_to(area.end().x, y);
+    }
+    // Mid-grey reads on both light and dark content
+    ctx.set_source_rgba(0.5, 0.5, 0.5, 0.6);
+    ctx.set_line_width(1.0);
+    ctx.stroke()?;
+    ctx.restore()?;
+
+    Ok(())
+}
+
+/// Draw the readout for the source pixel at capture coordinates `pixel`.
+///
+/// `argb` is the premultiplied pixel value, or None if `pixel` lies outside
+/// the capture. The readout is placed at `anchor` (top-left) unless it
+/// would leave `bounds`, in which case it is placed above `avoid` instead.
+pub fn draw_inspector(
+    ctx: &cairo::Context,
+    pixel: Vector2D,
+    argb: Option<u32>,
+    anchor: Vector2D,
+    avoid: Rect,
+    bounds: Rect,
+) -> Result<()> {
+    let text = match argb {
+        Some(argb) => {
+            let [r, g, b, a] = unpremultiply(argb);
+            format!(
+                "x {} y {}\nrgba({}, {}, {}, {})\n#{:02x}{:02x}{:02x}{:02x}",
+                pixel.x, pixel.y, r, g, b, a, r, g, b, a
+            )
+        }
+        None => format!("x {} y {}\noutside capture", pixel.x, pixel.y),
+    };
+
+    let layout = label_layout(ctx, &text);
+    let swatch = if argb.is_some() {
+        label_size(&layout).y
+    } else {
+        0.0
+    };
+    let size = label_size(&layout) + Vector2D::new(swatch, 0.0);
+
+    let mut pos = anchor;
+    if pos.y + size.y > bounds.end().y {
+        pos.y = avoid.pos.y - size.y - (anchor.y - avoid.end().y);
+    }
+    let pos = clamp_into(pos, size, bounds);
+
+    draw_label_background(ctx, pos, size)?;
+    if let Some(argb) = argb {
+        // Swatch of the pixel's colour, on the label's own dark background
+        let [r, g, b, a] = unpremultiply(argb);
+        ctx.rectangle(
+            pos.x + LABEL_PADDING,
+            pos.y + LABEL_PADDING,
+            swatch - LABEL_PADDING,
+            size.y - 2.0 * LABEL_PADDING,
+        );
+        ctx.set_source_rgba(
+            r as f64 / 255.0,
+            g as f64 / 255.0,
+            b as f64 / 255.0,
+            a as f64 / 255.0,
+        );
+        ctx.fill()?;
+    }
+    draw_label_text(ctx, &layout, pos + Vector2D::new(swatch, 0.0))?;
+
+    Ok(())
+}
+
+/// Size of a colour history swatch, in pixels.
+const SWATCH_SIZE: f64 = 16.0;
+
+/// Gap between swatches and between the strip and the view, in pixels.
+const SWATCH_GAP: f64 = 4.0;
+
+/// Draw recent picks as a column of swatches beside `view`, newest on top.
+///
+/// The column goes to the right of `view`, or to its left if there is no
+/// room within `bounds`.
+pub fn draw_swatches(
+    ctx: &cairo::Context,
+    history: &PickHistory,
+    view: Rect,
+    bounds: Rect,
+) -> Result<()> {
+    if history.is_empty() {
+        return Ok(());
+    }
+
+    let mut x = view.end().x + SWATCH_GAP;
+    if x + SWATCH_SIZE > bounds.end().x {
+        x = view.pos.x - SWATCH_GAP - SWATCH_SIZE;
+    }
+
+    ctx.save()?;
+    for (i, color) in history.iter().enumerate() {
+        let pos = clamp_into(
+            Vector2D::new(x, view.pos.y + i as f64 * (SWATCH_SIZE + SWATCH_GAP)),
+            Vector2D::new(SWATCH_SIZE, SWATCH_SIZE),
+            bounds,
+        );
+        ctx.rectangle(
+            pos.x + 0.5,
+            pos.y + 0.5,
+            SWATCH_SIZE - 1.0,
+            SWATCH_SIZE - 1.0,
+        );
+        ctx.set_source_rgb(
+            color.r as f64 / 255.0,
+            color.g as f64 / 255.0,
+            color.b as f64 / 255.0,
+        );
+        ctx.fill_preserve()?;
+        ctx.set_source_rgba(150.0 / 255.0, 150.0 / 255.0, 150.0 / 255.0, 1.0);
+        ctx.set_line_width(1.0);
+        ctx.stroke()?;
+    }
+    ctx.restore()?;
+
+    Ok(())
+}
+
+/// Outline the output cell that shows a single source pixel.
+pub fn draw_pixel_marker(ctx: &cairo::Context, cell: Rect) -> Result<()> {
+    ctx.save()?;
+    ctx.rectangle(
+        cell.pos.x.round() + 0.5,
+        cell.pos.y.round() + 0.5,
+        cell.size.x.round() - 1.0,
+        cell.size.y.round() - 1.0,
+    );
+    // Two strokes so the marker shows on any colour
+    ctx.set_line_width(3.0);
+    ctx.set_source_rgba(0.0, 0.0, 0.0, 0.8);
+    ctx.stroke_preserve()?;
+    ctx.set_line_width(1.0);
+    ctx.set_source_rgba(1.0, 1.0, 1.0, 0.9);
+    ctx.stroke()?;
+    ctx.restore()?;
+
+    Ok(())
+}
+
+/// Draw an alignment guide across `area`.
+pub fn draw_guide(ctx: &cairo::Context, guide: Guide, area: Rect) -> Result<()> {
+    ctx.save()?;
+    ctx.new_path();
+    match guide {
+        Guide::Horizontal(y) => {
+            let y = y.round() + 0.5;
+            ctx.move_to(area.pos.x, y);
+            ctx.line_to(area.end().x, y);
+        }
+        Guide::Vertical(x) => {
+            let x = x.round() + 0.5;
+            ctx.move_to(x, area.pos.y);
+            ctx.line_to(x, area.end().y);
+        }
+    }
+    ctx.set_line_width(1.0);
+    ctx.set_source_rgba(0.0, 0.8, 1.0, 0.9);
+    ctx.stroke()?;
+    ctx.restore()?;
+
+    Ok(())
+}
+
+/// Draw the ruler line from `from` to `to`, with a dot at the anchor.
+pub fn draw_ruler(ctx: &cairo::Context, from: Vector2D, to: Vector2D) -> Result<()> {
+    ctx.save()?;
+    ctx.new_path();
+    ctx.move_to(from.x, from.y);
+    ctx.line_to(to.x, to.y);
+    // Dark casing under a bright line, like the pixel marker
+    ctx.set_line_width(3.0);
+    ctx.set_source_rgba(0.0, 0.0, 0.0, 0.6);
+    ctx.stroke_preserve()?;
+    ctx.set_line_width(1.0);
+    ctx.set_source_rgba(1.0, 0.85, 0.0, 1.0);
+    ctx.stroke()?;
+
+    ctx.arc(from.x, from.y, 3.0, 0.0, 2.0 * std::f64::consts::PI);
+    ctx.fill()?;
+    ctx.restore()?;
+
+    Ok(())
+}
+
+/// Draw a plain text label above `view`, or below it if there is no room.
+///
+/// `align` places the label horizontally, from 0.0 (flush with the left
+/// edge of `view`) to 1.0 (flush with the right edge).
+pub fn draw_label_above(
+    ctx: &cairo::Context,
+    text: &str,
+    view: Rect,
+    align: f64,
+    bounds: Rect,
+) -> Result<()> {
+    let layout = label_layout(ctx, text);
+    let size = label_size(&layout);
+
+    let x = view.pos.x + (view.size.x - size.x) * align;
+    let mut pos = Vector2D::new(x, view.pos.y - size.y - 6.0);
+    if pos.y < bounds.pos.y {
+        pos.y = view.end().y + 6.0;
+    }
+    let pos = clamp_into(pos, size, bounds);
+
+    draw_label_background(ctx, pos, size)?;
+    draw_label_text(ctx, &layout, pos)
+}
+
+/// Lay out `text` in the label font.
+pub fn label_layout(ctx: &cairo::Context, text: &str) -> pango::Layout {
+    let layout = pangocairo::create_layout(ctx);
+    layout.set_font_description(Some(&FontDescription::from_string(LABEL_FONT)));
+    layout.set_text(text);
+    layout
+}
+
+/// Size of a label including its padding.
+pub fn label_size(layout: &pango::Layout) -> Vector2D {
+    let (width, height) = layout.pixel_size();
+    Vector2D::new(width as f64, height as f64) + Vector2D::new(LABEL_PADDING, LABEL_PADDING) * 2.0
+}
+
+/// Draw the translucent rounded box behind a label.
+pub fn draw_label_background(ctx: &cairo::Context, pos: Vector2D, size: Vector2D) -> Result<()> {
+    let r = LABEL_PADDING;
+    let (x, y) = (pos.x, pos.y);
+    let pi = std::f64::consts::PI;
+
+    ctx.save()?;
+    ctx.new_path();
+    ctx.arc(x + size.x - r, y + r, r, -pi / 2.0, 0.0);
+    ctx.arc(x + size.x - r, y + size.y - r, r, 0.0, pi / 2.0);
+    ctx.arc(x + r, y + size.y - r, r, pi / 2.0, pi);
+    ctx.arc(x + r, y + r, r, pi, 3.0 * pi / 2.0);
+    ctx.close_path();
+    ctx.set_source_rgba(0.1, 0.1, 0.1, 0.85);
+    ctx.fill()?;
+    ctx.restore()?;
+
+    Ok(())
+}
+
+/// Draw a label's text with its box's top-left corner at `pos`.
+pub fn draw_label_text(ctx: &cairo::Context, layout: &pango::Layout, pos: Vector2D) -> Result<()> {
+    ctx.save()?;
+    ctx.move_to(pos.x + LABEL_PADDING, pos.y + LABEL_PADDING);
+    ctx.set_source_rgba(1.0, 1.0, 1.0, 1.0);
+    pangocairo::show_layout(ctx, layout);
+    ctx.restore()?;
+
+    Ok(())
+}
+
+/// Move a box of `size` at `pos` so it lies inside `bounds` where possible.
+pub fn clamp_into(pos: Vector2D, size: Vector2D, bounds: Rect) -> Vector2D {
+    let max = bounds.end() - size;
+    Vector2D::new(
+        pos.x.min(max.x).max(bounds.pos.x),
+        pos.y.min(max.y).max(bounds.pos.y),
+    )
+}
+
+/// Convert a premultiplied ARGB32 pixel to straight `[r, g, b, a]`.
+pub fn unpremultiply(argb: u32) -> [u8; 4] {
+    let a = argb >> 24;
+    let channel = |c: u32| {
+        (c * 255 + a / 2)
+            .checked_div(a)
+            .map_or(0, |v| v.min(255) as u8)
+    };
+    [
+        channel((argb >> 16) & 0xff),
+        channel((argb >> 8) & 0xff),
+        channel(argb & 0xff),
+        a as u8,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_into() {
+        let bounds = Rect::new(Vector2D::new(0.0, 0.0), Vector2D::new(100.0, 100.0));
+        let size = Vector2D::new(30.0, 20.0);
+
+        assert_eq!(
+            clamp_into(Vector2D::new(10.0, 10.0), size, bounds),
+            Vector2D::new(10.0, 10.0)
+        );
+        assert_eq!(
+            clamp_into(Vector2D::new(90.0, 95.0), size, bounds),
+            Vector2D::new(70.0, 80.0)
+        );
+        assert_eq!(
+            clamp_into(Vector2D::new(-5.0, -5.0), size, bounds),
+            Vector2D::new(0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_unpremultiply() {
+        assert_eq!(unpremultiply(0xff12_3456), [0x12, 0x34, 0x56, 0xff]);
+        assert_eq!(unpremultiply(0x8080_0000), [0xff, 0, 0, 0x80]);
+        assert_eq!(unpremultiply(0), [0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,227 @@
+//! Colour picking from the screen capture.
+//!
+//! Samples the captured pixels under the lens centre, formats the colour in
+//! the usual CSS notations and keeps a short history of recent picks.
+
+use crate::capture::Capture;
+use crate::config::PickFormat;
+use crate::effects::color::rgb_to_hsl;
+use crate::overlay;
+use std::collections::VecDeque;
+
+/// Number of recent picks kept for the swatch strip.
+pub const HISTORY_LEN: usize = 8;
+
+/// An opaque sRGB colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+
+    /// `#rrggbb`
+    pub fn to_hex(self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// `rgb(r, g, b)`
+    pub fn to_rgb(self) -> String {
+        format!("rgb({}, {}, {})", self.r, self.g, self.b)
+    }
+
+    /// `hsl(h, s%, l%)`
+    pub fn to_hsl(self) -> String {
+        let (h, s, l) = rgb_to_hsl([self.r, self.g, self.b]);
+        format!(
+            "hsl({}, {}%, {}%)",
+            h.round() as i32 % 360,
+            (s * 100.0).round(),
+            (l * 100.0).round()
+        )
+    }
+
+    /// `oklch(L% C h)`
+    pub fn to_oklch(self) -> String {
+        let (l, c, h) = self.oklch();
+        format!("oklch({:.1}% {:.4} {:.2})", l * 100.0, c, h)
+    }
+
+    /// The colour in `format`.
+    pub fn format(self, format: PickFormat) -> String {
+        match format {
+            PickFormat::Hex => self.to_hex(),
+            PickFormat::Rgb => self.to_rgb(),
+            PickFormat::Hsl => self.to_hsl(),
+            PickFormat::Oklch => self.to_oklch(),
+        }
+    }
+
+    /// Perceptual lightness (0.0..=1.0), chroma and hue (degrees) in OKLCH.
+    pub fn oklch(self) -> (f64, f64, f64) {
+        let linear = |c: u8| {
+            let c = c as f64 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        let (r, g, b) = (linear(self.r), linear(self.g), linear(self.b));
+
+        let l = (0.412_221_470_8 * r + 0.536_332_536_3 * g + 0.051_445_992_9 * b).cbrt();
+        let m = (0.211_903_498_2 * r + 0.680_699_545_1 * g + 0.107_396_956_6 * b).cbrt();
+        let s = (0.088_302_461_9 * r + 0.281_718_837_6 * g + 0.629_978_700_5 * b).cbrt();
+
+        let lightness = 0.210_454_255_3 * l + 0.793_617_785 * m - 0.004_072_046_8 * s;
+        let a = 1.977_998_495_1 * l - 2.428_592_205 * m + 0.450_593_709_9 * s;
+        let b = 0.025_904_037_1 * l + 0.782_771_766_2 * m - 0.808_675_766 * s;
+
+        let chroma = a.hypot(b);
+        // Hue is meaningless for greys; report 0 rather than rounding noise
+        let hue = if chroma < 1e-4 {
+            0.0
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        };
+        (lightness, chroma, hue)
+    }
+}
+
+/// Average the `size` x `size` block of `capture` centred on `(x, y)`.
+///
+/// Even sizes are rounded up to the next odd size so the block stays
+/// centred. Returns None if the whole block lies outside the capture.
+pub fn sample(capture: &Capture, x: i32, y: i32, size: u32) -> Option<Color> {
+    sample_with(|x, y| capture.pixel_at(x, y), x, y, size)
+}
+
+fn sample_with(
+    pixel_at: impl Fn(i32, i32) -> Option<u32>,
+    x: i32,
+    y: i32,
+    size: u32,
+) -> Option<Color> {
+    let reach = (size / 2) as i32;
+    let mut sum = [0u32; 3];
+    let mut count = 0;
+
+    for dy in -reach..=reach {
+        for dx in -reach..=reach {
+            let Some(argb) = pixel_at(x + dx, y + dy) else {
+                continue;
+            };
+            let [r, g, b, _] = overlay::unpremultiply(argb);
+            for (total, value) in sum.iter_mut().zip([r, g, b]) {
+                *total += value as u32;
+            }
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        return None;
+    }
+    let avg = |c: u32| ((c + count / 2) / count) as u8;
+    Some(Color::new(avg(sum[0]), avg(sum[1]), avg(sum[2])))
+}
+
+/// Recently picked colours, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct PickHistory {
+    colors: VecDeque<Color>,
+}
+
+impl PickHistory {
+    /// Record a pick. Picking the same colour twice in a row keeps one entry.
+    pub fn push(&mut self, color: Color) {
+        if self.colors.front() == Some(&color) {
+            return;
+        }
+        self.colors.push_front(color);
+        self.colors.truncate(HISTORY_LEN);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Color> {
+        self.colors.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats() {
+        let orange = Color::new(255, 128, 0);
+        assert_eq!(orange.to_hex(), "#ff8000");
+        assert_eq!(orange.to_rgb(), "rgb(255, 128, 0)");
+        assert_eq!(orange.to_hsl(), "hsl(30, 100%, 50%)");
+
+        // Reference values from the OKLCH definition
+        assert_eq!(
+            Color::new(255, 0, 0).to_oklch(),
+            "oklch(62.8% 0.2577 29.23)"
+        );
+        assert_eq!(
+            Color::new(255, 255, 255).to_oklch(),
+            "oklch(100.0% 0.0000 0.00)"
+        );
+        assert_eq!(
+            Color::new(0, 0, 0).format(PickFormat::Oklch),
+            "oklch(0.0% 0.0000 0.00)"
+        );
+    }
+
+    #[test]
+    fn test_sample_averages_block() {
+        // Left half black, right half white
+        let pixel_at = |x: i32, y: i32| {
+            if !(0..4).contains(&x) || !(0..4).contains(&y) {
+                None
+            } else if x < 2 {
+                Some(0xff00_0000)
+            } else {
+                Some(0xffff_ffff)
+            }
+        };
+
+        assert_eq!(sample_with(pixel_at, 0, 0, 1), Some(Color::new(0, 0, 0)));
+        // 3x3 around (2, 1): one black column, two white
+        assert_eq!(
+            sample_with(pixel_at, 2, 1, 3),
+            Some(Color::new(170, 170, 170))
+        );
+        // Even sizes round up to the next odd size
+        assert_eq!(
+            sample_with(pixel_at, 2, 1, 2),
+            sample_with(pixel_at, 2, 1, 3)
+        );
+        assert_eq!(sample_with(pixel_at, 10, 10, 3), None);
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = PickHistory::default();
+        assert!(history.is_empty());
+
+        history.push(Color::new(1, 2, 3));
+        history.push(Color::new(1, 2, 3));
+        assert_eq!(history.iter().count(), 1);
+
+        for i in 0..20 {
+            history.push(Color::new(i, i, i));
+        }
+        assert_eq!(history.iter().count(), HISTORY_LEN);
+        assert_eq!(history.iter().next(), Some(&Color::new(19, 19, 19)));
+    }
+}
@@ -0,0 +1,230 @@
+//! Pixel formats of captured frames.
+//!
+//! Compositors capture into whichever `wl_shm` format they prefer, while
+//! Cairo and everything that reads pixels here work on premultiplied
+//! ARGB32 in native byte order. `ARGB8888` already is that layout and
+//! `XRGB8888` only needs its alpha forced opaque, so both are used in
+//! place; every other format is converted into a separate ARGB32 copy.
+//!
+//! `wl_shm` formats are little-endian, as DRM fourcc formats are: the
+//! channel named last sits in the lowest bits.
+
+use wayland_client::protocol::wl_shm::Format;
+
+/// A `wl_shm` format we can read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    Argb8888,
+    Xrgb8888,
+    Abgr8888,
+    Xbgr8888,
+    Rgb888,
+    Bgr888,
+    Rgb565,
+    Argb2101010,
+    Xrgb2101010,
+    Abgr2101010,
+    Xbgr2101010,
+}
+
+impl PixelFormat {
+    /// The format with `wl_shm` code `code`, if we can read it.
+    pub fn from_shm(code: u32) -> Option<Self> {
+        Some(match Format::try_from(code).ok()? {
+            Format::Argb8888 => Self::Argb8888,
+            Format::Xrgb8888 => Self::Xrgb8888,
+            Format::Abgr8888 => Self::Abgr8888,
+            Format::Xbgr8888 => Self::Xbgr8888,
+            Format::Rgb888 => Self::Rgb888,
+            Format::Bgr888 => Self::Bgr888,
+            Format::Rgb565 => Self::Rgb565,
+            Format::Argb2101010 => Self::Argb2101010,
+            Format::Xrgb2101010 => Self::Xrgb2101010,
+            Format::Abgr2101010 => Self::Abgr2101010,
+            Format::Xbgr2101010 => Self::Xbgr2101010,
+            _ => return None,
+        })
+    }
+
+    pub fn bytes_per_pixel(self) -> usize {
+        match self {
+            Self::Rgb565 => 2,
+            Self::Rgb888 | Self::Bgr888 => 3,
+            _ => 4,
+        }
+    }
+
+    /// Whether the pixels can be used as ARGB32 where they are, once
+    /// [`PixelFormat::fix_in_place`] has run.
+    pub fn is_in_place(self) -> bool {
+        cfg!(target_endian = "little") && matches!(self, Self::Argb8888 | Self::Xrgb8888)
+    }
+
+    /// Premultiplied ARGB32 value of the pixel stored in `px`.
+    pub fn to_argb(self, px: &[u8]) -> u32 {
+        match self {
+            Self::Rgb565 => {
+                let v = u16::from_le_bytes([px[0], px[1]]) as u32;
+                let (r, g, b) = (v >> 11, (v >> 5) & 0x3f, v & 0x1f);
+                argb(
+                    0xff,
+                    (r << 3) | (r >> 2),
+                    (g << 2) | (g >> 4),
+                    (b << 3) | (b >> 2),
+                )
+            }
+            Self::Rgb888 => argb(0xff, px[2] as u32, px[1] as u32, px[0] as u32),
+            Self::Bgr888 => argb(0xff, px[0] as u32, px[1] as u32, px[2] as u32),
+            _ => {
+                let v = u32::from_le_bytes([px[0], px[1], px[2], px[3]]);
+                match self {
+                    Self::Argb8888 => v,
+                    Self::Xrgb8888 => v | 0xff00_0000,
+                    Self::Abgr8888 | Self::Xbgr8888 => {
+                        let a = if self == Self::Abgr8888 {
+                            v >> 24
+                        } else {
+                            0xff
+                        };
+                        argb(a, v & 0xff, (v >> 8) & 0xff, (v >> 16) & 0xff)
+                    }
+                    _ => {
+                        // 2:10:10:10, keeping the top 8 bits of each channel
+                        let a = match self {
+                            Self::Argb2101010 | Self::Abgr2101010 => (v >> 30) * 0x55,
+                            _ => 0xff,
+                        };
+                        let (hi, g, lo) = ((v >> 22) & 0xff, (v >> 12) & 0xff, (v >> 2) & 0xff);
+                        match self {
+                            Self::Argb2101010 | Self::Xrgb2101010 => argb(a, hi, g, lo),
+                            _ => argb(a, lo, g, hi),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Make in-place pixels valid ARGB32: forces alpha opaque for
+    /// `XRGB8888`, whose padding byte may hold anything.
+    pub fn fix_in_place(self, data: &mut [u8], stride: usize, width: usize, height: usize) {
+        if self != Self::Xrgb8888 {
+            return;
+        }
+        for row in data.chunks_mut(stride).take(height) {
+            for px in row[..width * 4].chunks_exact_mut(4) {
+                px[3] = 0xff;
+            }
+        }
+    }
+
+    /// Convert `width` x `height` pixels of `src` into ARGB32 in `dst`.
+    pub fn convert(
+        self,
+        src: &[u8],
+        src_stride: usize,
+        dst: &mut [u8],
+        dst_stride: usize,
+        width: usize,
+        height: usize,
+    ) {
+        let bpp = self.bytes_per_pixel();
+        for (src_row, dst_row) in src
+            .chunks(src_stride)
+            .zip(dst.chunks_mut(dst_stride))
+            .take(height)
+        {
+            for (px, out) in src_row[..width * bpp]
+                .chunks_exact(bpp)
+                .zip(dst_row[..width * 4].chunks_exact_mut(4))
+            {
+                out.copy_from_slice(&self.to_argb(px).to_ne_bytes());
+            }
+        }
+    }
+}
+
+fn argb(a: u32, r: u32, g: u32, b: u32) -> u32 {
+    (a << 24) | (r << 16) | (g << 8) | b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_argb() {
+        let px = 0x8040_2010u32.to_le_bytes();
+        assert_eq!(PixelFormat::Argb8888.to_argb(&px), 0x8040_2010);
+        assert_eq!(PixelFormat::Xrgb8888.to_argb(&px), 0xff40_2010);
+        assert_eq!(PixelFormat::Abgr8888.to_argb(&px), 0x8010_2040);
+        assert_eq!(PixelFormat::Xbgr8888.to_argb(&px), 0xff10_2040);
+
+        // Bytes in memory: blue first for RGB888, red first for BGR888
+        assert_eq!(
+            PixelFormat::Rgb888.to_argb(&[0x10, 0x20, 0x30]),
+            0xff30_2010
+        );
+        assert_eq!(
+            PixelFormat::Bgr888.to_argb(&[0x10, 0x20, 0x30]),
+            0xff10_2030
+        );
+
+        // Full red, half green, no blue
+        let rgb565 = ((0x1f << 11) | (0x20 << 5)) as u16;
+        assert_eq!(
+            PixelFormat::Rgb565.to_argb(&rgb565.to_le_bytes()),
+            0xffff_8200
+        );
+
+        // Opaque, red at full, green at half, blue off
+        let v: u32 = (3 << 30) | (0x3ff << 20) | (0x200 << 10);
+        assert_eq!(
+            PixelFormat::Argb2101010.to_argb(&v.to_le_bytes()),
+            0xffff_8000
+        );
+        assert_eq!(
+            PixelFormat::Abgr2101010.to_argb(&v.to_le_bytes()),
+            0xff00_80ff
+        );
+        let v: u32 = 1 << 30;
+        assert_eq!(
+            PixelFormat::Argb2101010.to_argb(&v.to_le_bytes()),
+            0x5500_0000
+        );
+        assert_eq!(
+            PixelFormat::Xbgr2101010.to_argb(&v.to_le_bytes()),
+            0xff00_0000
+        );
+    }
+
+    #[test]
+    fn test_from_shm() {
+        assert_eq!(
+            PixelFormat::from_shm(Format::Xbgr8888.into()),
+            Some(PixelFormat::Xbgr8888)
+        );
+        assert_eq!(PixelFormat::from_shm(Format::Yuyv.into()), None);
+        assert_eq!(PixelFormat::Rgb565.bytes_per_pixel(), 2);
+    }
+
+    #[test]
+    fn test_convert_rows() {
+        // 2x2 BGR888 with a padded stride
+        let src = [
+            0xff, 0, 0, 0, 0xff, 0, 0xee, //
+            0, 0, 0xff, 1, 2, 3, 0xee,
+        ];
+        let mut dst = [0u8; 2 * 2 * 4];
+        PixelFormat::Bgr888.convert(&src, 7, &mut dst, 8, 2, 2);
+        let pixels: Vec<u32> = dst
+            .chunks_exact(4)
+            .map(|px| u32::from_ne_bytes([px[0], px[1], px[2], px[3]]))
+            .collect();
+        assert_eq!(pixels, [0xffff_0000, 0xff00_ff00, 0xff00_00ff, 0xff01_0203]);
+
+        let mut xrgb = [0x10, 0x20, 0x30, 0x00, 0x40, 0x50, 0x60, 0x7f];
+        PixelFormat::Xrgb8888.fix_in_place(&mut xrgb, 8, 2, 1);
+        assert_eq!(xrgb, [0x10, 0x20, 0x30, 0xff, 0x40, 0x50, 0x60, 0xff]);
+    }
+}
@@ -3,7 +3,12 @@
 //! This module handles the creation and management of shared memory buffers
 //! used for zero-copy rendering with the Wayland compositor. Buffers are
 //! memory-mapped files in XDG_RUNTIME_DIR.
+//!
+//! Captured frames arrive in whatever format the compositor chose; once
+//! [`PoolBuffer::normalize`] has run, pixel access and the Cairo surface
+//! see premultiplied ARGB32 regardless.
 
+use crate::pixel_format::PixelFormat;
 use crate::utils::Vector2D;
 use anyhow::{Context, Result};
 use cairo::{Context as CairoContext, Format, ImageSurface};
@@ -27,13 +32,12 @@ pub struct PoolBuffer {
     pub size: usize,
     pub stride: u32,
     pub pixel_size: Vector2D,
-    #[allow(dead_code)]
+    /// wl_shm format of the mapped data
     pub format: u32,
     pub busy: bool,
 
-    // Padded buffer for 24-bit formats
-    #[allow(dead_code)]
-    pub padded_data: Option<Vec<u8>>,
+    // ARGB32 copy of the pixels for formats Cairo can't use in place
+    padded_data: Option<Vec<u8>>,
 
     // Cairo surface (created on-demand)
     cairo_surface: Option<ImageSurface>,
@@ -102,7 +106,8 @@ impl PoolBuffer {
 
         // Convert u32 format to wayland Format enum
         use wayland_client::protocol::wl_shm::Format as WlFormat;
-        let wl_format = unsafe { std::mem::transmute::<u32, WlFormat>(format) };
+        let wl_format = WlFormat::try_from(format)
+            .map_err(|_| anyhow::anyhow!("Unknown shm format {:#x}", format))?;
 
         let buffer = pool.create_buffer(
             0,
@@ -126,7 +131,9 @@ impl PoolBuffer {
             pixel_size,
             format,
             busy: false,
-            padded_data: None,
+            padded_data: PixelFormat::from_shm(format)
+                .filter(|f| !f.is_in_place())
+                .map(|_| vec![0; pixel_size.x as usize * pixel_size.y as usize * 4]),
             cairo_surface: None,
             file_path: path,
         })
@@ -145,11 +152,11 @@ impl PoolBuffer {
         if self.cairo_surface.is_none() {
             let surface = unsafe {
                 ImageSurface::create_for_data_unsafe(
-                    self.data,
+                    self.pixels_ptr(),
                     Format::ARgb32,
                     self.pixel_size.x as i32,
                     self.pixel_size.y as i32,
-                    self.stride as i32,
+                    self.pixel_stride() as i32,
                 )?
             };
             self.cairo_surface = Some(surface);
@@ -171,6 +178,83 @@ impl PoolBuffer {
         Ok(CairoContext::new(surface)?)
     }
 
+    /// ARGB32 pixel bytes for CPU-side processing, rows
+    /// [`PoolBuffer::pixel_stride`] apart.
+    ///
+    /// Flushes pending Cairo drawing first. Call [`PoolBuffer::mark_dirty`]
+    /// after modifying the data so Cairo picks up the changes.
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        if let Some(surface) = &self.cairo_surface {
+            surface.flush();
+        }
+        match self.padded_data.as_mut() {
+            Some(padded) => padded,
+            // Safety: data points to a live mapping of `size` bytes owned by self
+            None => unsafe { std::slice::from_raw_parts_mut(self.data, self.size) },
+        }
+    }
+
+    /// Bytes per row of [`PoolBuffer::pixels_mut`].
+    pub fn pixel_stride(&self) -> usize {
+        match self.padded_data {
+            Some(_) => self.pixel_size.x as usize * 4,
+            None => self.stride as usize,
+        }
+    }
+
+    fn pixels_ptr(&mut self) -> *mut u8 {
+        match self.padded_data.as_mut() {
+            Some(padded) => padded.as_mut_ptr(),
+            None => self.data,
+        }
+    }
+
+    /// Make a frame the compositor just copied in readable as ARGB32.
+    pub fn normalize(&mut self) {
+        let Some(format) = PixelFormat::from_shm(self.format) else {
+            return;
+        };
+        let (width, height) = (self.pixel_size.x as usize, self.pixel_size.y as usize);
+        let stride = self.stride as usize;
+        if let Some(surface) = &self.cairo_surface {
+            surface.flush();
+        }
+
+        // Safety: data points to a live mapping of `size` bytes owned by self
+        let data = unsafe { std::slice::from_raw_parts_mut(self.data, self.size) };
+        match self.padded_data.as_mut() {
+            Some(padded) => format.convert(data, stride, padded, width * 4, width, height),
+            None => format.fix_in_place(data, stride, width, height),
+        }
+        self.mark_dirty();
+    }
+
+    /// Tell Cairo the pixel data was modified behind its back.
+    pub fn mark_dirty(&self) {
+        if let Some(surface) = &self.cairo_surface {
+            surface.mark_dirty();
+        }
+    }
+
+    /// Read the ARGB32 pixel at `(x, y)`, or None if it lies outside the buffer.
+    pub fn pixel_at(&self, x: i32, y: i32) -> Option<u32> {
+        if x < 0 || y < 0 || x as f64 >= self.pixel_size.x || y as f64 >= self.pixel_size.y {
+            return None;
+        }
+        let offset = y as usize * self.pixel_stride() + x as usize * 4;
+        let bytes = match &self.padded_data {
+            Some(padded) => padded.get(offset..offset + 4)?,
+            None => {
+                if offset + 4 > self.size {
+                    return None;
+                }
+                // Safety: offset was bounds-checked against the mapping size above
+                unsafe { std::slice::from_raw_parts(self.data.add(offset), 4) }
+            }
+        };
+        Some(u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
     /// Mark buffer as busy
     #[allow(dead_code)]
     pub fn set_busy(&mut self, busy: bool) {
@@ -1,7 +1,57 @@
-// Re-export Wayland protocol bindings from wayland-protocols-wlr crate
-// Reserved for future use when abstracting protocol interactions
+// Wayland protocol bindings: re-exports from the wayland-protocols-wlr
+// crate, plus bindings generated here for protocols it doesn't cover
 
 #[allow(unused_imports)]
 pub use wayland_protocols_wlr::layer_shell::v1::client as wlr_layer_shell;
 #[allow(unused_imports)]
 pub use wayland_protocols_wlr::screencopy::v1::client as wlr_screencopy;
+
+// The ext image capture protocols are newer than our wayland-protocols, so
+// their bindings are generated here from the XML in protocols/, the same
+// way wayland-protocols generates its own.
+
+#[allow(
+    dead_code,
+    non_camel_case_types,
+    non_upper_case_globals,
+    unused_imports
+)]
+#[allow(clippy::all)]
+pub mod ext_image_capture_source {
+    use wayland_client;
+    use wayland_client::protocol::*;
+    use wayland_protocols::ext::foreign_toplevel_list::v1::client::*;
+
+    pub mod __interfaces {
+        use wayland_client::backend as wayland_backend;
+        use wayland_client::protocol::__interfaces::*;
+        use wayland_protocols::ext::foreign_toplevel_list::v1::client::__interfaces::*;
+        wayland_scanner::generate_interfaces!("protocols/ext-image-capture-source-v1.xml");
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_client_code!("protocols/ext-image-capture-source-v1.xml");
+}
+
+#[allow(
+    dead_code,
+    non_camel_case_types,
+    non_upper_case_globals,
+    unused_imports
+)]
+#[allow(clippy::all)]
+pub mod ext_image_copy_capture {
+    use super::ext_image_capture_source::*;
+    use wayland_client;
+    use wayland_client::protocol::*;
+
+    pub mod __interfaces {
+        use super::super::ext_image_capture_source::__interfaces::*;
+        use wayland_client::backend as wayland_backend;
+        use wayland_client::protocol::__interfaces::*;
+        wayland_scanner::generate_interfaces!("protocols/ext-image-copy-capture-v1.xml");
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_client_code!("protocols/ext-image-copy-capture-v1.xml");
+}
@@ -3,50 +3,128 @@
 //! This module handles the Cairo-based rendering of the magnifier overlay,
 //! including background rendering, magnified region rendering, and outline drawing.
 
+use crate::capture::Capture;
+use crate::config::{LensOffset, LensProjection, LensShape, ScaleFilter, Spotlight, ViewMode};
+use crate::cursor::Cursor;
+use crate::effects::upscale;
+use crate::effects::{self, ColorFilter, CvdFilter, Fisheye, Image, TextFilter};
+use crate::hud::Hud;
+use crate::lens::Lens;
+use crate::measure::Measure;
+use crate::overlay;
+use crate::picker::PickHistory;
 use crate::pool_buffer::PoolBuffer;
-use crate::utils::Vector2D;
+use crate::utils::{Rect, Vector2D};
+use crate::zoom::ZoomSteps;
 use anyhow::Result;
-use cairo::{Filter, Matrix, SurfacePattern};
+use cairo::{Filter, Format, ImageSurface, Matrix, SurfacePattern};
+use std::f64::consts::PI;
 
 /// Renderer for magnified content.
 ///
 /// Manages the zoom level and renders the magnified view using Cairo.
-/// The rendering pipeline consists of three stages:
+/// In lens mode the rendering pipeline consists of three stages:
 /// 1. Background: Full screen capture at reduced size
 /// 2. Magnified region: Zoomed section around the pointer
 /// 3. Outline: Visual frame around the magnified area
+///
+/// In full-screen mode the whole surface shows the magnified capture instead.
 pub struct Renderer {
     /// Current zoom level (0.01 = 1%, 1.0 = 100%)
     pub zoom: f64,
+    /// Magnification limits the zoom is kept within
+    pub zoom_steps: ZoomSteps,
+    /// Shape used to clip the magnified region and draw its outline
+    pub shape: LensShape,
+    /// Flat or fisheye magnification inside the floating lens
+    pub lens: LensProjection,
+    /// Where the floating lens sits relative to the pointer
+    pub lens_offset: LensOffset,
+    /// Floating lens or full-screen zoom
+    pub mode: ViewMode,
+    /// Colour transform applied to the magnified pixels
+    pub color: ColorFilter,
+    /// Colour-vision deficiency filter, applied before the colour transform
+    pub cvd: CvdFilter,
+    /// Interpolation used to enlarge the capture
+    pub filter: ScaleFilter,
+    /// Magnification at which `ScaleFilter::Auto` switches to nearest
+    pub filter_threshold: f64,
+    /// Sharpening and reading mode, applied before the colour filters
+    pub text: TextFilter,
+    /// Draw lines between source pixels at high magnification
+    pub pixel_grid: bool,
+    /// Show the position and value of the source pixel under the centre
+    pub inspector: bool,
+    /// Recently picked colours, shown as swatches beside the view
+    pub picks: PickHistory,
+    /// Ruler anchor and alignment guides
+    pub measure: Measure,
+    /// Fading zoom, monitor and position readout
+    pub hud: Hud,
+    /// Pointer drawn over the magnified content
+    pub cursor: Cursor,
+    /// Dim over everything outside the lens
+    pub spotlight: Spotlight,
+    /// Paint the capture around the lens so the screen looks frozen; off
+    /// while capturing live, when the desktop itself shows through
+    pub background: bool,
 }
 
 impl Renderer {
     /// Create a new renderer with default zoom level (0.5 = 50%).
     pub fn new() -> Self {
-        Self { zoom: 0.5 }
+        Self {
+            zoom: 0.5,
+            zoom_steps: ZoomSteps::default(),
+            shape: LensShape::Rectangle,
+            lens: LensProjection::Flat,
+            lens_offset: LensOffset::default(),
+            mode: ViewMode::Lens,
+            color: ColorFilter::default(),
+            cvd: CvdFilter::default(),
+            filter: ScaleFilter::Auto,
+            filter_threshold: 4.0,
+            text: TextFilter::default(),
+            pixel_grid: false,
+            inspector: false,
+            picks: PickHistory::default(),
+            measure: Measure::default(),
+            hud: Hud::default(),
+            cursor: Cursor::default(),
+            spotlight: Spotlight::default(),
+            background: true,
+        }
     }
 
     /// Set the zoom level.
     ///
-    /// The zoom value is automatically clamped to the range 0.01..=1.0.
+    /// The zoom value is automatically clamped to the magnification limits
+    /// in `zoom_steps` (0.01..=1.0 by default).
     ///
     /// # Arguments
     ///
-    /// * `zoom` - Desired zoom level (will be clamped to 0.01..=1.0)
+    /// * `zoom` - Desired zoom level, the reciprocal of the magnification
     pub fn set_zoom(&mut self, zoom: f64) {
-        self.zoom = zoom.clamp(0.01, 1.0);
+        self.zoom = self.zoom_steps.clamp_zoom(zoom);
     }
 
-    /// Adjust the zoom level by a delta value.
-    ///
-    /// The resulting zoom value is automatically clamped to the range 0.01..=1.0.
+    /// The filter actually used at the current zoom level.
     ///
-    /// # Arguments
-    ///
-    /// * `delta` - Amount to add to current zoom (negative to zoom out)
-    #[allow(dead_code)]
-    pub fn adjust_zoom(&mut self, delta: f64) {
-        self.zoom = (self.zoom + delta).clamp(0.01, 1.0);
+    /// `Auto` resolves to bicubic below the threshold magnification, where
+    /// smooth edges help reading, and to nearest at or above it, where
+    /// individual pixels matter.
+    pub fn effective_filter(&self) -> ScaleFilter {
+        self.filter_at(self.zoom)
+    }
+
+    /// The filter used for a view at `zoom`.
+    fn filter_at(&self, zoom: f64) -> ScaleFilter {
+        match self.filter {
+            ScaleFilter::Auto if 1.0 / zoom >= self.filter_threshold => ScaleFilter::Nearest,
+            ScaleFilter::Auto => ScaleFilter::Bicubic,
+            filter => filter,
+        }
     }
 
     /// Render the magnified view onto the output buffer.
@@ -62,18 +140,23 @@ impl Renderer {
     /// * `output_buffer` - Destination buffer for rendering
     /// * `screen_buffer` - Source screen capture buffer
     /// * `position` - Center position of magnifier in output coordinates
+    /// * `pointer` - Pointer position, ahead of `position` while smoothing lags
     /// * `magnifier_size` - Size of the magnified region
+    /// * `pins` - Pinned lenses on this output, bottom to top
     ///
     /// # Returns
     ///
     /// * `Ok(())` - Rendering succeeded
     /// * `Err` - Cairo rendering error
+    #[allow(clippy::too_many_arguments)]
     pub fn render_surface(
         &self,
         output_buffer: &mut PoolBuffer,
-        screen_buffer: &mut PoolBuffer,
+        screen_buffer: &mut Capture,
         position: Vector2D,
+        pointer: Vector2D,
         magnifier_size: Vector2D,
+        pins: &[Lens],
         force_inactive: bool,
         render_inactive: bool,
     ) -> Result<()> {
@@ -92,23 +175,96 @@ impl Renderer {
         ctx.fill()?;
         ctx.restore()?;
 
-        if !force_inactive || render_inactive {
+        if self.mode == ViewMode::Fullscreen && !force_inactive {
+            // The magnified view replaces the whole surface
+            let sampling = self.render_fullscreen(&ctx, screen_buffer, output_buffer, position)?;
+            let area = Rect::new(Vector2D::default(), output_buffer.pixel_size);
+            self.apply_effects(output_buffer, area);
+
+            self.draw_grid(&ctx, sampling, area, false)?;
+            let to_capture = screen_buffer.pixel_size / output_buffer.pixel_size;
+            self.draw_measure(&ctx, |p| sampling.invert(p * to_capture), position, area)?;
+            self.draw_cursor(&ctx, cursor_hotspot(sampling, pointer, to_capture))?;
+            // Keep the readout next to the pointer rather than at the screen edge
+            let pointer = Rect::from_center(position, Vector2D::new(32.0, 32.0));
+            self.draw_inspector(&ctx, screen_buffer, sampling, position, pointer, area)?;
+            overlay::draw_swatches(&ctx, &self.picks, pointer, area)?;
+            self.draw_measure_label(&ctx, position, pointer, area)?;
+            self.draw_hud(&ctx, position, pointer, area)?;
+            return Ok(());
+        }
+
+        // The lens may sit beside the pointer or be pushed in from the
+        // edges, but it always magnifies what is under the pointer
+        let bounds = Rect::new(Vector2D::default(), output_buffer.pixel_size);
+        let center = lens_center(position, magnifier_size, bounds, self.lens_offset).floor();
+
+        if self.background && (!force_inactive || render_inactive) {
             // Render full screen background
             self.render_background(&ctx, screen_buffer, output_buffer)?;
         }
 
+        if self.spotlight.enabled && self.mode == ViewMode::Lens {
+            // Outputs without the lens are dimmed all over
+            let hole = (!force_inactive).then_some(center);
+            self.draw_spotlight(&ctx, hole, magnifier_size)?;
+        }
+
+        // Pins stay on screen whether or not the pointer is on this output
+        for pin in pins {
+            self.render_pin(&ctx, screen_buffer, output_buffer, pin)?;
+        }
+
         if !force_inactive {
+            // Guides and ruler at screen scale; the lens covers them where
+            // it overlaps and redraws them magnified
+            self.draw_measure(&ctx, |p| p, position, bounds)?;
+
+            let live = Lens {
+                source: position,
+                center,
+                size: magnifier_size,
+                zoom: self.zoom,
+            };
+
             // Render magnified region
-            self.render_magnified_region(
-                &ctx,
-                screen_buffer,
-                output_buffer,
-                position,
-                magnifier_size,
-            )?;
+            let sampling =
+                self.render_magnified_region(&ctx, screen_buffer, output_buffer, &live)?;
+
+            // Post-process only the lens bounds so cost tracks lens size
+            let lens = Rect::from_center(center, magnifier_size);
+            self.apply_lens_effects(&ctx, output_buffer, center, magnifier_size)?;
+
+            // Grid lines and guides assume a uniform zoom across the lens;
+            // under a fisheye `sampling` only holds at the centre
+            if self.lens == LensProjection::Flat {
+                self.draw_grid(&ctx, sampling, lens, true)?;
+
+                let to_capture = screen_buffer.pixel_size / output_buffer.pixel_size;
+                ctx.save()?;
+                self.lens_path(&ctx, center, magnifier_size);
+                ctx.clip();
+                self.draw_measure(&ctx, |p| sampling.invert(p * to_capture), position, lens)?;
+                ctx.restore()?;
+            }
+
+            // The pointer over the magnified content, at the exact hotspot
+            // rather than the pixel the lens is centred on, which trails
+            // it while smoothing catches up
+            let to_capture = screen_buffer.pixel_size / output_buffer.pixel_size;
+            ctx.save()?;
+            self.lens_path(&ctx, center, magnifier_size);
+            ctx.clip();
+            self.draw_cursor(&ctx, cursor_hotspot(sampling, pointer, to_capture))?;
+            ctx.restore()?;
 
             // Draw outline
-            self.draw_outline(&ctx, position, magnifier_size)?;
+            self.draw_outline(&ctx, center, magnifier_size)?;
+
+            self.draw_inspector(&ctx, screen_buffer, sampling, center, lens, bounds)?;
+            overlay::draw_swatches(&ctx, &self.picks, lens, bounds)?;
+            self.draw_measure_label(&ctx, position, lens, bounds)?;
+            self.draw_hud(&ctx, position, lens, bounds)?;
         }
 
         Ok(())
@@ -117,17 +273,14 @@ impl Renderer {
     fn render_background(
         &self,
         ctx: &cairo::Context,
-        screen: &mut PoolBuffer,
+        screen: &mut Capture,
         output: &PoolBuffer,
     ) -> Result<()> {
-        let screen_surf = screen.get_cairo_surface()?;
-        let pattern = SurfacePattern::create(screen_surf);
-        pattern.set_filter(Filter::Bilinear);
-
         let scale = screen.pixel_size / output.pixel_size;
         let mut matrix = Matrix::identity();
         matrix.scale(scale.x, scale.y);
-        pattern.set_matrix(matrix);
+        let pattern = screen.pattern(matrix)?;
+        pattern.set_filter(Filter::Bilinear);
 
         ctx.set_source(&pattern)?;
         ctx.paint()?;
@@ -135,39 +288,424 @@ impl Renderer {
         Ok(())
     }
 
-    fn render_magnified_region(
+    /// Paint the whole output with the magnified capture.
+    ///
+    /// The viewport is `zoom` times the output size and slides across the
+    /// screen proportionally to the pointer, so the pointer always sits over
+    /// the content it points at and the screen edges are reachable.
+    fn render_fullscreen(
         &self,
         ctx: &cairo::Context,
-        screen: &mut PoolBuffer,
+        screen: &mut Capture,
         output: &PoolBuffer,
         position: Vector2D,
+    ) -> Result<Sampling> {
+        let scale = screen.pixel_size / output.pixel_size;
+        let origin = viewport_origin(position, output.pixel_size, self.zoom);
+
+        // output -> viewport (zoom, then offset) -> screen pixels (scale)
+        let sampling = Sampling {
+            origin: origin * scale,
+            scale: scale * self.zoom,
+        };
+        let area = Rect::new(Vector2D::default(), output.pixel_size);
+        let pattern = self.magnified_pattern(screen, sampling, area, self.effective_filter())?;
+
+        ctx.set_source(&pattern)?;
+        ctx.paint()?;
+
+        Ok(sampling)
+    }
+
+    /// Paint `lens`, magnifying the capture at its source point.
+    fn render_magnified_region(
+        &self,
+        ctx: &cairo::Context,
+        screen: &mut Capture,
+        output: &PoolBuffer,
+        lens: &Lens,
+    ) -> Result<Sampling> {
+        let click_pos = lens.source.floor() / output.pixel_size * screen.pixel_size;
+
+        let sampling = Sampling::around(click_pos, lens.center, lens.zoom);
+        let area = lens.bounds();
+        let pattern = match self.lens {
+            LensProjection::Flat => {
+                self.magnified_pattern(screen, sampling, area, self.filter_at(lens.zoom))?
+            }
+            LensProjection::Fisheye(strength) => {
+                let to_capture = screen.pixel_size / output.pixel_size;
+                self.fisheye_pattern(screen, click_pos, area, to_capture, lens.zoom, strength)?
+            }
+        };
+        ctx.set_source(&pattern)?;
+
+        // Clip to magnifier region (anti-aliased for curved shapes)
+        ctx.save()?;
+        self.lens_path(ctx, lens.center, lens.size);
+        ctx.clip();
+        ctx.paint()?;
+        ctx.restore()?;
+
+        Ok(sampling)
+    }
+
+    /// Dim the output except for the lens centred on `center`, fading in
+    /// over the spotlight's soft edge. Without a lens everything is dimmed.
+    fn draw_spotlight(
+        &self,
+        ctx: &cairo::Context,
+        center: Option<Vector2D>,
         size: Vector2D,
     ) -> Result<()> {
-        let screen_surf = screen.get_cairo_surface()?;
-        let pattern = SurfacePattern::create(screen_surf);
-        pattern.set_filter(Filter::Nearest);
+        ctx.save()?;
+        ctx.push_group();
+        ctx.set_source_rgba(0.0, 0.0, 0.0, self.spotlight.dim);
+        ctx.paint()?;
 
-        let scale = screen.pixel_size / output.pixel_size;
-        let magnifier_pos = position.floor();
-        let click_pos = magnifier_pos / output.pixel_size * screen.pixel_size;
+        if let Some(center) = center {
+            // Each band replaces the dim inside it with a lighter one
+            ctx.set_operator(cairo::Operator::Source);
+            for (grow, dim) in self.spotlight.bands() {
+                self.lens_path(ctx, center, size + Vector2D::new(grow, grow) * 2.0);
+                ctx.set_source_rgba(0.0, 0.0, 0.0, dim);
+                ctx.fill()?;
+            }
+        }
 
-        // Calculate the transform matrix for magnification
-        let mut matrix = Matrix::identity();
-        matrix.translate(click_pos.x, click_pos.y);
-        matrix.scale(self.zoom, self.zoom);
-        matrix.translate(-click_pos.x / scale.x, -click_pos.y / scale.y);
-        pattern.set_matrix(matrix);
+        ctx.pop_group_to_source()?;
+        ctx.paint()?;
+        ctx.restore()?;
+
+        Ok(())
+    }
+
+    /// Paint a pinned lens with its effects, grid and outline.
+    fn render_pin(
+        &self,
+        ctx: &cairo::Context,
+        screen: &mut Capture,
+        output: &mut PoolBuffer,
+        pin: &Lens,
+    ) -> Result<()> {
+        let sampling = self.render_magnified_region(ctx, screen, output, pin)?;
+        self.apply_lens_effects(ctx, output, pin.center, pin.size)?;
+        if self.lens == LensProjection::Flat {
+            self.draw_grid(ctx, sampling, pin.bounds(), true)?;
+        }
+        self.draw_outline(ctx, pin.center, pin.size)?;
+
+        Ok(())
+    }
+
+    /// Render a docked pane showing the area around `source`.
+    ///
+    /// Unlike the lens, the pane is drawn at a fixed place: the whole output
+    /// buffer is the pane and its centre shows the screen point `source`.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_buffer` - The dock surface's buffer
+    /// * `screen_buffer` - Source screen capture buffer
+    /// * `source` - Point to magnify, in the monitor's logical coordinates
+    /// * `pointer` - Pointer position, ahead of `source` while smoothing lags
+    /// * `source_space` - Logical size of the monitor `source` refers to
+    pub fn render_docked(
+        &self,
+        output_buffer: &mut PoolBuffer,
+        screen_buffer: &mut Capture,
+        source: Vector2D,
+        pointer: Vector2D,
+        source_space: Vector2D,
+    ) -> Result<()> {
+        let ctx = output_buffer.create_cairo_context()?;
+        let size = output_buffer.pixel_size;
+
+        ctx.save()?;
+        ctx.set_source_rgba(0.1, 0.1, 0.1, 1.0);
+        ctx.set_operator(cairo::Operator::Source);
+        ctx.paint()?;
+        ctx.restore()?;
 
+        let click_pos = source.floor() / source_space * screen_buffer.pixel_size;
+        let sampling = Sampling::around(click_pos, size / 2.0, self.zoom);
+        let area = Rect::new(Vector2D::default(), size);
+        let pattern =
+            self.magnified_pattern(screen_buffer, sampling, area, self.effective_filter())?;
         ctx.set_source(&pattern)?;
+        ctx.paint()?;
+
+        self.apply_effects(output_buffer, area);
+        self.draw_grid(&ctx, sampling, area, false)?;
+        let to_capture = screen_buffer.pixel_size / source_space;
+        self.draw_measure(&ctx, |p| sampling.invert(p * to_capture), source, area)?;
+        self.draw_cursor(&ctx, cursor_hotspot(sampling, pointer, to_capture))?;
+
+        ctx.rectangle(0.0, 0.0, size.x, size.y);
+        ctx.set_source_rgba(150.0 / 255.0, 150.0 / 255.0, 150.0 / 255.0, 1.0);
+        ctx.set_line_width(2.0);
+        ctx.stroke()?;
+
+        self.draw_inspector(&ctx, screen_buffer, sampling, size / 2.0, area, area)?;
+        // Inside the pane, along its right edge
+        let edge = Rect::new(Vector2D::new(size.x, 0.0), Vector2D::new(0.0, size.y));
+        overlay::draw_swatches(&ctx, &self.picks, edge, area)?;
+        // Top-left corner of the pane
+        let corner = Rect::new(Vector2D::new(6.0, 0.0), Vector2D::default());
+        self.draw_measure_label(&ctx, source, corner, area)?;
+        // Bottom-right corner, clear of the swatches
+        let bottom = Rect::new(
+            Vector2D::new(0.0, size.y - 6.0),
+            Vector2D::new(size.x - 6.0, 0.0),
+        );
+        self.draw_hud(&ctx, source, bottom, area)?;
+
+        Ok(())
+    }
+
+    /// Copy the lens drawn at `center` out of a rendered buffer, leaving
+    /// everything outside its shape transparent.
+    pub fn snapshot_lens(
+        &self,
+        output: &mut PoolBuffer,
+        center: Vector2D,
+        size: Vector2D,
+    ) -> Result<ImageSurface> {
+        let area = Rect::from_center(center, size);
+        let (x, y, width, height) = area.to_pixels();
+        let drawn = Image::crop(output, area).into_surface()?;
+
+        let surface = ImageSurface::create(Format::ARgb32, width.max(1), height.max(1))?;
+        let ctx = cairo::Context::new(&surface)?;
+        ctx.translate(-f64::from(x), -f64::from(y));
+        self.lens_path(&ctx, center, size);
+        ctx.clip();
+        ctx.set_source_surface(&drawn, f64::from(x), f64::from(y))?;
+        ctx.paint()?;
+        drop(ctx);
+
+        Ok(surface)
+    }
+
+    /// Capture area `lens` shows, in capture pixels. `source_space` is the
+    /// logical size of the monitor the lens source refers to.
+    pub fn lens_source(&self, screen_size: Vector2D, source_space: Vector2D, lens: &Lens) -> Rect {
+        let click_pos = lens.source.floor() / source_space * screen_size;
+        match self.lens {
+            LensProjection::Flat => {
+                Sampling::around(click_pos, lens.center, lens.zoom).source_rect(lens.bounds())
+            }
+            // The rim is at 1x, so the lens reaches as far as its own size
+            LensProjection::Fisheye(_) => {
+                Rect::from_center(click_pos, lens.size * (screen_size / source_space))
+            }
+        }
+    }
+
+    /// Capture area the full-screen view shows with the pointer at `position`.
+    pub fn viewport_source(
+        &self,
+        screen_size: Vector2D,
+        output_size: Vector2D,
+        position: Vector2D,
+    ) -> Rect {
+        let scale = screen_size / output_size;
+        let origin = viewport_origin(position, output_size, self.zoom);
+        Rect::new(origin * scale, output_size * scale * self.zoom)
+    }
+
+    /// Draw the pixel grid over `view`, clipped to the lens shape if `shaped`.
+    fn draw_grid(
+        &self,
+        ctx: &cairo::Context,
+        sampling: Sampling,
+        view: Rect,
+        shaped: bool,
+    ) -> Result<()> {
+        if !self.pixel_grid {
+            return Ok(());
+        }
 
-        // Clip to magnifier region
         ctx.save()?;
-        ctx.rectangle(
-            magnifier_pos.x - size.x / 2.0,
-            magnifier_pos.y - size.y / 2.0,
-            size.x,
-            size.y,
+        if shaped {
+            self.lens_path(ctx, view.pos + view.size / 2.0, view.size);
+        } else {
+            ctx.rectangle(view.pos.x, view.pos.y, view.size.x, view.size.y);
+        }
+        ctx.clip();
+        overlay::draw_pixel_grid(ctx, sampling.origin, sampling.scale, view)?;
+        ctx.restore()?;
+
+        Ok(())
+    }
+
+    /// Draw the guides and the ruler to `pointer` over `area`.
+    ///
+    /// `map` takes the monitor's logical coordinates, where guides and the
+    /// anchor are stored, to output coordinates.
+    fn draw_measure(
+        &self,
+        ctx: &cairo::Context,
+        map: impl Fn(Vector2D) -> Vector2D,
+        pointer: Vector2D,
+        area: Rect,
+    ) -> Result<()> {
+        for guide in self.measure.guides() {
+            overlay::draw_guide(ctx, guide.map(&map), area)?;
+        }
+        if let (true, Some(anchor)) = (self.measure.enabled, self.measure.anchor()) {
+            overlay::draw_ruler(ctx, map(anchor), map(pointer))?;
+        }
+
+        Ok(())
+    }
+
+    /// Draw the pointer with its hotspot at `hotspot`, scaled to the zoom.
+    fn draw_cursor(&self, ctx: &cairo::Context, hotspot: Vector2D) -> Result<()> {
+        self.cursor.draw(ctx, hotspot, 1.0 / self.zoom)
+    }
+
+    /// Print the ruler readout next to `view`.
+    fn draw_measure_label(
+        &self,
+        ctx: &cairo::Context,
+        pointer: Vector2D,
+        view: Rect,
+        bounds: Rect,
+    ) -> Result<()> {
+        match self.measure.label(pointer) {
+            Some(text) => overlay::draw_label_above(ctx, &text, view, 0.0, bounds),
+            None => Ok(()),
+        }
+    }
+
+    /// Draw the HUD at the top-right of `view`, faded to its current opacity.
+    fn draw_hud(
+        &self,
+        ctx: &cairo::Context,
+        pointer: Vector2D,
+        view: Rect,
+        bounds: Rect,
+    ) -> Result<()> {
+        let opacity = self.hud.opacity();
+        if opacity <= 0.0 {
+            return Ok(());
+        }
+
+        ctx.save()?;
+        ctx.push_group();
+        overlay::draw_label_above(ctx, &self.hud.text(pointer), view, 1.0, bounds)?;
+        ctx.pop_group_to_source()?;
+        ctx.paint_with_alpha(opacity)?;
+        ctx.restore()?;
+
+        Ok(())
+    }
+
+    /// Mark the source pixel shown at `focus` and print its position and value.
+    ///
+    /// The readout goes below `view`, or above it if there is no room.
+    fn draw_inspector(
+        &self,
+        ctx: &cairo::Context,
+        screen: &Capture,
+        sampling: Sampling,
+        focus: Vector2D,
+        view: Rect,
+        bounds: Rect,
+    ) -> Result<()> {
+        if !self.inspector {
+            return Ok(());
+        }
+
+        let pixel = sampling.apply(focus).floor();
+        let cell = Rect::new(
+            (pixel - sampling.origin) / sampling.scale,
+            Vector2D::new(1.0, 1.0) / sampling.scale,
         );
+        // Only worth marking once a source pixel is clearly bigger than the marker
+        if cell.size.x >= 4.0 && cell.size.y >= 4.0 {
+            overlay::draw_pixel_marker(ctx, cell)?;
+        }
+
+        let argb = screen.pixel_at(pixel.x as i32, pixel.y as i32);
+        let anchor = Vector2D::new(view.pos.x, view.end().y + 6.0);
+        overlay::draw_inspector(ctx, pixel, argb, anchor, view, bounds)
+    }
+
+    /// Run the CPU-side pixel effects over `area` of the output buffer.
+    fn apply_effects(&self, output: &mut PoolBuffer, area: Rect) {
+        if !self.text.is_identity() {
+            // Neighbourhood filters work on a copy of the area
+            let bounds = Rect::new(Vector2D::default(), output.pixel_size);
+            let area = area.intersect(&bounds);
+            if !area.is_empty() {
+                let (x, y, _, _) = area.to_pixels();
+                let mut image = Image::crop(output, area);
+                self.text.apply(&mut image);
+                image.paste(output, x, y);
+            }
+        }
+
+        if self.cvd.is_identity() && self.color.is_identity() {
+            return;
+        }
+
+        // One pass over the pixels for all per-pixel filters
+        let cvd = self.cvd.compile();
+        let color = self.color.compile();
+        effects::map_pixels(output, area, |rgb| color(cvd(rgb)));
+    }
+
+    /// Run the pixel effects over the lens centred on `center`, leaving the
+    /// rest of its bounding box as it was.
+    fn apply_lens_effects(
+        &self,
+        ctx: &cairo::Context,
+        output: &mut PoolBuffer,
+        center: Vector2D,
+        size: Vector2D,
+    ) -> Result<()> {
+        let bounds = Rect::new(Vector2D::default(), output.pixel_size);
+        let area = Rect::from_center(center, size).intersect(&bounds);
+        if self.shape == LensShape::Rectangle {
+            self.apply_effects(output, area);
+            return Ok(());
+        }
+        if area.is_empty() || !self.has_effects() {
+            return Ok(());
+        }
+
+        let (x, y, _, _) = area.to_pixels();
+        let image = Image::crop(output, area);
+        self.effects_through_lens(ctx, image, Vector2D::new(x as f64, y as f64), center, size)
+    }
+
+    /// Filter `image`, a copy of the output at `origin`, and paint it back
+    /// clipped to the lens path. The lens edge is anti-aliased, so its
+    /// pixels blend the filtered and the untouched colours.
+    fn effects_through_lens(
+        &self,
+        ctx: &cairo::Context,
+        mut image: Image,
+        origin: Vector2D,
+        center: Vector2D,
+        size: Vector2D,
+    ) -> Result<()> {
+        if !self.text.is_identity() {
+            self.text.apply(&mut image);
+        }
+        if !self.cvd.is_identity() || !self.color.is_identity() {
+            let cvd = self.cvd.compile();
+            let color = self.color.compile();
+            effects::map_image(&mut image, |rgb| color(cvd(rgb)));
+        }
+
+        let surface = image.into_surface()?;
+        ctx.save()?;
+        ctx.set_source_surface(&surface, origin.x, origin.y)?;
+        ctx.set_operator(cairo::Operator::Source);
+        self.lens_path(ctx, center, size);
         ctx.clip();
         ctx.paint()?;
         ctx.restore()?;
@@ -175,19 +713,323 @@ impl Renderer {
         Ok(())
     }
 
-    fn draw_outline(&self, ctx: &cairo::Context, position: Vector2D, size: Vector2D) -> Result<()> {
-        ctx.rectangle(
-            position.x - size.x / 2.0,
-            position.y - size.y / 2.0,
-            size.x,
-            size.y,
+    fn has_effects(&self) -> bool {
+        !self.text.is_identity() || !self.cvd.is_identity() || !self.color.is_identity()
+    }
+
+    /// Pattern that shows the capture magnified through `sampling`.
+    ///
+    /// `area` is the part of the output that will be painted; the CPU
+    /// filters only process the capture behind it. Cairo's own filters
+    /// sample the capture directly.
+    fn magnified_pattern(
+        &self,
+        screen: &mut Capture,
+        sampling: Sampling,
+        area: Rect,
+        filter: ScaleFilter,
+    ) -> Result<SurfacePattern> {
+        let cairo_filter = match filter {
+            ScaleFilter::Lanczos => return self.lanczos_pattern(screen, sampling, area),
+            ScaleFilter::Scale2x => {
+                return self.pixel_art_pattern(screen, sampling, area, upscale::scale2x)
+            }
+            ScaleFilter::Xbr => {
+                return self.pixel_art_pattern(screen, sampling, area, upscale::xbr2x)
+            }
+            ScaleFilter::Bilinear => Filter::Bilinear,
+            ScaleFilter::Bicubic => Filter::Best,
+            ScaleFilter::Nearest | ScaleFilter::Auto => Filter::Nearest,
+        };
+
+        let pattern = screen.pattern(sampling.matrix())?;
+        pattern.set_filter(cairo_filter);
+
+        Ok(pattern)
+    }
+
+    /// Lanczos-resample the capture behind `area` to output resolution.
+    fn lanczos_pattern(
+        &self,
+        screen: &mut Capture,
+        sampling: Sampling,
+        area: Rect,
+    ) -> Result<SurfacePattern> {
+        let (x, y, width, height) = area.to_pixels();
+        let area_pos = Vector2D::new(x as f64, y as f64);
+
+        // Margin for the kernel taps either side of the visible samples
+        let crop = sampling.source_rect(area).inflate(4.0);
+        let crop_pos = crop.pos.floor();
+        let image = screen.crop(crop);
+
+        let origin = sampling.apply(area_pos) - crop_pos;
+        let resampled = upscale::lanczos(
+            &image,
+            width.max(1) as usize,
+            height.max(1) as usize,
+            (origin.x as f32, origin.y as f32),
+            (sampling.scale.x as f32, sampling.scale.y as f32),
         );
+
+        let surface = resampled.into_surface()?;
+        let pattern = SurfacePattern::create(&surface);
+        pattern.set_filter(Filter::Nearest);
+        pattern.set_matrix(Matrix::new(1.0, 0.0, 0.0, 1.0, -area_pos.x, -area_pos.y));
+
+        Ok(pattern)
+    }
+
+    /// Remap the capture behind `area` through a fisheye centred on `source`.
+    ///
+    /// `to_capture` converts output pixels to capture pixels at 1x; the
+    /// lens edge shows the capture at that scale, so it lines up with the
+    /// background around it.
+    fn fisheye_pattern(
+        &self,
+        screen: &mut Capture,
+        source: Vector2D,
+        area: Rect,
+        to_capture: Vector2D,
+        zoom: f64,
+        strength: f64,
+    ) -> Result<SurfacePattern> {
+        let (x, y, width, height) = area.to_pixels();
+        let area_pos = Vector2D::new(x as f64, y as f64);
+
+        // Nothing inside the lens reaches further out than its 1x footprint
+        let crop = Rect::from_center(source, area.size * to_capture).inflate(2.0);
+        let crop_pos = crop.pos.floor();
+        let image = screen.crop(crop);
+
+        // Fall off toward the edge of the visible shape, not its bounding box
+        let center = area.pos + area.size / 2.0 - area_pos;
+        let lens = match self.shape {
+            LensShape::Circle => {
+                let diameter = area.size.x.min(area.size.y);
+                Rect::from_center(center, Vector2D::new(diameter, diameter))
+            }
+            _ => Rect::from_center(center, area.size),
+        };
+
+        let fisheye = Fisheye {
+            magnification: 1.0 / zoom,
+            strength,
+        };
+        let remapped = fisheye.remap(
+            &image,
+            (width.max(1) as usize, height.max(1) as usize),
+            lens,
+            source - crop_pos,
+            to_capture,
+            self.filter_at(zoom) != ScaleFilter::Nearest,
+        );
+
+        let surface = remapped.into_surface()?;
+        let pattern = SurfacePattern::create(&surface);
+        pattern.set_filter(Filter::Nearest);
+        pattern.set_matrix(Matrix::new(1.0, 0.0, 0.0, 1.0, -area_pos.x, -area_pos.y));
+
+        Ok(pattern)
+    }
+
+    /// Upscale the capture behind `area` with a 2x pixel-art scaler.
+    ///
+    /// The scaler runs as many times as fits in the magnification (up to
+    /// 8x); Cairo scales the rest of the way with nearest neighbour.
+    fn pixel_art_pattern(
+        &self,
+        screen: &mut Capture,
+        sampling: Sampling,
+        area: Rect,
+        scaler: fn(&Image) -> Image,
+    ) -> Result<SurfacePattern> {
+        // Scalers look two pixels out from the one being enlarged
+        let crop = sampling.source_rect(area).inflate(2.0);
+        let crop_pos = crop.pos.floor();
+        let mut image = screen.crop(crop);
+
+        let magnification = 1.0 / sampling.scale.x.max(sampling.scale.y);
+        let passes = magnification.log2().floor().clamp(1.0, 3.0) as i32;
+        for _ in 0..passes {
+            image = scaler(&image);
+        }
+        let factor = f64::from(1 << passes);
+
+        let surface = image.into_surface()?;
+        let pattern = SurfacePattern::create(&surface);
+        pattern.set_filter(if magnification >= factor {
+            Filter::Nearest
+        } else {
+            Filter::Good
+        });
+
+        // output -> capture -> crop -> upscaled crop
+        let offset = (sampling.origin - crop_pos) * factor;
+        pattern.set_matrix(Matrix::new(
+            sampling.scale.x * factor,
+            0.0,
+            0.0,
+            sampling.scale.y * factor,
+            offset.x,
+            offset.y,
+        ));
+
+        Ok(pattern)
+    }
+
+    fn draw_outline(&self, ctx: &cairo::Context, position: Vector2D, size: Vector2D) -> Result<()> {
+        self.lens_path(ctx, position, size);
         ctx.set_source_rgba(150.0 / 255.0, 150.0 / 255.0, 150.0 / 255.0, 1.0);
         ctx.set_line_width(2.0);
         ctx.stroke()?;
 
         Ok(())
     }
+
+    /// Build the lens outline as the current path.
+    ///
+    /// The path is centred on `center` and fitted to `size`, so the same path
+    /// serves both as the clip for the magnified region and as the outline.
+    fn lens_path(&self, ctx: &cairo::Context, center: Vector2D, size: Vector2D) {
+        let x = center.x - size.x / 2.0;
+        let y = center.y - size.y / 2.0;
+
+        ctx.new_path();
+        match self.shape {
+            LensShape::Rectangle => ctx.rectangle(x, y, size.x, size.y),
+            LensShape::Circle => {
+                let radius = size.x.min(size.y) / 2.0;
+                ctx.arc(center.x, center.y, radius, 0.0, 2.0 * PI);
+            }
+            LensShape::Ellipse => {
+                ctx.save().ok();
+                ctx.translate(center.x, center.y);
+                ctx.scale(size.x / 2.0, size.y / 2.0);
+                ctx.arc(0.0, 0.0, 1.0, 0.0, 2.0 * PI);
+                ctx.restore().ok();
+            }
+            LensShape::Rounded(radius) => {
+                let r = radius.min(size.x / 2.0).min(size.y / 2.0);
+                ctx.arc(x + size.x - r, y + r, r, -PI / 2.0, 0.0);
+                ctx.arc(x + size.x - r, y + size.y - r, r, 0.0, PI / 2.0);
+                ctx.arc(x + r, y + size.y - r, r, PI / 2.0, PI);
+                ctx.arc(x + r, y + r, r, PI, 3.0 * PI / 2.0);
+                ctx.close_path();
+            }
+        }
+    }
+}
+
+/// Where the pointer at `pointer` is drawn in a view sampled through
+/// `sampling`; `to_capture` scales pointer coordinates to capture pixels.
+fn cursor_hotspot(sampling: Sampling, pointer: Vector2D, to_capture: Vector2D) -> Vector2D {
+    sampling.invert(pointer * to_capture)
+}
+
+/// Affine map from output coordinates to capture pixels.
+///
+/// A point `p` on the output shows the capture at `origin + p * scale`, so
+/// `scale` is the reciprocal of the magnification.
+#[derive(Debug, Clone, Copy)]
+struct Sampling {
+    origin: Vector2D,
+    scale: Vector2D,
+}
+
+impl Sampling {
+    /// Show the capture pixel `source` at output point `center`, zoomed.
+    fn around(source: Vector2D, center: Vector2D, zoom: f64) -> Self {
+        Self {
+            origin: source - center * zoom,
+            scale: Vector2D::new(zoom, zoom),
+        }
+    }
+
+    fn apply(&self, p: Vector2D) -> Vector2D {
+        self.origin + p * self.scale
+    }
+
+    /// Output point that shows the capture point `c`.
+    fn invert(&self, c: Vector2D) -> Vector2D {
+        (c - self.origin) / self.scale
+    }
+
+    /// Capture area shown by the output area `area`.
+    fn source_rect(&self, area: Rect) -> Rect {
+        Rect::new(self.apply(area.pos), area.size * self.scale)
+    }
+
+    fn matrix(&self) -> Matrix {
+        Matrix::new(
+            self.scale.x,
+            0.0,
+            0.0,
+            self.scale.y,
+            self.origin.x,
+            self.origin.y,
+        )
+    }
+}
+
+/// Top-left corner of the full-screen viewport in output coordinates.
+///
+/// The viewport covers `output_size * zoom` and is placed so that the point
+/// under `position` stays under `position`, clamped to the output bounds.
+pub fn viewport_origin(position: Vector2D, output_size: Vector2D, zoom: f64) -> Vector2D {
+    let position = Vector2D::new(
+        position.x.clamp(0.0, output_size.x),
+        position.y.clamp(0.0, output_size.y),
+    );
+    position * (1.0 - zoom)
+}
+
+/// Centre of a lens of `size` for the pointer at `position`.
+///
+/// The offset moves the lens off the pointer, and the result is then
+/// clamped so the whole lens stays inside `bounds` (or centred on it, if
+/// the lens is larger).
+pub fn lens_center(
+    position: Vector2D,
+    size: Vector2D,
+    bounds: Rect,
+    offset: LensOffset,
+) -> Vector2D {
+    let offset = match offset {
+        LensOffset::Fixed(offset) => offset,
+        LensOffset::Auto => {
+            // Toward the middle of the screen, where there is more room
+            let middle = bounds.pos + bounds.size / 2.0;
+            let toward = |p: f64, m: f64| if p > m { -1.0 } else { 1.0 };
+            Vector2D::new(toward(position.x, middle.x), toward(position.y, middle.y))
+                * LensOffset::AUTO_GAP
+        }
+    };
+
+    // A non-zero offset is the gap between the pointer and the lens's near edge
+    let shift = |o: f64, half: f64| {
+        if o > 0.0 {
+            o + half
+        } else if o < 0.0 {
+            o - half
+        } else {
+            0.0
+        }
+    };
+    let half = size / 2.0;
+    let center = position + Vector2D::new(shift(offset.x, half.x), shift(offset.y, half.y));
+
+    let clamp = |c: f64, half: f64, start: f64, end: f64| {
+        if end - start < 2.0 * half {
+            (start + end) / 2.0
+        } else {
+            c.clamp(start + half, end - half)
+        }
+    };
+    Vector2D::new(
+        clamp(center.x, half.x, bounds.pos.x, bounds.end().x),
+        clamp(center.y, half.y, bounds.pos.y, bounds.end().y),
+    )
 }
 
 impl Default for Renderer {
@@ -199,6 +1041,8 @@ impl Default for Renderer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{ColorMode, ReadingMode};
+    use crate::effects::{CvdKind, CvdMode};
 
     #[test]
     fn test_renderer_default_zoom() {
@@ -231,24 +1075,287 @@ mod tests {
     }
 
     #[test]
-    fn test_adjust_zoom_clamping() {
-        let mut renderer = Renderer::new();
+    fn test_zoom_limits() {
+        let mut renderer = Renderer {
+            zoom_steps: ZoomSteps::new(2.0, 400.0, 1.05, Vec::new()),
+            ..Renderer::new()
+        };
+
+        // Beyond 100x is reachable when the ceiling allows it
+        renderer.set_zoom(0.001);
+        assert_eq!(renderer.zoom, 1.0 / 400.0);
+
+        // The floor keeps some magnification
+        renderer.set_zoom(1.0);
         assert_eq!(renderer.zoom, 0.5);
+    }
+
+    #[test]
+    fn test_lens_path_follows_shape() {
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 400, 400).unwrap();
+        let ctx = cairo::Context::new(&surface).unwrap();
+        let center = Vector2D::new(200.0, 200.0);
+        let size = Vector2D::new(200.0, 100.0);
 
-        // Test positive adjustment
-        renderer.adjust_zoom(0.2);
-        assert!((renderer.zoom - 0.7).abs() < 1e-10);
+        let mut renderer = Renderer::new();
 
-        // Test negative adjustment
-        renderer.adjust_zoom(-0.3);
-        assert!((renderer.zoom - 0.4).abs() < 1e-10);
+        // Rectangle covers its corners
+        renderer.lens_path(&ctx, center, size);
+        assert!(ctx.in_fill(101.0, 151.0).unwrap());
 
-        // Test adjustment that would exceed max - should clamp
-        renderer.adjust_zoom(1.0);
-        assert_eq!(renderer.zoom, 1.0);
+        // Circle uses the shorter side as diameter
+        renderer.shape = LensShape::Circle;
+        renderer.lens_path(&ctx, center, size);
+        assert!(ctx.in_fill(200.0, 155.0).unwrap());
+        assert!(!ctx.in_fill(270.0, 200.0).unwrap());
 
-        // Test adjustment that would go below min - should clamp
-        renderer.adjust_zoom(-2.0);
-        assert_eq!(renderer.zoom, 0.01);
+        // Ellipse fills the full width but not the corners
+        renderer.shape = LensShape::Ellipse;
+        renderer.lens_path(&ctx, center, size);
+        assert!(ctx.in_fill(295.0, 200.0).unwrap());
+        assert!(!ctx.in_fill(101.0, 151.0).unwrap());
+
+        // Rounded rectangle cuts only the corners
+        renderer.shape = LensShape::Rounded(20.0);
+        renderer.lens_path(&ctx, center, size);
+        assert!(ctx.in_fill(200.0, 151.0).unwrap());
+        assert!(!ctx.in_fill(101.0, 151.0).unwrap());
+    }
+
+    /// Paint `fill` over a 100x100 surface, run the renderer's effects
+    /// through a lens filling it and return the corner and centre pixels.
+    fn lens_effect_pixels(renderer: &Renderer, fill: u32) -> (u32, u32) {
+        let mut surface = ImageSurface::create(Format::ARgb32, 100, 100).unwrap();
+        let ctx = cairo::Context::new(&surface).unwrap();
+        let image = Image::new(100, 100, fill);
+        ctx.set_source_surface(image.clone().into_surface().unwrap(), 0.0, 0.0)
+            .unwrap();
+        ctx.paint().unwrap();
+
+        let center = Vector2D::new(50.0, 50.0);
+        let size = Vector2D::new(100.0, 100.0);
+        renderer
+            .effects_through_lens(&ctx, image, Vector2D::default(), center, size)
+            .unwrap();
+        drop(ctx);
+
+        let stride = surface.stride() as usize;
+        let data = surface.data().unwrap();
+        let pixel = |x: usize, y: usize| {
+            let offset = y * stride + x * 4;
+            u32::from_ne_bytes(data[offset..offset + 4].try_into().unwrap())
+        };
+        (pixel(2, 2), pixel(50, 50))
+    }
+
+    #[test]
+    fn test_effects_stay_inside_shaped_lens() {
+        let protanopia = CvdFilter {
+            kind: Some(CvdKind::Protanopia),
+            mode: CvdMode::Simulate,
+        };
+        let [r, g, b] = protanopia.compile()([255, 0, 0]);
+        let simulated = 0xff00_0000 | (r as u32) << 16 | (g as u32) << 8 | b as u32;
+        assert_ne!(simulated, 0xffff_0000);
+
+        // Colour, CVD and text filters each over an opaque frozen
+        // background: (renderer, background, filtered lens centre)
+        let cases = [
+            (
+                Renderer {
+                    shape: LensShape::Circle,
+                    color: ColorFilter {
+                        mode: ColorMode::Invert,
+                        ..ColorFilter::default()
+                    },
+                    ..Renderer::new()
+                },
+                0xff40_4040,
+                0xffbf_bfbf,
+            ),
+            (
+                Renderer {
+                    shape: LensShape::Ellipse,
+                    cvd: protanopia,
+                    ..Renderer::new()
+                },
+                0xffff_0000,
+                simulated,
+            ),
+            (
+                // A flat background is all paper inside the lens
+                Renderer {
+                    shape: LensShape::Rounded(20.0),
+                    text: TextFilter {
+                        reading: ReadingMode::DarkOnLight,
+                        ..TextFilter::default()
+                    },
+                    ..Renderer::new()
+                },
+                0xff80_8080,
+                0xffff_ffff,
+            ),
+        ];
+
+        for (renderer, fill, filtered) in cases {
+            // The corner lies outside every shape and keeps the background
+            let (corner, center) = lens_effect_pixels(&renderer, fill);
+            assert_eq!(corner, fill, "{:?}", renderer.shape);
+            assert_eq!(center, filtered, "{:?}", renderer.shape);
+        }
+    }
+
+    #[test]
+    fn test_effective_filter() {
+        let mut renderer = Renderer::new();
+
+        // Auto is smooth at 2x and nearest from the 4x threshold up
+        renderer.set_zoom(0.5);
+        assert_eq!(renderer.effective_filter(), ScaleFilter::Bicubic);
+        renderer.set_zoom(0.25);
+        assert_eq!(renderer.effective_filter(), ScaleFilter::Nearest);
+
+        renderer.filter_threshold = 10.0;
+        assert_eq!(renderer.effective_filter(), ScaleFilter::Bicubic);
+
+        // Explicit filters ignore the zoom level
+        renderer.filter = ScaleFilter::Xbr;
+        renderer.set_zoom(0.5);
+        assert_eq!(renderer.effective_filter(), ScaleFilter::Xbr);
+    }
+
+    #[test]
+    fn test_sampling_matches_lens_transform() {
+        // The lens centre shows the source pixel; zoom spreads the rest out
+        let sampling =
+            Sampling::around(Vector2D::new(500.0, 300.0), Vector2D::new(100.0, 50.0), 0.5);
+        assert_eq!(
+            sampling.apply(Vector2D::new(100.0, 50.0)),
+            Vector2D::new(500.0, 300.0)
+        );
+        assert_eq!(
+            sampling.apply(Vector2D::new(120.0, 50.0)),
+            Vector2D::new(510.0, 300.0)
+        );
+
+        assert_eq!(
+            sampling.invert(Vector2D::new(510.0, 300.0)),
+            Vector2D::new(120.0, 50.0)
+        );
+        // A pointer part-way into the centre pixel lands that far into its
+        // magnified copy, which is where the drawn cursor's hotspot goes
+        assert_eq!(
+            sampling.invert(Vector2D::new(500.5, 300.25)),
+            Vector2D::new(101.0, 50.5)
+        );
+
+        let (x, y) = sampling.matrix().transform_point(120.0, 50.0);
+        assert_eq!((x, y), (510.0, 300.0));
+
+        let area = Rect::from_center(Vector2D::new(100.0, 50.0), Vector2D::new(40.0, 20.0));
+        let source = sampling.source_rect(area);
+        assert_eq!(source.pos, Vector2D::new(490.0, 295.0));
+        assert_eq!(source.size, Vector2D::new(20.0, 10.0));
+    }
+
+    #[test]
+    fn test_cursor_follows_pointer_not_lens() {
+        // The smoothed lens still shows (100, 100) at 2x while the pointer
+        // has already moved 10 pixels right
+        let source = Vector2D::new(100.0, 100.0);
+        let pointer = Vector2D::new(110.0, 100.0);
+        let center = Vector2D::new(300.0, 200.0);
+        let sampling = Sampling::around(source, center, 0.5);
+
+        let to_capture = Vector2D::new(1.0, 1.0);
+        assert_eq!(cursor_hotspot(sampling, source, to_capture), center);
+        assert_eq!(
+            cursor_hotspot(sampling, pointer, to_capture),
+            Vector2D::new(320.0, 200.0)
+        );
+    }
+
+    #[test]
+    fn test_lens_center() {
+        let bounds = Rect::new(Vector2D::default(), Vector2D::new(1920.0, 1080.0));
+        let size = Vector2D::new(300.0, 150.0);
+        let centred = LensOffset::default();
+
+        // Away from the edges the lens sits on the pointer
+        let position = Vector2D::new(600.0, 400.0);
+        assert_eq!(lens_center(position, size, bounds, centred), position);
+
+        // Near an edge it is pushed back on screen
+        assert_eq!(
+            lens_center(Vector2D::new(10.0, 1075.0), size, bounds, centred),
+            Vector2D::new(150.0, 1005.0)
+        );
+
+        // A fixed offset leaves a gap to the near edge of the lens
+        let beside = LensOffset::Fixed(Vector2D::new(40.0, -40.0));
+        assert_eq!(
+            lens_center(position, size, bounds, beside),
+            Vector2D::new(790.0, 285.0)
+        );
+
+        // Auto heads for the middle of the screen
+        let auto = lens_center(Vector2D::new(1800.0, 100.0), size, bounds, LensOffset::Auto);
+        assert!(auto.x + size.x / 2.0 < 1800.0);
+        assert!(auto.y - size.y / 2.0 > 100.0);
+
+        // A lens larger than the screen is centred on it
+        let huge = Vector2D::new(4000.0, 100.0);
+        assert_eq!(lens_center(position, huge, bounds, centred).x, 960.0);
+    }
+
+    #[test]
+    fn test_viewport_origin() {
+        let output = Vector2D::new(1920.0, 1080.0);
+
+        // At the top-left corner the viewport starts at the origin
+        assert_eq!(
+            viewport_origin(Vector2D::new(0.0, 0.0), output, 0.5),
+            Vector2D::new(0.0, 0.0)
+        );
+
+        // At the bottom-right corner the viewport ends at the output edge
+        let origin = viewport_origin(output, output, 0.25);
+        assert_eq!(origin + output * 0.25, output);
+
+        // The point under the pointer maps to itself
+        let position = Vector2D::new(600.0, 300.0);
+        let zoom = 0.5;
+        let origin = viewport_origin(position, output, zoom);
+        assert_eq!(origin + position * zoom, position);
+
+        // No magnification means no panning
+        assert_eq!(
+            viewport_origin(position, output, 1.0),
+            Vector2D::new(0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn test_snapshot_source() {
+        let mut renderer = Renderer::new();
+        renderer.set_zoom(0.25);
+
+        // A 2x capture behind a lens on a logical 1920x1080 monitor
+        let screen = Vector2D::new(3840.0, 2160.0);
+        let space = Vector2D::new(1920.0, 1080.0);
+        let lens = Lens {
+            source: Vector2D::new(1000.0, 500.0),
+            center: Vector2D::new(1200.0, 600.0),
+            size: Vector2D::new(400.0, 200.0),
+            zoom: 0.25,
+        };
+        let source = renderer.lens_source(screen, space, &lens);
+        // A quarter of the lens in logical pixels, around the source point
+        assert_eq!(source.to_pixels(), (1950, 975, 100, 50));
+
+        // Full screen shows a quarter of the capture along each side
+        let source = renderer.viewport_source(screen, space, Vector2D::default());
+        assert_eq!(source.pos, Vector2D::default());
+        assert_eq!(source.size, Vector2D::new(960.0, 540.0));
     }
 }
@@ -0,0 +1,278 @@
+//! Smoothed pointer following.
+//!
+//! The lens chases a goal derived from the raw pointer. A dead zone keeps
+//! the goal still until the pointer has moved far enough, and an optional
+//! lead pushes it ahead along the pointer's recent velocity. Input events
+//! only move the pointer; the lens itself advances in `step`, once per
+//! drawn frame, so its motion is paced by the display rather than by how
+//! often the mouse reports.
+
+use crate::config::SmoothingMode;
+use crate::utils::Vector2D;
+use std::time::{Duration, Instant};
+
+/// Longest time step taken at once, so a stall doesn't become a jump.
+const MAX_STEP: f64 = 0.05;
+
+/// Time step assumed for the first frame after the lens came to rest.
+const FIRST_STEP: f64 = 1.0 / 60.0;
+
+/// Time constant of the pointer velocity estimate used for the lead.
+const VELOCITY_SMOOTHING: f64 = 0.05;
+
+/// Distance and speed below which the lens counts as settled.
+const SETTLE_DISTANCE: f64 = 0.05;
+const SETTLE_SPEED: f64 = 1.0;
+
+/// Smoothing settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Smoothing {
+    pub mode: SmoothingMode,
+    /// How quickly the lens catches up; roughly the time to cover two
+    /// thirds of the distance
+    pub time: Duration,
+    /// Radius the pointer can move in without moving the lens, in logical
+    /// pixels
+    pub dead_zone: f64,
+    /// How far ahead along the pointer's velocity the lens aims
+    pub lead: Duration,
+}
+
+impl Default for Smoothing {
+    fn default() -> Self {
+        Self {
+            mode: SmoothingMode::Off,
+            time: Duration::from_millis(80),
+            dead_zone: 0.0,
+            lead: Duration::ZERO,
+        }
+    }
+}
+
+/// Lens position following the pointer.
+#[derive(Debug, Clone)]
+pub struct Follower {
+    settings: Smoothing,
+    pointer: Vector2D,
+    /// Pointer position after the dead zone
+    goal: Vector2D,
+    position: Vector2D,
+    velocity: Vector2D,
+    pointer_velocity: Vector2D,
+    last_pointer: Vector2D,
+    last_step: Option<Instant>,
+}
+
+impl Follower {
+    pub fn new(settings: Smoothing, position: Vector2D) -> Self {
+        Self {
+            settings,
+            pointer: position,
+            goal: position,
+            position,
+            velocity: Vector2D::default(),
+            pointer_velocity: Vector2D::default(),
+            last_pointer: position,
+            last_step: None,
+        }
+    }
+
+    /// Jump straight to `position`, dropping any motion in progress.
+    pub fn reset(&mut self, position: Vector2D) {
+        *self = Self::new(self.settings, position);
+    }
+
+    /// Whether the lens tracks the pointer without easing, in which case
+    /// `set_pointer` moves it immediately and `step` does nothing.
+    pub fn is_instant(&self) -> bool {
+        self.settings.mode == SmoothingMode::Off && self.settings.lead.is_zero()
+    }
+
+    /// Record a new raw pointer position.
+    pub fn set_pointer(&mut self, pointer: Vector2D) {
+        self.pointer = pointer;
+
+        // Drag the goal along once the pointer reaches the edge of the zone
+        let offset = pointer - self.goal;
+        let distance = offset.length();
+        if distance > self.settings.dead_zone {
+            self.goal = pointer - offset * (self.settings.dead_zone / distance);
+        }
+
+        if self.is_instant() {
+            self.position = self.goal;
+        }
+    }
+
+    pub fn position(&self) -> Vector2D {
+        self.position
+    }
+
+    /// Advance the lens to `now`. Returns whether it is still moving.
+    pub fn step(&mut self, now: Instant) -> bool {
+        if self.is_instant() {
+            return false;
+        }
+
+        let dt = match self.last_step {
+            Some(last) => now
+                .saturating_duration_since(last)
+                .as_secs_f64()
+                .min(MAX_STEP),
+            None => FIRST_STEP,
+        };
+        self.last_step = Some(now);
+        if dt <= 0.0 {
+            return true;
+        }
+
+        // Velocity of the raw pointer, smoothed so single reports don't
+        // fling the lens
+        let raw = (self.pointer - self.last_pointer) / dt;
+        self.last_pointer = self.pointer;
+        let blend = 1.0 - (-dt / VELOCITY_SMOOTHING).exp();
+        self.pointer_velocity += (raw - self.pointer_velocity) * blend;
+
+        let target = self.goal + self.pointer_velocity * self.settings.lead.as_secs_f64();
+        let tau = self.settings.time.as_secs_f64().max(1e-3);
+
+        match self.settings.mode {
+            SmoothingMode::Off => self.position = target,
+            SmoothingMode::Exponential => {
+                let blend = 1.0 - (-dt / tau).exp();
+                self.position += (target - self.position) * blend;
+            }
+            SmoothingMode::Inertia => {
+                // Exact step of a critically damped spring: no overshoot,
+                // but the lens keeps some momentum through direction changes
+                let omega = 1.0 / tau;
+                let error = self.position - target;
+                let temp = (self.velocity + error * omega) * dt;
+                let decay = (-omega * dt).exp();
+                self.velocity = (self.velocity - temp * omega) * decay;
+                self.position = target + (error + temp) * decay;
+            }
+        }
+
+        let settled = (target - self.position).length() < SETTLE_DISTANCE
+            && self.velocity.length() < SETTLE_SPEED
+            && self.pointer_velocity.length() < SETTLE_SPEED;
+        if settled {
+            self.position = target;
+            self.velocity = Vector2D::default();
+            self.last_step = None;
+        }
+        !settled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(mode: SmoothingMode) -> Smoothing {
+        Smoothing {
+            mode,
+            ..Smoothing::default()
+        }
+    }
+
+    /// Step at 60 Hz for `frames` frames, returning the time reached.
+    fn run(follower: &mut Follower, start: Instant, frames: u32) -> Instant {
+        let frame = Duration::from_micros(16_667);
+        let mut now = start;
+        for _ in 0..frames {
+            now += frame;
+            follower.step(now);
+        }
+        now
+    }
+
+    #[test]
+    fn test_off_follows_immediately() {
+        let mut follower = Follower::new(Smoothing::default(), Vector2D::default());
+        assert!(follower.is_instant());
+        follower.set_pointer(Vector2D::new(10.0, 20.0));
+        assert_eq!(follower.position(), Vector2D::new(10.0, 20.0));
+        assert!(!follower.step(Instant::now()));
+    }
+
+    #[test]
+    fn test_dead_zone() {
+        let mut follower = Follower::new(
+            Smoothing {
+                dead_zone: 5.0,
+                ..Smoothing::default()
+            },
+            Vector2D::default(),
+        );
+
+        // Small jitter is absorbed
+        follower.set_pointer(Vector2D::new(3.0, -4.0));
+        assert_eq!(follower.position(), Vector2D::default());
+
+        // Past the edge the lens trails the pointer by the zone radius
+        follower.set_pointer(Vector2D::new(20.0, 0.0));
+        assert_eq!(follower.position(), Vector2D::new(15.0, 0.0));
+    }
+
+    #[test]
+    fn test_exponential_converges() {
+        let start = Instant::now();
+        let mut follower = Follower::new(settings(SmoothingMode::Exponential), Vector2D::default());
+        follower.set_pointer(Vector2D::new(100.0, 0.0));
+        assert_eq!(follower.position(), Vector2D::default());
+
+        // Moving but not there yet after one frame
+        assert!(follower.step(start));
+        let x = follower.position().x;
+        assert!(x > 0.0 && x < 50.0, "{}", x);
+
+        run(&mut follower, start, 120);
+        assert_eq!(follower.position(), Vector2D::new(100.0, 0.0));
+        assert!(!follower.step(start + Duration::from_secs(3)));
+    }
+
+    #[test]
+    fn test_inertia_does_not_overshoot() {
+        let start = Instant::now();
+        let mut follower = Follower::new(settings(SmoothingMode::Inertia), Vector2D::default());
+        follower.set_pointer(Vector2D::new(100.0, 0.0));
+
+        let frame = Duration::from_micros(16_667);
+        let mut now = start;
+        for _ in 0..240 {
+            now += frame;
+            follower.step(now);
+            assert!(follower.position().x <= 100.0);
+        }
+        assert_eq!(follower.position(), Vector2D::new(100.0, 0.0));
+    }
+
+    #[test]
+    fn test_lead_aims_ahead() {
+        let start = Instant::now();
+        let mut follower = Follower::new(
+            Smoothing {
+                lead: Duration::from_millis(100),
+                ..Smoothing::default()
+            },
+            Vector2D::default(),
+        );
+        assert!(!follower.is_instant());
+
+        // Pointer moving right at 600 px/s
+        let frame = Duration::from_micros(16_667);
+        let mut now = start;
+        for i in 1..=30 {
+            now += frame;
+            follower.set_pointer(Vector2D::new(i as f64 * 10.0, 0.0));
+            follower.step(now);
+        }
+        assert!(follower.position().x > 300.0, "{:?}", follower.position());
+
+        // Once the pointer stops the lead fades and the lens settles on it
+        run(&mut follower, now, 120);
+        assert_eq!(follower.position(), Vector2D::new(300.0, 0.0));
+    }
+}
@@ -0,0 +1,226 @@
+//! Saving the magnified view as PNG.
+//!
+//! A snapshot is either what the view shows, as drawn (lens shape, effects,
+//! grid and all), or the source area behind it at the capture's own
+//! resolution. Files are named after the UTC time they were taken so a
+//! directory of them sorts chronologically.
+
+use anyhow::{Context, Result};
+use cairo::ImageSurface;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// MIME type offered when a snapshot goes on the clipboard.
+pub const PNG_MIME_TYPE: &str = "image/png";
+
+/// What a snapshot contains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotKind {
+    /// The lens or view as drawn on screen
+    View,
+    /// The source area behind the view, unmagnified
+    Source,
+}
+
+impl SnapshotKind {
+    fn label(self) -> &'static str {
+        match self {
+            SnapshotKind::View => "view",
+            SnapshotKind::Source => "source",
+        }
+    }
+}
+
+/// Where snapshots go.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Snapshots {
+    /// Directory for the files; the pictures directory if unset
+    pub dir: Option<PathBuf>,
+    /// Also put each snapshot on the clipboard
+    pub clipboard: bool,
+}
+
+impl Snapshots {
+    /// Directory snapshots are written to.
+    pub fn directory(&self) -> PathBuf {
+        self.dir.clone().unwrap_or_else(pictures_dir)
+    }
+
+    /// Write `png` to a new timestamped file and return its path.
+    pub fn save(&self, png: &[u8], kind: SnapshotKind, time: SystemTime) -> Result<PathBuf> {
+        let dir = self.directory();
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+        let path = unique_path(&dir, &file_name(kind, time));
+        std::fs::write(&path, png)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// Encode `surface` as PNG.
+pub fn encode(surface: &ImageSurface) -> Result<Vec<u8>> {
+    let mut png = Vec::new();
+    surface
+        .write_to_png(&mut png)
+        .context("Failed to encode PNG")?;
+    Ok(png)
+}
+
+/// File name for a snapshot taken at `time`, e.g.
+/// `remagnify-20240131-235959.123-view.png`.
+pub fn file_name(kind: SnapshotKind, time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let time_of_day = secs % 86_400;
+
+    format!(
+        "remagnify-{:04}{:02}{:02}-{:02}{:02}{:02}.{:03}-{}.png",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day / 60 % 60,
+        time_of_day % 60,
+        since_epoch.subsec_millis(),
+        kind.label()
+    )
+}
+
+/// `name` in `dir`, numbered if a file of that name already exists.
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let path = dir.join(name);
+    if !path.exists() {
+        return path;
+    }
+    let stem = name.trim_end_matches(".png");
+    (1..)
+        .map(|n| dir.join(format!("{}-{}.png", stem, n)))
+        .find(|path| !path.exists())
+        .unwrap_or(path)
+}
+
+/// The user's pictures directory: `XDG_PICTURES_DIR` from the environment
+/// or `user-dirs.dirs`, then `~/Pictures`, then the working directory.
+fn pictures_dir() -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+
+    if let Some(dir) = std::env::var_os("XDG_PICTURES_DIR").filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir);
+    }
+
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|h| h.join(".config")));
+    if let (Some(config), Some(home)) = (config, &home) {
+        if let Ok(dirs) = std::fs::read_to_string(config.join("user-dirs.dirs")) {
+            if let Some(dir) = parse_user_dirs(&dirs, "XDG_PICTURES_DIR", home) {
+                return dir;
+            }
+        }
+    }
+
+    home.map(|h| h.join("Pictures"))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Look `key` up in the shell-style `user-dirs.dirs` format, where values
+/// are quoted and may start with `$HOME`.
+fn parse_user_dirs(contents: &str, key: &str, home: &Path) -> Option<PathBuf> {
+    contents.lines().find_map(|line| {
+        let (name, value) = line.trim().split_once('=')?;
+        if name != key {
+            return None;
+        }
+        let value = value.trim().trim_matches('"');
+        match value.strip_prefix("$HOME") {
+            Some(rest) => Some(home.join(rest.trim_start_matches('/'))),
+            None if value.starts_with('/') => Some(PathBuf::from(value)),
+            None => None,
+        }
+    })
+}
+
+/// Proleptic Gregorian date of the day `days` after 1970-01-01.
+///
+/// Howard Hinnant's `civil_from_days`, so timestamps need no date crate.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_file_name() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_706_745_599_123);
+        assert_eq!(
+            file_name(SnapshotKind::View, time),
+            "remagnify-20240131-235959.123-view.png"
+        );
+        assert_eq!(
+            file_name(SnapshotKind::Source, UNIX_EPOCH),
+            "remagnify-19700101-000000.000-source.png"
+        );
+
+        // Leap day and the turn of a century
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+    }
+
+    #[test]
+    fn test_parse_user_dirs() {
+        let home = Path::new("/home/user");
+        let dirs = "# comment\nXDG_DESKTOP_DIR=\"$HOME/Desktop\"\n\
+                    XDG_PICTURES_DIR=\"$HOME/Bilder\"\n";
+        assert_eq!(
+            parse_user_dirs(dirs, "XDG_PICTURES_DIR", home),
+            Some(PathBuf::from("/home/user/Bilder"))
+        );
+        assert_eq!(
+            parse_user_dirs("XDG_PICTURES_DIR=\"/srv/pics\"", "XDG_PICTURES_DIR", home),
+            Some(PathBuf::from("/srv/pics"))
+        );
+        assert_eq!(parse_user_dirs(dirs, "XDG_MUSIC_DIR", home), None);
+    }
+
+    #[test]
+    fn test_save_numbers_clashing_names() {
+        let dir = std::env::temp_dir().join(format!("remagnify-snapshot-{}", std::process::id()));
+        let snapshots = Snapshots {
+            dir: Some(dir.clone()),
+            clipboard: false,
+        };
+
+        let surface = ImageSurface::create(cairo::Format::ARgb32, 4, 3).unwrap();
+        let png = encode(&surface).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+
+        let first = snapshots
+            .save(&png, SnapshotKind::View, UNIX_EPOCH)
+            .unwrap();
+        let second = snapshots
+            .save(&png, SnapshotKind::View, UNIX_EPOCH)
+            .unwrap();
+        assert_ne!(first, second);
+        assert!(second.to_string_lossy().ends_with("-view-1.png"));
+        assert_eq!(std::fs::read(&second).unwrap(), png);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,3 +1,5 @@
+pub mod rect;
 pub mod vector;
 
+pub use rect::Rect;
 pub use vector::Vector2D;
@@ -0,0 +1,151 @@
+//! Axis-aligned rectangles.
+//!
+//! Provides a Rect type used for lens bounds, damage areas and capture
+//! regions.
+
+use super::Vector2D;
+
+/// An axis-aligned rectangle with floating-point position and size.
+///
+/// `pos` is the top-left corner; `size` is never negative for rectangles
+/// built through the constructors.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    /// Top-left corner
+    pub pos: Vector2D,
+    /// Width and height
+    pub size: Vector2D,
+}
+
+impl Rect {
+    /// Create a new rectangle from its top-left corner and size.
+    pub fn new(pos: Vector2D, size: Vector2D) -> Self {
+        Self { pos, size }
+    }
+
+    /// Create a rectangle of `size` centred on `center`.
+    pub fn from_center(center: Vector2D, size: Vector2D) -> Self {
+        Self {
+            pos: center - size / 2.0,
+            size,
+        }
+    }
+
+    /// Bottom-right corner.
+    pub fn end(&self) -> Vector2D {
+        self.pos + self.size
+    }
+
+    /// Intersection with `other`, or an empty rectangle if they don't overlap.
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let start = Vector2D::new(self.pos.x.max(other.pos.x), self.pos.y.max(other.pos.y));
+        let end = Vector2D::new(
+            self.end().x.min(other.end().x),
+            self.end().y.min(other.end().y),
+        );
+        Rect::new(
+            start,
+            Vector2D::new((end.x - start.x).max(0.0), (end.y - start.y).max(0.0)),
+        )
+    }
+
+    /// Smallest rectangle containing both `self` and `other`.
+    pub fn union(&self, other: &Rect) -> Rect {
+        let start = Vector2D::new(self.pos.x.min(other.pos.x), self.pos.y.min(other.pos.y));
+        let end = Vector2D::new(
+            self.end().x.max(other.end().x),
+            self.end().y.max(other.end().y),
+        );
+        Rect::new(start, end - start)
+    }
+
+    /// Whether `other` lies entirely inside the rectangle.
+    pub fn covers(&self, other: &Rect) -> bool {
+        other.pos.x >= self.pos.x
+            && other.pos.y >= self.pos.y
+            && other.end().x <= self.end().x
+            && other.end().y <= self.end().y
+    }
+
+    /// Grow the rectangle by `amount` on every side.
+    pub fn inflate(&self, amount: f64) -> Rect {
+        Rect::new(
+            self.pos - Vector2D::new(amount, amount),
+            self.size + Vector2D::new(amount, amount) * 2.0,
+        )
+    }
+
+    /// Whether `p` lies inside the rectangle; the far edges are outside.
+    pub fn contains(&self, p: Vector2D) -> bool {
+        p.x >= self.pos.x && p.y >= self.pos.y && p.x < self.end().x && p.y < self.end().y
+    }
+
+    /// Whether the rectangle has no area.
+    pub fn is_empty(&self) -> bool {
+        self.size.x <= 0.0 || self.size.y <= 0.0
+    }
+
+    /// Expand outwards to whole pixels: `(x, y, width, height)`.
+    pub fn to_pixels(self) -> (i32, i32, i32, i32) {
+        let start = self.pos.floor();
+        let end = self.end().ceil();
+        (
+            start.x as i32,
+            start.y as i32,
+            (end.x - start.x) as i32,
+            (end.y - start.y) as i32,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_center() {
+        let r = Rect::from_center(Vector2D::new(100.0, 50.0), Vector2D::new(40.0, 20.0));
+        assert_eq!(r.pos, Vector2D::new(80.0, 40.0));
+        assert_eq!(r.end(), Vector2D::new(120.0, 60.0));
+    }
+
+    #[test]
+    fn test_intersect() {
+        let a = Rect::new(Vector2D::new(0.0, 0.0), Vector2D::new(100.0, 100.0));
+        let b = Rect::new(Vector2D::new(50.0, 80.0), Vector2D::new(100.0, 100.0));
+        let i = a.intersect(&b);
+        assert_eq!(i.pos, Vector2D::new(50.0, 80.0));
+        assert_eq!(i.size, Vector2D::new(50.0, 20.0));
+
+        let c = Rect::new(Vector2D::new(200.0, 0.0), Vector2D::new(10.0, 10.0));
+        assert!(a.intersect(&c).is_empty());
+    }
+
+    #[test]
+    fn test_union_and_covers() {
+        let a = Rect::new(Vector2D::new(0.0, 10.0), Vector2D::new(20.0, 20.0));
+        let b = Rect::new(Vector2D::new(50.0, 0.0), Vector2D::new(10.0, 10.0));
+        let u = a.union(&b);
+        assert_eq!(u.pos, Vector2D::new(0.0, 0.0));
+        assert_eq!(u.end(), Vector2D::new(60.0, 30.0));
+
+        assert!(u.covers(&a) && u.covers(&b) && u.covers(&u));
+        assert!(!a.covers(&u));
+        assert!(!a.covers(&a.inflate(0.5)));
+    }
+
+    #[test]
+    fn test_contains() {
+        let r = Rect::new(Vector2D::new(10.0, 10.0), Vector2D::new(20.0, 10.0));
+        assert!(r.contains(Vector2D::new(10.0, 10.0)));
+        assert!(r.contains(Vector2D::new(29.9, 19.9)));
+        assert!(!r.contains(Vector2D::new(30.0, 15.0)));
+        assert!(!r.contains(Vector2D::new(5.0, 15.0)));
+    }
+
+    #[test]
+    fn test_to_pixels() {
+        let r = Rect::new(Vector2D::new(1.5, 2.2), Vector2D::new(3.0, 3.0));
+        assert_eq!(r.to_pixels(), (1, 2, 4, 4));
+    }
+}
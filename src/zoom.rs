@@ -0,0 +1,190 @@
+//! Zoom levels as magnification factors.
+//!
+//! Zooming is geometric: every scroll notch multiplies the magnification by
+//! the same ratio, so a notch feels the same at 2x as at 50x. With a preset
+//! ladder, scrolling climbs from rung to rung instead. The renderer and the
+//! app state keep the zoom as the fraction of the source shown
+//! (1 / magnification), which is what the sampling maths want; this module
+//! only deals in magnifications.
+
+/// Axis units of one mouse wheel notch, as libinput reports them.
+pub const SCROLL_NOTCH: f64 = 15.0;
+
+/// Highest magnification that can be configured.
+pub const MAX_MAGNIFICATION: f64 = 1000.0;
+
+/// Presets reached with the number keys when none are configured.
+pub const DEFAULT_PRESETS: [f64; 9] = [1.5, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0, 32.0];
+
+/// Relative slack when comparing a magnification with a rung, so rounding
+/// doesn't leave the zoom a hair below a rung it has reached.
+const RUNG_TOLERANCE: f64 = 1e-6;
+
+/// How the zoom moves and where it stops.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoomSteps {
+    /// Lowest magnification; 1.0 lets scrolling out close the magnifier
+    pub min: f64,
+    pub max: f64,
+    /// Magnification ratio per scroll notch, above 1
+    pub ratio: f64,
+    /// Magnifications reached with the number keys 1 to 9, ascending
+    pub presets: Vec<f64>,
+    /// Scroll from preset to preset instead of by `ratio`
+    pub ladder: bool,
+    /// Scroll not yet used up by a ladder step, in notches
+    remainder: f64,
+}
+
+impl Default for ZoomSteps {
+    fn default() -> Self {
+        Self {
+            min: 1.0,
+            max: 100.0,
+            ratio: 1.05,
+            presets: DEFAULT_PRESETS.to_vec(),
+            ladder: false,
+            remainder: 0.0,
+        }
+    }
+}
+
+impl ZoomSteps {
+    /// Steps between `min` and `max`. Given presets also become the ladder
+    /// that scrolling climbs; without them scrolling is geometric and the
+    /// number keys use the default presets.
+    pub fn new(min: f64, max: f64, ratio: f64, presets: Vec<f64>) -> Self {
+        let ladder = !presets.is_empty();
+        Self {
+            min,
+            max,
+            ratio,
+            presets: if ladder {
+                presets
+            } else {
+                DEFAULT_PRESETS.to_vec()
+            },
+            ladder,
+            remainder: 0.0,
+        }
+    }
+
+    pub fn clamp(&self, magnification: f64) -> f64 {
+        magnification.clamp(self.min, self.max)
+    }
+
+    /// Clamp a zoom fraction (1 / magnification) to the limits.
+    pub fn clamp_zoom(&self, zoom: f64) -> f64 {
+        zoom.clamp(1.0 / self.max, 1.0 / self.min)
+    }
+
+    /// Magnification after scrolling `notches` from `magnification`;
+    /// positive notches zoom in. On a ladder, partial notches add up until
+    /// they make a whole step.
+    pub fn scroll(&mut self, magnification: f64, notches: f64) -> f64 {
+        if !self.ladder {
+            return self.clamp(magnification * self.ratio.powf(notches));
+        }
+
+        self.remainder += notches;
+        let steps = self.remainder.trunc();
+        self.remainder -= steps;
+        self.climb(magnification, steps as i32)
+    }
+
+    /// Magnification of preset `n` (1-based), if there is one.
+    pub fn preset(&self, n: usize) -> Option<f64> {
+        let i = n.checked_sub(1)?;
+        self.presets.get(i).map(|&m| self.clamp(m))
+    }
+
+    /// Move `steps` rungs up (positive) or down the ladder. The floor is a
+    /// rung too, so the ladder always leads back out.
+    fn climb(&self, magnification: f64, steps: i32) -> f64 {
+        let mut rungs: Vec<f64> = self.presets.iter().map(|&m| self.clamp(m)).collect();
+        rungs.push(self.min);
+        rungs.sort_by(f64::total_cmp);
+        rungs.dedup();
+
+        let mut m = magnification;
+        for _ in 0..steps.unsigned_abs() {
+            let next = if steps > 0 {
+                rungs.iter().find(|&&r| r > m * (1.0 + RUNG_TOLERANCE))
+            } else {
+                rungs
+                    .iter()
+                    .rev()
+                    .find(|&&r| r < m * (1.0 - RUNG_TOLERANCE))
+            };
+            match next {
+                Some(&r) => m = r,
+                None => break,
+            }
+        }
+        m
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_geometric_scroll() {
+        let mut steps = ZoomSteps {
+            ratio: 1.25,
+            ..ZoomSteps::default()
+        };
+
+        // The same notch is the same ratio at any magnification
+        assert!((steps.scroll(2.0, 1.0) - 2.5).abs() < 1e-12);
+        assert!((steps.scroll(40.0, 1.0) - 50.0).abs() < 1e-12);
+        assert!((steps.scroll(2.5, -1.0) - 2.0).abs() < 1e-12);
+        // Half a notch is half the ratio, geometrically
+        assert!((steps.scroll(4.0, 0.5) - 4.0 * 1.25f64.sqrt()).abs() < 1e-12);
+
+        // Limits hold at both ends
+        assert_eq!(steps.scroll(90.0, 5.0), 100.0);
+        assert_eq!(steps.scroll(1.1, -5.0), 1.0);
+    }
+
+    #[test]
+    fn test_ladder_scroll() {
+        let mut steps = ZoomSteps {
+            min: 1.0,
+            max: 10.0,
+            presets: vec![1.5, 2.0, 4.0, 16.0],
+            ladder: true,
+            ..ZoomSteps::default()
+        };
+
+        assert_eq!(steps.scroll(2.0, 1.0), 4.0);
+        // Presets beyond the limits are clamped to them
+        assert_eq!(steps.scroll(4.0, 1.0), 10.0);
+        assert_eq!(steps.scroll(10.0, 1.0), 10.0);
+        // Between rungs, a step goes to the next one in that direction
+        assert_eq!(steps.scroll(3.0, -1.0), 2.0);
+        assert_eq!(steps.scroll(2.0, -2.0), 1.0);
+
+        // Smooth scrolling only climbs once a whole notch has built up
+        assert_eq!(steps.scroll(2.0, 0.4), 2.0);
+        assert_eq!(steps.scroll(2.0, 0.4), 2.0);
+        assert_eq!(steps.scroll(2.0, 0.4), 4.0);
+    }
+
+    #[test]
+    fn test_presets() {
+        let steps = ZoomSteps {
+            max: 20.0,
+            ..ZoomSteps::default()
+        };
+        assert_eq!(steps.preset(1), Some(1.5));
+        assert_eq!(steps.preset(4), Some(4.0));
+        assert_eq!(steps.preset(9), Some(20.0));
+        assert_eq!(steps.preset(0), None);
+        assert_eq!(steps.preset(10), None);
+
+        assert_eq!(steps.clamp_zoom(0.001), 0.05);
+        assert_eq!(steps.clamp_zoom(2.0), 1.0);
+    }
+}